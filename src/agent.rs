@@ -1,5 +1,7 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
@@ -8,16 +10,30 @@ use std::time::Duration;
 use crate::config::Config;
 use crate::error::{RepomanError, Result};
 use crate::hooks;
-use crate::metadata::Metadata;
+use crate::metadata::{Metadata, SyncTrigger};
 use crate::operations;
+use crate::storage;
+use crate::throttle;
 use crate::util;
 use crate::vault::Vault;
 
+/// Consecutive network failures (with no fetch succeeding in between) after
+/// which the agent assumes the network is gone
+const NETWORK_LOSS_FAILURES: u32 = 3;
+
+/// Seconds between reachability probes while syncing is paused
+const NETWORK_PROBE_SECS: u64 = 60;
+
 /// Get the path to the PID file
 pub fn pid_file_path(config: &Config) -> PathBuf {
     config.logs_dir.join("agent.pid")
 }
 
+/// Get the path to the agent's network state file (read by `agent status`)
+pub fn network_state_path(config: &Config) -> PathBuf {
+    config.logs_dir.join("agent-network.json")
+}
+
 /// Get the path to the agent log file
 pub fn log_file_path(config: &Config) -> PathBuf {
     config.logs_dir.join("agent.log")
@@ -105,8 +121,15 @@ pub fn start_agent(config: &Config) -> Result<()> {
     let log_file =
         fs::File::create(&log_path).map_err(|e| RepomanError::AgentSpawnError(e.to_string()))?;
 
-    // Spawn the agent process
-    let child = Command::new(&exe_path)
+    // Spawn the agent process, pinned to this profile so each profile runs its own agent
+    let mut cmd = Command::new(&exe_path);
+    if let Some(profile) = &config.active_profile {
+        cmd.arg("--profile").arg(profile);
+    }
+    if config.offline() {
+        cmd.arg("--offline");
+    }
+    let child = cmd
         .arg("agent")
         .arg("run")
         .stdout(Stdio::from(
@@ -160,15 +183,112 @@ pub fn get_agent_status(config: &Config) -> String {
         Some(pid) => {
             let log_path = log_file_path(config);
             format!(
-                "Agent is running (PID: {})\nLog file: {}",
+                "Agent is running (PID: {})\nLog file: {}\nNetwork: {}",
                 pid,
-                log_path.display()
+                log_path.display(),
+                NetworkState::load(config).describe(config)
             )
         }
         None => "Agent is not running".to_string(),
     }
 }
 
+/// What the agent knows about network reachability. Fetches failing with DNS or
+/// connect errors across hosts pause syncing until a probe reaches one again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkState {
+    /// Network failures since the last fetch that reached its host
+    pub consecutive_failures: u32,
+    /// Hosts those failures were against
+    pub failed_hosts: BTreeSet<String>,
+    pub last_error: Option<String>,
+    /// When syncing was paused (`None` = syncing normally)
+    pub paused_since: Option<DateTime<Utc>>,
+}
+
+impl NetworkState {
+    /// Load the state the running agent last saved (default if none)
+    pub fn load(config: &Config) -> Self {
+        fs::read_to_string(network_state_path(config))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, config: &Config) {
+        let result = serde_json::to_vec_pretty(self)
+            .map_err(std::io::Error::other)
+            .and_then(|json| storage::atomic_write(&network_state_path(config), &json));
+        if let Err(e) = result {
+            warn!("agent: failed to save network state: {}", e);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_since.is_some()
+    }
+
+    /// A fetch reached its host: the network is up
+    pub fn record_reachable(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Count a network failure against `host`. Returns true when this pauses
+    /// syncing: enough failures, spread over at least two of the `known_hosts`
+    /// (or the only one there is).
+    pub fn record_failure(&mut self, host: &str, error: &str, known_hosts: usize) -> bool {
+        self.consecutive_failures += 1;
+        self.failed_hosts.insert(host.to_string());
+        self.last_error = Some(error.to_string());
+        let hosts_needed = known_hosts.clamp(1, 2);
+        let pause = !self.is_paused()
+            && self.consecutive_failures >= NETWORK_LOSS_FAILURES
+            && self.failed_hosts.len() >= hosts_needed;
+        if pause {
+            self.paused_since = Some(Utc::now());
+        }
+        pause
+    }
+
+    /// One-line summary for `agent status`
+    pub fn describe(&self, config: &Config) -> String {
+        if config.offline() {
+            return "offline mode (syncs skipped)".to_string();
+        }
+        match self.paused_since {
+            Some(since) => format!(
+                "unreachable since {} ({}), syncs paused: {} failed fetches across {}{}",
+                since.format("%Y-%m-%d %H:%M:%S UTC"),
+                util::relative_time(&since),
+                self.consecutive_failures,
+                self.failed_hosts
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                self.last_error
+                    .as_ref()
+                    .map(|e| format!(" (last error: {})", e))
+                    .unwrap_or_default()
+            ),
+            None => "online".to_string(),
+        }
+    }
+}
+
+/// Whether any of `hosts` accepts a TCP connection on a git port
+fn probe_hosts(hosts: &BTreeSet<String>) -> bool {
+    use std::net::{TcpStream, ToSocketAddrs};
+    hosts.iter().any(|host| {
+        [443, 22, 9418, 80].iter().any(|port| {
+            let Ok(mut addrs) = (host.as_str(), *port).to_socket_addrs() else {
+                return false;
+            };
+            addrs.any(|addr| TcpStream::connect_timeout(&addr, Duration::from_secs(3)).is_ok())
+        })
+    })
+}
+
 /// Result of a single agent iteration.
 pub struct IterationResult {
     /// How many seconds to sleep before the next iteration.
@@ -181,19 +301,55 @@ pub async fn run_agent_iteration(
     config: &Config,
     heartbeat_secs: u64,
     last_heartbeat: &mut std::time::Instant,
+    network: &mut NetworkState,
 ) -> IterationResult {
     let default_interval: i64 = 3600;
     let mut earliest_due_secs: u64 = default_interval as u64;
 
+    // No fetches in offline mode, or while the network looks down
+    let syncing = if config.offline() {
+        debug!("agent: offline mode, skipping syncs");
+        false
+    } else if network.is_paused() {
+        let hosts = network.failed_hosts.clone();
+        let reachable = tokio::task::spawn_blocking(move || probe_hosts(&hosts))
+            .await
+            .unwrap_or(false);
+        if reachable {
+            info!("agent: network reachable again, resuming syncs");
+            println!("Network is back, resuming syncs");
+            network.record_reachable();
+            network.save(config);
+        } else {
+            debug!("agent: network still unreachable, syncs paused");
+            earliest_due_secs = earliest_due_secs.min(NETWORK_PROBE_SECS);
+        }
+        reachable
+    } else {
+        true
+    };
+
     match Vault::load(config) {
+        Ok(_) if !syncing => {}
         Ok(vault) => {
-            let repos = vault.get_all_names();
-            debug!("agent: checking {} vaulted repos", repos.len());
+            let repos: Vec<&str> = vault
+                .entries
+                .iter()
+                .filter(|e| !e.archived)
+                .map(|e| e.name.as_str())
+                .collect();
+            debug!(
+                "agent: checking {} vaulted repos (archived skipped)",
+                repos.len()
+            );
 
             let now = Utc::now();
+            let mut all_metadata = Metadata::load_all(config).unwrap_or_default();
 
             // Phase 1: collect which repos are due for sync (fast, sequential)
             let mut due_repos: Vec<String> = Vec::new();
+            // Host each repo fetches from, for telling network loss from a bad remote
+            let mut repo_hosts: HashMap<String, String> = HashMap::new();
 
             for repo_name in repos {
                 let pristine_path = config.pristines_dir.join(repo_name);
@@ -202,30 +358,42 @@ pub async fn run_agent_iteration(
                     continue;
                 }
 
-                let metadata = match Metadata::load(repo_name, config) {
-                    Ok(m) => m,
-                    Err(e) => {
-                        debug!("agent: failed to load metadata for '{}': {}", repo_name, e);
-                        continue;
-                    }
+                let Some(metadata) = all_metadata.remove(repo_name) else {
+                    debug!("agent: no metadata for '{}'", repo_name);
+                    continue;
                 };
+                if let Some(host) = metadata.default_url().and_then(throttle::host_of) {
+                    repo_hosts.insert(repo_name.to_string(), host);
+                }
 
-                let interval = config.effective_sync_interval(repo_name, &metadata) as i64;
+                if metadata.is_quarantined() {
+                    debug!("agent: skipping '{}' (quarantined)", repo_name);
+                    continue;
+                }
 
-                let due = if let Some(ref last_sync) = metadata.last_sync {
-                    let elapsed = (now - last_sync.timestamp).num_seconds();
-                    elapsed >= interval
-                } else {
-                    true // never synced — due immediately
-                };
+                let interval = config.effective_sync_interval(repo_name, &metadata);
 
-                if !due {
-                    if let Some(ref last_sync) = metadata.last_sync {
-                        let elapsed = (now - last_sync.timestamp).num_seconds();
-                        let remaining = (interval - elapsed).max(1) as u64;
-                        earliest_due_secs = earliest_due_secs.min(remaining);
+                // A failing repo is retried with exponential backoff instead of
+                // every interval; never synced means due immediately
+                let next_due = match metadata.sync_failures {
+                    Some(ref failures) => {
+                        Some(failures.next_retry(interval, config.max_backoff(repo_name)))
                     }
-                    debug!("agent: '{}' not yet due for sync", repo_name);
+                    None => metadata
+                        .last_sync
+                        .as_ref()
+                        .map(|s| s.timestamp + chrono::Duration::seconds(interval as i64)),
+                };
+
+                if let Some(next_due) = next_due
+                    && next_due > now
+                {
+                    let remaining = (next_due - now).num_seconds().max(1) as u64;
+                    earliest_due_secs = earliest_due_secs.min(remaining);
+                    debug!(
+                        "agent: '{}' not yet due for sync ({}s)",
+                        repo_name, remaining
+                    );
                     continue;
                 }
 
@@ -236,7 +404,12 @@ pub async fn run_agent_iteration(
             if !due_repos.is_empty() {
                 let config_clone = config.clone();
                 let max = config.max_parallel();
-                let results = util::run_parallel(due_repos, max, move |name| {
+                // Repos on a capped host queue for it without holding a parallel slot
+                let host_key = |name: &str| {
+                    let host = repo_hosts.get(name)?;
+                    Some((host.clone(), config.host_limit(host)?))
+                };
+                let results = util::run_parallel_keyed(due_repos, max, host_key, move |name| {
                     // Check for new tags
                     let new_tag = match operations::check_for_new_tag(name, &config_clone) {
                         Ok(Some(ref new_tag)) => {
@@ -256,27 +429,48 @@ pub async fn run_agent_iteration(
                     };
 
                     // Sync
-                    let sync_result = operations::sync_pristine(name, &config_clone);
+                    let sync_result = operations::sync_pristine_triggered(
+                        name,
+                        SyncTrigger::Agent,
+                        &config_clone,
+                    );
                     (sync_result, new_tag)
                 })
                 .await;
 
                 // Phase 3: run hooks sequentially (plugin manager is single-threaded)
+                let known_hosts = repo_hosts.values().collect::<HashSet<_>>().len();
+                let mut reached_network = false;
+                let mut network_failures = Vec::new();
                 for (name, result) in results {
                     match result {
                         Ok((sync_result, new_tag)) => {
                             if let Err(e) = sync_result {
                                 error!("agent: failed to sync '{}': {}", name, e);
                                 println!("Failed to sync {}: {}", name, e);
-                            } else if let Some(ref tag) = new_tag {
-                                println!("New tag found for {}: {}", name, tag);
-                                let pristine_path = config.pristines_dir.join(&name);
-                                let _ = hooks::run_post_sync_on_new_tag(
-                                    config,
-                                    &name,
-                                    &pristine_path,
-                                    tag,
-                                );
+                                // Network errors don't extend the streak
+                                if !e.is_network_error() {
+                                    report_failure_streak(config, &name);
+                                }
+                                match repo_hosts.get(&name) {
+                                    Some(host) if e.is_network_error() => {
+                                        network_failures.push((host.clone(), e.to_string()));
+                                    }
+                                    // The remote answered (or is local), so the network is up
+                                    _ => reached_network = true,
+                                }
+                            } else {
+                                reached_network = true;
+                                if let Some(ref tag) = new_tag {
+                                    println!("New tag found for {}: {}", name, tag);
+                                    let pristine_path = config.pristines_dir.join(&name);
+                                    let _ = hooks::run_post_sync_on_new_tag(
+                                        config,
+                                        &name,
+                                        &pristine_path,
+                                        tag,
+                                    );
+                                }
                             }
                         }
                         Err(e) => {
@@ -284,6 +478,11 @@ pub async fn run_agent_iteration(
                         }
                     }
                 }
+                track_network(network, reached_network, network_failures, known_hosts);
+                network.save(config);
+                if network.is_paused() {
+                    earliest_due_secs = earliest_due_secs.min(NETWORK_PROBE_SECS);
+                }
             }
         }
         Err(e) => {
@@ -328,6 +527,56 @@ pub async fn run_agent_iteration(
     IterationResult { sleep_secs }
 }
 
+/// Fold one iteration's sync outcomes into the network state, announcing a pause
+fn track_network(
+    network: &mut NetworkState,
+    reached_network: bool,
+    failures: Vec<(String, String)>,
+    known_hosts: usize,
+) {
+    if reached_network {
+        network.record_reachable();
+        return;
+    }
+    for (host, error) in failures {
+        if network.record_failure(&host, &error, known_hosts) {
+            warn!(
+                "agent: network unreachable ({} failures across {} host(s)), pausing syncs",
+                network.consecutive_failures,
+                network.failed_hosts.len()
+            );
+            println!("Network appears to be down, pausing syncs until it's reachable again");
+        }
+    }
+}
+
+/// Print how a failing repo will be retried, or that it was quarantined
+fn report_failure_streak(config: &Config, name: &str) {
+    let Ok(metadata) = Metadata::load(name, config) else {
+        return;
+    };
+    let Some(ref failures) = metadata.sync_failures else {
+        return;
+    };
+    if failures.quarantined_at.is_some() {
+        warn!(
+            "agent: '{}' quarantined after {} consecutive failures",
+            name, failures.consecutive
+        );
+        println!(
+            "Quarantined {} after {} consecutive failures (run 'repoman sync {} --reset-failures' to retry)",
+            name, failures.consecutive, name
+        );
+    } else {
+        let interval = config.effective_sync_interval(name, &metadata);
+        let backoff = failures.backoff_secs(interval, config.max_backoff(name));
+        info!(
+            "agent: '{}' failed {} time(s) in a row, retrying in {}s",
+            name, failures.consecutive, backoff
+        );
+    }
+}
+
 /// Run the agent main loop (called when agent starts)
 ///
 /// Uses per-repo `sync_interval` from metadata. Sleeps until the next repo
@@ -338,11 +587,15 @@ pub async fn run_agent_loop(config: &Config) -> Result<()> {
 
     let heartbeat_secs = config.agent_heartbeat_interval.unwrap_or(300);
     let mut last_heartbeat = std::time::Instant::now();
+    // Start from a clean slate; a previous run's pause may be long over
+    let mut network = NetworkState::default();
+    network.save(config);
 
     loop {
         info!("agent: polling cycle starting");
 
-        let result = run_agent_iteration(config, heartbeat_secs, &mut last_heartbeat).await;
+        let result =
+            run_agent_iteration(config, heartbeat_secs, &mut last_heartbeat, &mut network).await;
 
         info!(
             "agent: polling cycle complete, sleeping {}s",
@@ -355,28 +608,7 @@ pub async fn run_agent_loop(config: &Config) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::TempDir;
-
-    fn create_test_config() -> (TempDir, Config) {
-        let temp_dir = TempDir::new().unwrap();
-        let base = temp_dir.path().to_path_buf();
-        let config = Config {
-            vault_dir: base.join("vault"),
-            pristines_dir: base.join("pristines"),
-            clones_dir: base.join("clones"),
-            plugins_dir: base.join("plugins"),
-            logs_dir: base.join("logs"),
-            agent_heartbeat_interval: None,
-            json_output: None,
-            max_parallel: None,
-            repos: None,
-        };
-        fs::create_dir_all(&config.vault_dir).unwrap();
-        fs::create_dir_all(&config.pristines_dir).unwrap();
-        fs::create_dir_all(&config.clones_dir).unwrap();
-        fs::create_dir_all(&config.logs_dir).unwrap();
-        (temp_dir, config)
-    }
+    use crate::test_support::create_test_config;
 
     #[tokio::test]
     async fn test_agent_iteration_empty_vault() {
@@ -387,7 +619,13 @@ mod tests {
         vault.save(&config).unwrap();
 
         let mut last_heartbeat = std::time::Instant::now();
-        let result = run_agent_iteration(&config, 300, &mut last_heartbeat).await;
+        let result = run_agent_iteration(
+            &config,
+            300,
+            &mut last_heartbeat,
+            &mut NetworkState::default(),
+        )
+        .await;
 
         // Should return a sleep duration without error
         assert!(result.sleep_secs > 0);
@@ -413,7 +651,13 @@ mod tests {
         fs::create_dir_all(config.pristines_dir.join("test-repo")).unwrap();
 
         let mut last_heartbeat = std::time::Instant::now();
-        let result = run_agent_iteration(&config, 300, &mut last_heartbeat).await;
+        let result = run_agent_iteration(
+            &config,
+            300,
+            &mut last_heartbeat,
+            &mut NetworkState::default(),
+        )
+        .await;
 
         // Should return a sleep duration (repo not due since we just synced)
         assert!(result.sleep_secs > 0);
@@ -423,6 +667,109 @@ mod tests {
         assert_eq!(loaded.last_sync.as_ref().unwrap().sync_type, "auto");
     }
 
+    #[tokio::test]
+    async fn test_agent_iteration_backs_off_and_skips_quarantined() {
+        let (_temp, config) = create_test_config();
+
+        let mut vault = Vault::default();
+        for name in ["failing", "quarantined"] {
+            vault
+                .add_entry(name.to_string(), "file:///nonexistent".to_string())
+                .unwrap();
+            fs::create_dir_all(config.pristines_dir.join(name)).unwrap();
+        }
+        vault.save(&config).unwrap();
+
+        // Never synced, so both would be due without their failure streaks
+        let mut failing = Metadata::new(vec!["file:///nonexistent".to_string()]);
+        failing.record_sync_failure("timeout", 10);
+        failing.save("failing", &config).unwrap();
+        let mut quarantined = Metadata::new(vec!["file:///nonexistent".to_string()]);
+        quarantined.record_sync_failure("timeout", 1);
+        quarantined.save("quarantined", &config).unwrap();
+
+        let mut last_heartbeat = std::time::Instant::now();
+        let result = run_agent_iteration(
+            &config,
+            300,
+            &mut last_heartbeat,
+            &mut NetworkState::default(),
+        )
+        .await;
+        assert!(result.sleep_secs > 0);
+
+        for name in ["failing", "quarantined"] {
+            let loaded = Metadata::load(name, &config).unwrap();
+            assert!(loaded.sync_history.is_empty(), "{} was synced", name);
+            assert_eq!(loaded.sync_failures.unwrap().consecutive, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agent_iteration_offline_skips_syncs() {
+        let (_temp, mut config) = create_test_config();
+        config.network = Some(crate::config::NetworkConfig {
+            offline: Some(true),
+            ..Default::default()
+        });
+
+        let mut vault = Vault::default();
+        vault
+            .add_entry("test-repo".to_string(), "file:///nonexistent".to_string())
+            .unwrap();
+        vault.save(&config).unwrap();
+        // Never synced: due immediately when online
+        Metadata::new(vec!["file:///nonexistent".to_string()])
+            .save("test-repo", &config)
+            .unwrap();
+        fs::create_dir_all(config.pristines_dir.join("test-repo")).unwrap();
+
+        let mut last_heartbeat = std::time::Instant::now();
+        let mut network = NetworkState::default();
+        run_agent_iteration(&config, 300, &mut last_heartbeat, &mut network).await;
+
+        let loaded = Metadata::load("test-repo", &config).unwrap();
+        assert!(loaded.sync_history.is_empty());
+        assert!(loaded.sync_failures.is_none());
+        assert!(network.describe(&config).contains("offline"));
+    }
+
+    #[test]
+    fn test_network_state_pauses_on_failures_across_hosts() {
+        let mut network = NetworkState::default();
+        assert!(!network.record_failure("github.com", "could not resolve host", 3));
+        assert!(!network.record_failure("github.com", "could not resolve host", 3));
+        // Three failures, but all on one host while others are known
+        assert!(!network.record_failure("github.com", "could not resolve host", 3));
+        assert!(network.record_failure("gitlab.internal", "failed to connect", 3));
+        assert!(network.is_paused());
+        assert_eq!(network.failed_hosts.len(), 2);
+
+        track_network(&mut network, true, Vec::new(), 3);
+        assert!(!network.is_paused());
+        assert_eq!(network.consecutive_failures, 0);
+
+        // With a single host, its failures alone are enough
+        let failures = vec![("gitlab.internal".to_string(), "timed out".to_string()); 3];
+        track_network(&mut network, false, failures, 1);
+        assert!(network.is_paused());
+    }
+
+    #[test]
+    fn test_is_network_error() {
+        let dns = RepomanError::GitCommandFailed(
+            "fetch".to_string(),
+            "fatal: unable to access 'https://example.com/x/': Could not resolve host: example.com"
+                .to_string(),
+        );
+        assert!(dns.is_network_error());
+        let missing = RepomanError::GitError(git2::Error::from_str(
+            "failed to resolve path '/srv/x': No such file or directory",
+        ));
+        assert!(!missing.is_network_error());
+        assert!(!RepomanError::AuthenticationFailed("x".to_string()).is_network_error());
+    }
+
     #[test]
     fn test_agent_pid_file_path() {
         let (_temp, config) = create_test_config();
@@ -0,0 +1,18 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::operations;
+
+pub fn handle_archive(name: &str, compact: bool, config: &Config) -> Result<()> {
+    let entry = operations::archive_repo(name, compact, config)?;
+    println!(
+        "Archived '{}' (skipped by sync, update, refresh and the agent)",
+        entry.name
+    );
+    Ok(())
+}
+
+pub fn handle_unarchive(name: &str, config: &Config) -> Result<()> {
+    let entry = operations::unarchive_repo(name, config)?;
+    println!("Unarchived '{}'", entry.name);
+    Ok(())
+}
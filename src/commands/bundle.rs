@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::operations;
+use crate::util;
+
+pub fn handle_bundle_create(
+    name: &str,
+    since: Option<&str>,
+    output: Option<&Path>,
+    config: &Config,
+) -> Result<()> {
+    let summary = operations::create_bundle(name, since, output, config)?;
+    let kind = if summary.prerequisites == 0 {
+        "full".to_string()
+    } else {
+        format!("incremental, needs {} commit(s)", summary.prerequisites)
+    };
+    println!(
+        "Bundled {} ref(s) of {} into {} ({}, {})",
+        summary.refs,
+        name,
+        summary.path.display(),
+        util::format_bytes(summary.bytes),
+        kind
+    );
+    Ok(())
+}
+
+pub fn handle_bundle_apply(
+    file: &Path,
+    name: Option<&str>,
+    url: Option<&str>,
+    config: &Config,
+) -> Result<()> {
+    let applied = operations::apply_bundle(file, name, url, config)?;
+    if applied.registered {
+        println!("Added {} to vault", applied.name);
+    }
+    if applied.created {
+        println!(
+            "Pristine created for {} from {}",
+            applied.name,
+            file.display()
+        );
+    } else {
+        println!("Pristine {} updated from {}", applied.name, file.display());
+    }
+    println!("  {}", applied.report.summary());
+    Ok(())
+}
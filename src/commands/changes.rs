@@ -0,0 +1,82 @@
+use colored::Colorize;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::metadata::RefChangeKind;
+use crate::operations;
+use crate::util::{parse_since, relative_time};
+
+pub fn handle_changes(name: &str, since: Option<&str>, json: bool, config: &Config) -> Result<()> {
+    let since = since.map(parse_since).transpose()?;
+    let syncs = operations::list_changes(name, since, config)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&syncs)?);
+        return Ok(());
+    }
+
+    if syncs.is_empty() {
+        println!("No recorded changes for '{}'", name);
+        return Ok(());
+    }
+
+    for sync in &syncs {
+        println!(
+            "{} ({})  {}",
+            sync.timestamp.format("%Y-%m-%d %H:%M").to_string().cyan(),
+            relative_time(&sync.timestamp),
+            sync.summary
+        );
+        for changed in &sync.refs {
+            let change = &changed.change;
+            let short = |oid: &Option<String>| {
+                oid.as_deref()
+                    .map(|o| o[..o.len().min(7)].to_string())
+                    .unwrap_or_default()
+            };
+            let label = if change.is_tag() {
+                format!("tag {}", change.short_name())
+            } else {
+                change.short_name().to_string()
+            };
+            let commits = match change.commits {
+                0 => String::new(),
+                1 => " (1 commit)".to_string(),
+                n => format!(" ({} commits)", n),
+            };
+            match change.kind {
+                RefChangeKind::Created => {
+                    println!("  {} {}{}", "+".green(), label, commits);
+                }
+                RefChangeKind::Updated => println!(
+                    "  {} {}  {}..{}{}",
+                    "~".yellow(),
+                    label,
+                    short(&change.old),
+                    short(&change.new),
+                    commits
+                ),
+                RefChangeKind::ForceUpdated => println!(
+                    "  {} {}  {}...{} (forced){}",
+                    "!".red(),
+                    label,
+                    short(&change.old),
+                    short(&change.new),
+                    commits
+                ),
+                RefChangeKind::Deleted => {
+                    println!("  {} {}  was {}", "-".red(), label, short(&change.old));
+                }
+            }
+            for commit in &changed.log {
+                println!(
+                    "      {} {} {}",
+                    commit.id[..7].dimmed(),
+                    commit.summary,
+                    format!("({}, {})", commit.author, relative_time(&commit.time)).dimmed()
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -6,9 +6,17 @@ pub fn handle_clone(
     pristine: &str,
     clone_name: Option<String>,
     branch: Option<String>,
+    sparse: &[String],
+    sparse_profile: Option<&str>,
+    force: bool,
     config: &Config,
 ) -> Result<()> {
-    let clone_path = operations::clone_from_pristine(pristine, clone_name, branch, config)?;
+    let sparse = operations::sparse_paths_for(pristine, sparse, sparse_profile, config)?;
+    let clone_path =
+        operations::clone_from_pristine(pristine, clone_name, branch, &sparse, force, config)?;
+    if !sparse.is_empty() {
+        println!("Sparse checkout: {}", sparse.join(", "));
+    }
     println!("Clone created at: {}", clone_path.display());
     Ok(())
 }
@@ -41,6 +41,7 @@ pub fn handle_config(action: Option<&str>, config: &Config) -> Result<()> {
         _ => {
             // Print effective config
             println!("{}", "Effective Configuration:".bold());
+            println!("  profile:       {}", config.profile_name());
             println!("  vault_dir:     {}", config.vault_dir.display());
             println!("  pristines_dir: {}", config.pristines_dir.display());
             println!("  clones_dir:    {}", config.clones_dir.display());
@@ -51,6 +52,7 @@ pub fn handle_config(action: Option<&str>, config: &Config) -> Result<()> {
                 config.agent_heartbeat_interval.unwrap_or(300)
             );
             println!("  json_output:   {}", config.json_output.unwrap_or(false));
+            println!("  state_backend: {:?}", config.state_backend());
             if let Some(ref repos) = config.repos {
                 println!("  repos:");
                 for (name, rc) in repos {
@@ -0,0 +1,38 @@
+use colored::Colorize;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::operations;
+
+pub fn handle_dedupe(json: bool, config: &Config) -> Result<()> {
+    let groups = operations::find_duplicates(config)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&groups)?);
+        return Ok(());
+    }
+
+    if groups.is_empty() {
+        println!("No duplicate remotes in vault");
+        return Ok(());
+    }
+
+    for group in &groups {
+        println!("{}", group.remote.bold());
+        for (i, (name, url)) in group.entries.iter().enumerate() {
+            let marker = if i == 0 {
+                "keep".green()
+            } else {
+                "dup ".yellow()
+            };
+            println!("  {} {:<20} {}", marker, name.cyan(), url);
+        }
+    }
+    println!(
+        "\n{} remote(s) vaulted more than once. To merge, remove the duplicates \
+         ('repoman remove <name>') and alias their names to the kept entry \
+         ('repoman alias <kept> <name>').",
+        groups.len()
+    );
+    Ok(())
+}
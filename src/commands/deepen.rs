@@ -0,0 +1,32 @@
+use colored::Colorize;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::operations::{self, Deepen};
+
+pub fn handle_deepen(name: &str, how: &Deepen, json: bool, config: &Config) -> Result<()> {
+    let report = operations::deepen_pristine(name, how, config)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    match (&report.before, &report.after) {
+        (None, _) => println!("{} already has its full history", report.name.cyan()),
+        (Some(_), None) => println!("{} now has its full history", report.name.cyan()),
+        (Some(before), Some(after)) if after.depth <= before.depth => println!(
+            "{} already has {} ({})",
+            report.name.cyan(),
+            how,
+            after.summary()
+        ),
+        (Some(before), Some(after)) => println!(
+            "{} deepened from {} to {} commits ({})",
+            report.name.cyan(),
+            before.depth,
+            after.depth,
+            after.summary()
+        ),
+    }
+    Ok(())
+}
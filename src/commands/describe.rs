@@ -0,0 +1,40 @@
+use colored::Colorize;
+
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::operations::{self, EntryDetails};
+use crate::vault::Vault;
+
+pub fn handle_describe(name: &str, details: &EntryDetails, config: &Config) -> Result<()> {
+    let entry = if details.is_empty() {
+        Vault::load(config)?
+            .get_entry(name)
+            .cloned()
+            .ok_or_else(|| RepomanError::RepoNotInVault(name.to_string()))?
+    } else {
+        operations::describe_repo(name, details, config)?
+    };
+
+    let unset = || "-".dimmed().to_string();
+    println!("{}", entry.name.cyan());
+    println!(
+        "  {} {}",
+        "Description:".bold(),
+        entry.description.clone().unwrap_or_else(unset)
+    );
+    println!(
+        "  {} {}",
+        "Owner:".bold(),
+        entry.owner.clone().unwrap_or_else(unset)
+    );
+    match &entry.notes {
+        Some(notes) => {
+            println!("  {}", "Notes:".bold());
+            for line in notes.lines() {
+                println!("    {}", line);
+            }
+        }
+        None => println!("  {} {}", "Notes:".bold(), unset()),
+    }
+    Ok(())
+}
@@ -8,6 +8,7 @@ pub fn handle_destroy(
     all_clones: Option<String>,
     all_pristines: bool,
     stale: Option<u64>,
+    archive: bool,
     confirmed: bool,
     config: &Config,
 ) -> Result<()> {
@@ -39,7 +40,7 @@ pub fn handle_destroy(
             println!("Aborted.");
             return Ok(());
         }
-        operations::destroy_target(&target, config)?;
+        operations::destroy_target(&target, archive, config)?;
     } else {
         eprintln!(
             "Error: provide a target, --all-clones <name>, --all-pristines, or --stale <days>"
@@ -5,6 +5,7 @@ use std::path::PathBuf;
 use crate::config::Config;
 use crate::error::Result;
 use crate::metadata::Metadata;
+use crate::operations;
 use crate::vault::Vault;
 
 pub fn handle_doctor(config: &Config) -> Result<()> {
@@ -38,6 +39,7 @@ pub fn handle_doctor(config: &Config) -> Result<()> {
 
     // Check vault integrity
     let vault = Vault::load(config)?;
+    let all_metadata = Metadata::load_all(config)?;
     println!(
         "\n  {} {} repositories in vault",
         "OK".green(),
@@ -48,11 +50,13 @@ pub fn handle_doctor(config: &Config) -> Result<()> {
     let mut broken_pristines = 0;
     let mut broken_alternates = 0;
     let mut missing_clones = 0;
+    let mut clones_at_risk = 0;
+    let mut failing_syncs = 0;
     let mut total_clones = 0;
 
     for entry in &vault.entries {
         // Check metadata exists
-        let metadata = if let Ok(m) = Metadata::load(&entry.name, config) {
+        let metadata = if let Some(m) = all_metadata.get(&entry.name) {
             m
         } else {
             println!("  {} No metadata for '{}'", "WARN".yellow(), entry.name);
@@ -71,6 +75,29 @@ pub fn handle_doctor(config: &Config) -> Result<()> {
             broken_pristines += 1;
         }
 
+        // Check sync failures
+        if let Some(ref failures) = metadata.sync_failures {
+            if failures.quarantined_at.is_some() {
+                println!(
+                    "  {} '{}' quarantined after {} failed syncs: {} (run 'repoman sync {} --reset-failures')",
+                    "ERROR".red(),
+                    entry.name,
+                    failures.consecutive,
+                    failures.last_error,
+                    entry.name
+                );
+            } else {
+                println!(
+                    "  {} '{}' failed its last {} sync(s): {}",
+                    "WARN".yellow(),
+                    entry.name,
+                    failures.consecutive,
+                    failures.last_error
+                );
+            }
+            failing_syncs += 1;
+        }
+
         // Check clones
         for clone in &metadata.clones {
             total_clones += 1;
@@ -85,6 +112,22 @@ pub fn handle_doctor(config: &Config) -> Result<()> {
                 continue;
             }
 
+            // Check that gc in the pristine can't prune what the clone borrows
+            if pristine_path.exists()
+                && let Ok(unpinned) =
+                    operations::unpinned_commits(&pristine_path, &clone.name, &clone.path)
+                && !unpinned.is_empty()
+            {
+                println!(
+                    "  {} Clone '{}' uses {} commit(s) the pristine doesn't pin; gc could break it (run 'repoman update {}')",
+                    "WARN".yellow(),
+                    clone.name,
+                    unpinned.len(),
+                    entry.name
+                );
+                clones_at_risk += 1;
+            }
+
             // Check alternates
             let alt_file = clone
                 .path
@@ -128,8 +171,13 @@ pub fn handle_doctor(config: &Config) -> Result<()> {
     }
 
     // Summary
-    let total_issues =
-        issues + orphan_metadata + broken_pristines + broken_alternates + missing_clones;
+    let total_issues = issues
+        + orphan_metadata
+        + broken_pristines
+        + broken_alternates
+        + missing_clones
+        + clones_at_risk
+        + failing_syncs;
     println!();
     println!(
         "  {} repos, {} clones, {} issues",
@@ -145,6 +193,9 @@ pub fn handle_doctor(config: &Config) -> Result<()> {
             "  {}",
             format!("{} issue(s) found", total_issues).yellow().bold()
         );
+        if broken_pristines + broken_alternates > 0 {
+            println!("  Run 'repoman verify' to check objects and repair broken pristines");
+        }
     }
 
     Ok(())
@@ -3,17 +3,23 @@ use crate::error::Result;
 use crate::operations;
 use crate::util;
 
-pub fn handle_gc(days: u64, dry_run: bool, confirmed: bool, config: &Config) -> Result<()> {
+pub fn handle_gc(
+    days: u64,
+    dry_run: bool,
+    tags: &[String],
+    confirmed: bool,
+    config: &Config,
+) -> Result<()> {
     if dry_run {
         // Dry-run mode: just show what would be done
-        let report = operations::run_gc(days, true, config)?;
+        let report = operations::run_gc(days, true, tags, config)?;
         print_gc_report(&report, days, true);
         return Ok(());
     }
 
     if !confirmed {
         // Preview what will be done first
-        let report = operations::run_gc(days, true, config)?;
+        let report = operations::run_gc(days, true, tags, config)?;
         print_gc_report(&report, days, true);
 
         if report.stale_clones.is_empty() {
@@ -26,7 +32,7 @@ pub fn handle_gc(days: u64, dry_run: bool, confirmed: bool, config: &Config) ->
         }
     }
 
-    let report = operations::run_gc(days, false, config)?;
+    let report = operations::run_gc(days, false, tags, config)?;
     print_gc_report(&report, days, false);
 
     Ok(())
@@ -0,0 +1,41 @@
+use colored::Colorize;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::operations;
+
+pub fn handle_history(limit: Option<usize>, json: bool, config: &Config) -> Result<()> {
+    let items = operations::list_history(limit, config)?;
+
+    if json {
+        let json_str = serde_json::to_string_pretty(&items)
+            .map_err(|e| crate::error::RepomanError::ConfigError(e.to_string()))?;
+        println!("{}", json_str);
+        return Ok(());
+    }
+
+    if items.is_empty() {
+        println!("No journal entries");
+        return Ok(());
+    }
+
+    for item in &items {
+        let marker = if item.undone {
+            " (undone)".dimmed().to_string()
+        } else if item.undoable {
+            " [undoable]".green().to_string()
+        } else {
+            String::new()
+        };
+        println!(
+            "{:>5}  {}  {:<8} {:<8} {}{}",
+            format!("#{}", item.entry.id).cyan(),
+            item.entry.timestamp.format("%Y-%m-%d %H:%M"),
+            item.entry.user,
+            item.entry.operation,
+            item.entry.change.summary(),
+            marker
+        );
+    }
+    Ok(())
+}
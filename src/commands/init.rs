@@ -1,19 +1,27 @@
 use crate::config::Config;
 use crate::error::Result;
 use crate::operations;
+use crate::throttle;
 use crate::util;
 
 pub async fn handle_init(
     vault_name: Option<String>,
     depth: Option<i32>,
+    filter: Option<String>,
+    tags: &[String],
     config: &Config,
 ) -> Result<()> {
+    if let Some(ref f) = filter {
+        operations::validate_filter(f)?;
+    }
+    config.ensure_online("initialize pristines")?;
+
     if let Some(name) = vault_name {
         // Initialize single repo
-        operations::init_pristine(&name, depth, config)?;
+        operations::init_pristine(&name, depth, filter.as_deref(), config)?;
     } else {
         // Initialize all uninitialized repos in parallel
-        let uninitialized = operations::get_uninitialized_repos(config)?;
+        let uninitialized = operations::get_uninitialized_repos(tags, config)?;
 
         if uninitialized.is_empty() {
             println!("All vaulted repositories already have pristines");
@@ -25,11 +33,12 @@ pub async fn handle_init(
             uninitialized.len()
         );
 
-        let config = config.clone();
+        let config_clone = config.clone();
         let max = config.max_parallel();
-        let results = util::run_parallel(uninitialized, max, move |name| {
-            // For parallel init-all, use CLI depth or fall back to per-repo config
-            operations::init_pristine(name, depth, &config)
+        let host_key = |name: &str| throttle::repo_host_limit(name, config);
+        let results = util::run_parallel_keyed(uninitialized, max, host_key, move |name| {
+            // For parallel init-all, use CLI depth/filter or fall back to per-repo config
+            operations::init_pristine(name, depth, filter.as_deref(), &config_clone)
         })
         .await;
 
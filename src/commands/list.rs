@@ -1,9 +1,9 @@
 use crate::config::Config;
 use crate::error::Result;
-use crate::operations::{format_repo_status, format_summary, list_all_repos};
+use crate::operations::{format_repo_status, format_summary, list_repos};
 
-pub fn handle_list(verbose: bool, json: bool, config: &Config) -> Result<()> {
-    let statuses = list_all_repos(config)?;
+pub fn handle_list(verbose: bool, tags: &[String], json: bool, config: &Config) -> Result<()> {
+    let statuses = list_repos(tags, config)?;
 
     if json {
         let json_str = serde_json::to_string_pretty(&statuses)
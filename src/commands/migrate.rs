@@ -0,0 +1,74 @@
+use crate::config::{Config, StateBackend};
+use crate::error::Result;
+use crate::operations;
+
+pub fn handle_migrate(
+    check: bool,
+    target: Option<StateBackend>,
+    json: bool,
+    config: &Config,
+) -> Result<()> {
+    if let Some(target) = target {
+        let copied = operations::convert_state(target, config)?;
+        let name = match target {
+            StateBackend::Json => "json",
+            StateBackend::Sqlite => "sqlite",
+        };
+        println!("Copied vault and metadata for {} repositories", copied);
+        println!(
+            "Set 'state_backend: {}' in config.yaml to start using it. The old state was left in place.",
+            name
+        );
+        return Ok(());
+    }
+
+    let report = if check {
+        operations::check_migrations(config)?
+    } else {
+        operations::run_migrations(config)?
+    };
+
+    if json {
+        let json_str = serde_json::to_string_pretty(&report)
+            .map_err(|e| crate::error::RepomanError::ConfigError(e.to_string()))?;
+        println!("{}", json_str);
+        return Ok(());
+    }
+
+    print_migration_report(&report, check);
+    Ok(())
+}
+
+fn print_migration_report(report: &operations::migrate::MigrationReport, check: bool) {
+    let prefix = if check { "[check] " } else { "" };
+
+    if report.files.is_empty() {
+        println!(
+            "{}All {} state file(s) are at the current schema version",
+            prefix, report.scanned
+        );
+        return;
+    }
+
+    for file in &report.files {
+        if file.too_new {
+            println!(
+                "{}{}: schema v{} is newer than supported v{} (left untouched)",
+                prefix, file.location, file.from_version, file.to_version
+            );
+            continue;
+        }
+        let verb = if check { "would migrate" } else { "migrated" };
+        println!(
+            "{}{}: {} v{} -> v{}",
+            prefix, file.location, verb, file.from_version, file.to_version
+        );
+        for step in &file.steps {
+            println!("    {}", step);
+        }
+    }
+
+    if !check && report.has_pending() {
+        println!("Backups kept alongside each file as <file>.v<N>.bak");
+    }
+}
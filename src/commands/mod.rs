@@ -1,41 +1,75 @@
 pub mod add;
 pub mod agent;
 pub mod alias;
+pub mod archive;
+pub mod bundle;
+pub mod changes;
 pub mod clone_cmd;
 pub mod config_cmd;
+pub mod dedupe;
+pub mod deepen;
+pub mod describe;
 pub mod destroy;
 pub mod doctor;
 pub mod export_import;
 pub mod gc;
+pub mod history;
 pub mod init;
 pub mod list;
+pub mod migrate;
 pub mod open;
+pub mod profile;
 pub mod refresh;
+pub mod remote;
 pub mod remove;
 pub mod rename;
 pub mod shell_init;
+pub mod sparse;
 pub mod status;
 pub mod sync;
+pub mod tag;
+pub mod track;
+pub mod undo;
 pub mod update;
 pub mod upgrade;
+pub mod vault;
+pub mod verify;
 
 pub use add::handle_add;
 pub use agent::handle_agent;
 pub use alias::{handle_alias, handle_alias_list};
+pub use archive::{handle_archive, handle_unarchive};
+pub use bundle::{handle_bundle_apply, handle_bundle_create};
+pub use changes::handle_changes;
 pub use clone_cmd::handle_clone;
 pub use config_cmd::handle_config;
+pub use dedupe::handle_dedupe;
+pub use deepen::handle_deepen;
+pub use describe::handle_describe;
 pub use destroy::handle_destroy;
 pub use doctor::handle_doctor;
 pub use export_import::{handle_export, handle_import};
 pub use gc::handle_gc;
+pub use history::handle_history;
 pub use init::handle_init;
 pub use list::handle_list;
+pub use migrate::handle_migrate;
 pub use open::handle_open;
+pub use profile::{handle_profile_create, handle_profile_list, handle_profile_use};
 pub use refresh::handle_refresh;
+pub use remote::{
+    handle_remote_add, handle_remote_list, handle_remote_remove, handle_remote_set_default,
+};
 pub use remove::handle_remove;
 pub use rename::handle_rename;
 pub use shell_init::handle_shell_init;
+pub use sparse::{handle_sparse_add, handle_sparse_list, handle_sparse_remove};
 pub use status::handle_status;
 pub use sync::handle_sync;
+pub use tag::handle_tag;
+pub use track::handle_track;
+pub use undo::handle_undo;
 pub use update::handle_update;
 pub use upgrade::handle_upgrade;
+pub use vault::{handle_vault_init, handle_vault_pull, handle_vault_push};
+pub use verify::handle_verify;
@@ -0,0 +1,103 @@
+use colored::Colorize;
+use std::path::PathBuf;
+
+use crate::config::{self, Config, DEFAULT_PROFILE, ProfileConfig};
+use crate::error::{RepomanError, Result};
+
+pub fn handle_profile_list(json: bool, config: &Config) -> Result<()> {
+    let active = config.profile_name();
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+    names.extend(config.profile_names());
+
+    if json {
+        let entries: Vec<serde_json::Value> = names
+            .iter()
+            .map(|name| {
+                let mut resolved = config.clone();
+                let _ = resolved.apply_profile(name);
+                serde_json::json!({
+                    "name": name,
+                    "active": name == active,
+                    "vault_dir": resolved.vault_dir,
+                    "pristines_dir": resolved.pristines_dir,
+                    "clones_dir": resolved.clones_dir,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    for name in &names {
+        let mut resolved = config.clone();
+        let _ = resolved.apply_profile(name);
+        let marker = if name == active { "*" } else { " " };
+        println!(
+            "{} {:<12} {}",
+            marker.green(),
+            name.cyan(),
+            resolved.clones_dir.display()
+        );
+    }
+    Ok(())
+}
+
+pub fn handle_profile_use(name: &str, config: &Config) -> Result<()> {
+    // Validate before persisting the choice
+    config.clone().apply_profile(name)?;
+
+    let path = config::selected_profile_path()
+        .ok_or_else(|| RepomanError::ConfigError("no config directory".to_string()))?;
+    if name == DEFAULT_PROFILE {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+    } else {
+        std::fs::create_dir_all(path.parent().unwrap_or(&path))?;
+        std::fs::write(&path, format!("{}\n", name))?;
+    }
+
+    println!("Using profile '{}'", name);
+    if std::env::var("REPOMAN_PROFILE").is_ok_and(|v| !v.is_empty() && v != name) {
+        println!(
+            "{} REPOMAN_PROFILE is set and takes precedence in this shell",
+            "Note:".yellow()
+        );
+    }
+    Ok(())
+}
+
+pub fn handle_profile_create(name: &str, root: Option<PathBuf>) -> Result<()> {
+    config::validate_profile_name(name)?;
+
+    let profile = root.map_or_else(ProfileConfig::default, |root| ProfileConfig {
+        vault_dir: Some(root.join("vault")),
+        pristines_dir: Some(root.join("pristines")),
+        clones_dir: Some(root.join("clones")),
+        logs_dir: Some(root.join("logs")),
+        state_backend: None,
+    });
+
+    let path = config::config_file_path()
+        .ok_or_else(|| RepomanError::ConfigError("no config directory".to_string()))?;
+    let contents = if path.exists() {
+        std::fs::read_to_string(&path)?
+    } else {
+        // Start from the top-level defaults so the file stays complete
+        serde_yml::to_string(&Config::load())
+            .map_err(|e| RepomanError::ConfigError(e.to_string()))?
+    };
+    let updated = config::add_profile_to_yaml(&contents, name, &profile)?;
+
+    std::fs::create_dir_all(path.parent().unwrap_or(&path))?;
+    std::fs::write(&path, updated)?;
+
+    let mut resolved = Config::load();
+    resolved.apply_profile(name)?;
+    println!("Created profile '{}' in {}", name, path.display());
+    println!("  vault_dir:     {}", resolved.vault_dir.display());
+    println!("  pristines_dir: {}", resolved.pristines_dir.display());
+    println!("  clones_dir:    {}", resolved.clones_dir.display());
+    println!("Switch to it with 'repoman profile use {}'", name);
+    Ok(())
+}
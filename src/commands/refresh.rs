@@ -1,12 +1,18 @@
 use crate::config::Config;
 use crate::error::Result;
 use crate::operations;
+use crate::throttle;
 use crate::util;
 
 /// Combined init + sync in one parallel pass.
 pub async fn handle_refresh(config: &Config) -> Result<()> {
-    let uninitialized = operations::get_uninitialized_repos(config)?;
-    let syncable = operations::get_syncable_repos(config)?;
+    if config.offline() {
+        println!("Offline mode: nothing fetched. Pristines are used as they are.");
+        return Ok(());
+    }
+
+    let uninitialized = operations::get_uninitialized_repos(&[], config)?;
+    let syncable = operations::get_syncable_repos(&[], config)?;
 
     if uninitialized.is_empty() && syncable.is_empty() {
         println!("Nothing to refresh — vault is empty or all repos are up to date.");
@@ -49,10 +55,11 @@ pub async fn handle_refresh(config: &Config) -> Result<()> {
     let config_clone = config.clone();
     let max = config.max_parallel();
 
-    let results = util::run_parallel(names, max, move |name| {
+    let host_key = |name: &str| throttle::repo_host_limit(name, config);
+    let results = util::run_parallel_keyed(names, max, host_key, move |name| {
         let action = action_map.get(name).copied().unwrap_or(Action::Sync);
         match action {
-            Action::Init => operations::init_pristine(name, None, &config_clone).map(|_| ()),
+            Action::Init => operations::init_pristine(name, None, None, &config_clone).map(|_| ()),
             Action::Sync => operations::sync_pristine(name, &config_clone),
         }
     })
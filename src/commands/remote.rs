@@ -0,0 +1,43 @@
+use colored::Colorize;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::operations;
+
+pub fn handle_remote_list(name: &str, json: bool, config: &Config) -> Result<()> {
+    let remotes = operations::list_repo_remotes(name, config)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&remotes)?);
+        return Ok(());
+    }
+
+    for (i, remote) in remotes.iter().enumerate() {
+        let marker = if i == 0 { "*".green() } else { " ".normal() };
+        println!("{} {:<12} {}", marker, remote.name, remote.url);
+    }
+    Ok(())
+}
+
+pub fn handle_remote_add(name: &str, remote: &str, url: &str, config: &Config) -> Result<()> {
+    operations::add_repo_remote(name, remote, url, config)?;
+    println!("Remote '{}' -> {} added to {}", remote, url, name);
+    println!("  Fetched into the pristine on the next sync");
+    Ok(())
+}
+
+pub fn handle_remote_remove(name: &str, remote: &str, config: &Config) -> Result<()> {
+    let removed = operations::remove_repo_remote(name, remote, config)?;
+    println!(
+        "Remote '{}' ({}) removed from {}",
+        removed.name, removed.url, name
+    );
+    Ok(())
+}
+
+pub fn handle_remote_set_default(name: &str, remote: &str, config: &Config) -> Result<()> {
+    operations::set_default_repo_remote(name, remote, config)?;
+    println!("'{}' is now the default remote of {}", remote, name);
+    println!("  The pristine mirrors its branches from the next sync on");
+    Ok(())
+}
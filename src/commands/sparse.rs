@@ -0,0 +1,36 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::operations;
+
+fn print_paths(clone: &str, paths: &[String]) {
+    if paths.is_empty() {
+        println!("{}: only top-level files are checked out", clone);
+    } else {
+        println!("{}:", clone);
+        for path in paths {
+            println!("  {}", path);
+        }
+    }
+}
+
+pub fn handle_sparse_list(clone: &str, json: bool, config: &Config) -> Result<()> {
+    let paths = operations::sparse_list(clone, config)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&paths)?);
+    } else {
+        print_paths(clone, &paths);
+    }
+    Ok(())
+}
+
+pub fn handle_sparse_add(clone: &str, paths: &[String], config: &Config) -> Result<()> {
+    let paths = operations::sparse_add(clone, paths, config)?;
+    print_paths(clone, &paths);
+    Ok(())
+}
+
+pub fn handle_sparse_remove(clone: &str, paths: &[String], config: &Config) -> Result<()> {
+    let paths = operations::sparse_remove(clone, paths, config)?;
+    print_paths(clone, &paths);
+    Ok(())
+}
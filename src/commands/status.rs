@@ -1,17 +1,60 @@
+use colored::Colorize;
+
 use crate::config::Config;
 use crate::error::Result;
+use crate::metadata::SyncAttempt;
 use crate::operations;
+use crate::util;
 
-pub fn handle_status(name: &str, json: bool, config: &Config) -> Result<()> {
-    let status = operations::get_detailed_status(name, config)?;
+pub fn handle_status(name: &str, history: bool, json: bool, config: &Config) -> Result<()> {
+    let mut status = operations::get_detailed_status(name, config)?;
 
     if json {
+        if !history {
+            status.sync_history.clear();
+        }
         let json_str = serde_json::to_string_pretty(&status)
             .map_err(|e| crate::error::RepomanError::ConfigError(e.to_string()))?;
         println!("{}", json_str);
     } else {
         println!("{}", status);
+        if history {
+            print_sync_history(&status.sync_history);
+        }
     }
 
     Ok(())
 }
+
+/// Sync attempts, newest first, one per line
+fn print_sync_history(attempts: &[SyncAttempt]) {
+    if attempts.is_empty() {
+        println!("  {}: none recorded", "Sync history".bold());
+        return;
+    }
+    println!("  {} ({}):", "Sync history".bold(), attempts.len());
+    for attempt in attempts.iter().rev() {
+        let outcome = if attempt.succeeded() {
+            "ok    ".green()
+        } else {
+            "FAILED".red()
+        };
+        let detail = match attempt.error {
+            Some(ref e) => e.red().to_string(),
+            None => match (attempt.received_bytes, attempt.received_objects) {
+                (Some(bytes), Some(objects)) => {
+                    format!("{}, {} objects", util::format_bytes(bytes), objects)
+                }
+                _ => String::new(),
+            },
+        };
+        println!(
+            "    {}  {:<7} {} {:>7.1}s  {}",
+            attempt.started.format("%Y-%m-%d %H:%M:%S"),
+            attempt.trigger.to_string(),
+            outcome,
+            attempt.duration_ms as f64 / 1000.0,
+            detail
+        );
+    }
+}
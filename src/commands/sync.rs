@@ -1,15 +1,51 @@
 use crate::config::Config;
 use crate::error::Result;
+use crate::metadata::SyncTrigger;
 use crate::operations;
+use crate::throttle;
 use crate::util;
 
-pub async fn handle_sync(pristine: Option<String>, config: &Config) -> Result<()> {
+pub async fn handle_sync(
+    pristine: Option<String>,
+    tags: &[String],
+    trigger: SyncTrigger,
+    reset_failures: bool,
+    config: &Config,
+) -> Result<()> {
     if let Some(name) = pristine {
         // Sync single repo
-        operations::sync_pristine(&name, config)?;
+        if reset_failures {
+            print_reset(&operations::reset_sync_failures(Some(&name), config)?);
+        }
+        operations::sync_pristine_triggered(&name, trigger, config)?;
     } else {
         // Sync all repos with pristines in parallel
-        let syncable = operations::get_syncable_repos(config)?;
+        let mut syncable = operations::get_syncable_repos(tags, config)?;
+
+        if config.offline() {
+            println!(
+                "Offline mode: skipping sync of {} repositories",
+                syncable.len()
+            );
+            return Ok(());
+        }
+
+        if reset_failures {
+            for name in &syncable {
+                print_reset(&operations::reset_sync_failures(Some(name), config)?);
+            }
+        } else {
+            // Quarantined repos wait for --reset-failures or a sync by name
+            let quarantined = operations::quarantined_repos(&syncable, config)?;
+            if !quarantined.is_empty() {
+                println!(
+                    "Skipping {} quarantined: {} (retry with --reset-failures)",
+                    quarantined.len(),
+                    quarantined.join(", ")
+                );
+                syncable.retain(|name| !quarantined.contains(name));
+            }
+        }
 
         if syncable.is_empty() {
             println!("No pristines to sync. Run 'repoman init' first.");
@@ -18,10 +54,11 @@ pub async fn handle_sync(pristine: Option<String>, config: &Config) -> Result<()
 
         println!("Syncing {} repositories in parallel...", syncable.len());
 
-        let config = config.clone();
+        let config_clone = config.clone();
         let max = config.max_parallel();
-        let results = util::run_parallel(syncable, max, move |name| {
-            operations::sync_pristine(name, &config)
+        let host_key = |name: &str| throttle::repo_host_limit(name, config);
+        let results = util::run_parallel_keyed(syncable, max, host_key, move |name| {
+            operations::sync_pristine_triggered(name, trigger, &config_clone)
         })
         .await;
 
@@ -50,3 +87,9 @@ pub async fn handle_sync(pristine: Option<String>, config: &Config) -> Result<()
 
     Ok(())
 }
+
+fn print_reset(names: &[String]) {
+    for name in names {
+        println!("Cleared sync failures for {}", name);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::operations;
+use crate::vault::Vault;
+
+pub fn handle_tag(name: &str, add: &[String], remove: &[String], config: &Config) -> Result<()> {
+    let entry = if add.is_empty() && remove.is_empty() {
+        Vault::load(config)?
+            .get_entry(name)
+            .cloned()
+            .ok_or_else(|| RepomanError::RepoNotInVault(name.to_string()))?
+    } else {
+        operations::tag_repo(name, add, remove, config)?
+    };
+
+    if entry.tags.is_empty() {
+        println!("'{}' has no tags", entry.name);
+    } else {
+        println!("{}: {}", entry.name, entry.tags.join(", "));
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::operations;
+
+fn print_patterns(name: &str, kind: &str, patterns: &[String]) {
+    if patterns.is_empty() {
+        println!("{}: all {}", name, kind);
+    } else {
+        println!("{}: {} {}", name, kind, patterns.join(", "));
+    }
+}
+
+pub fn handle_track(
+    name: &str,
+    patterns: &[String],
+    tags: bool,
+    remove: bool,
+    json: bool,
+    config: &Config,
+) -> Result<()> {
+    if patterns.is_empty() {
+        let (branches, tag_patterns) = operations::list_tracked(name, config)?;
+        if json {
+            let value = serde_json::json!({ "branches": branches, "tags": tag_patterns });
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        } else {
+            print_patterns(name, "branches", &branches);
+            print_patterns(name, "tags", &tag_patterns);
+        }
+        return Ok(());
+    }
+
+    let kind = if tags { "tags" } else { "branches" };
+    let result = operations::track_refs(name, patterns, tags, remove, config)?;
+    print_patterns(name, kind, &result);
+    println!("Run 'repoman sync {}' to apply", name);
+    Ok(())
+}
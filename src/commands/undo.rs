@@ -0,0 +1,18 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::operations;
+use crate::util;
+
+pub fn handle_undo(id: Option<u64>, confirmed: bool, config: &Config) -> Result<()> {
+    let entry = operations::resolve_undo_target(id, config)?;
+    let summary = entry.change.summary();
+
+    if !confirmed && !util::confirm(&format!("Undo #{} ({})?", entry.id, summary)) {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    operations::undo_entry(&entry, config)?;
+    println!("Undid #{}: {}", entry.id, summary);
+    Ok(())
+}
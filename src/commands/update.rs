@@ -3,11 +3,11 @@ use crate::error::Result;
 use crate::operations;
 use crate::util;
 
-pub async fn handle_update(name: Option<String>, config: &Config) -> Result<()> {
+pub async fn handle_update(name: Option<String>, tags: &[String], config: &Config) -> Result<()> {
     if let Some(name) = name {
         operations::update_repo(&name, config)?;
     } else {
-        let updatable = operations::get_updatable_repos(config)?;
+        let updatable = operations::get_updatable_repos(tags, config)?;
 
         if updatable.is_empty() {
             println!("No pristines to update. Run 'repoman init' first.");
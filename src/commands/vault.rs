@@ -0,0 +1,56 @@
+use colored::Colorize;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::operations;
+
+pub fn handle_vault_init(remote: Option<&str>, config: &Config) -> Result<()> {
+    operations::vault_init_git(remote, config)?;
+    println!("Vault at {} is now git-backed", config.vault_dir.display());
+    if let Some(url) = remote {
+        println!("  origin: {}", url);
+    }
+    Ok(())
+}
+
+pub fn handle_vault_push(config: &Config) -> Result<()> {
+    operations::vault_push(config)?;
+    println!("{} Vault pushed", "✓".green());
+    Ok(())
+}
+
+pub fn handle_vault_pull(init_new: bool, json: bool, config: &Config) -> Result<()> {
+    let result = operations::vault_pull(init_new, config)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    if result.up_to_date {
+        println!("Vault is already up to date");
+        return Ok(());
+    }
+
+    for name in &result.added {
+        println!("  {} {}", "+".green(), name);
+    }
+    for (old, new) in &result.renamed {
+        println!("  {} {} -> {}", "~".yellow(), old, new);
+    }
+    for name in &result.removed {
+        println!(
+            "  {} {} (local pristine and clones were left in place)",
+            "-".red(),
+            name
+        );
+    }
+    for conflict in &result.conflicts {
+        println!("  {} {}", "!".yellow(), conflict);
+    }
+    if !result.initialized.is_empty() {
+        println!("Initialized pristines: {}", result.initialized.join(", "));
+    }
+    println!("{} Vault merged", "✓".green());
+    Ok(())
+}
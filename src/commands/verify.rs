@@ -0,0 +1,102 @@
+use colored::Colorize;
+
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::operations::{self, ObjectCheck, Repair, VerifyReport};
+
+fn print_check(label: &str, check: &ObjectCheck) {
+    if check.is_ok() {
+        println!("  {} {}", "OK".green(), label);
+        return;
+    }
+    let mut problems = Vec::new();
+    if !check.missing.is_empty() {
+        problems.push(format!("{} missing object(s)", check.missing.len()));
+    }
+    if !check.errors.is_empty() {
+        problems.push(format!("{} error(s)", check.errors.len()));
+    }
+    println!("  {} {}: {}", "ERROR".red(), label, problems.join(", "));
+    // A broken pristine can report thousands of objects; the first few are enough
+    for line in check
+        .missing
+        .iter()
+        .map(|m| format!("missing {}", m))
+        .chain(check.errors.iter().cloned())
+        .take(5)
+    {
+        println!("      {}", line);
+    }
+}
+
+fn print_report(report: &VerifyReport) {
+    println!("{}", report.name.bold());
+    if let Some(repair) = report.repaired {
+        println!("  repaired by {}", repair);
+    }
+    print_check("pristine", &report.pristine);
+    for clone in &report.clones {
+        print_check(&format!("clone {}", clone.target), clone);
+    }
+}
+
+pub fn handle_verify(
+    name: Option<&str>,
+    quick: bool,
+    repair: Option<Repair>,
+    json: bool,
+    config: &Config,
+) -> Result<()> {
+    let results = match name {
+        Some(n) => vec![(
+            n.to_string(),
+            operations::verify_repo(n, quick, repair, config),
+        )],
+        None => operations::verify_all(quick, repair, config)?,
+    };
+
+    let mut reports = Vec::new();
+    let mut failed: Vec<String> = Vec::new();
+    for (name, result) in results {
+        match result {
+            Ok(report) => {
+                if !report.is_ok() {
+                    failed.push(name);
+                }
+                reports.push(report);
+            }
+            Err(e) => {
+                eprintln!("{} {}: {}", "ERROR".red(), name, e);
+                failed.push(name);
+            }
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        for report in &reports {
+            print_report(report);
+        }
+        println!();
+        println!(
+            "{} repositories verified, {} with problems",
+            reports.len(),
+            failed.len()
+        );
+        let pristine_broken = reports
+            .iter()
+            .any(|r| !r.pristine.is_ok() && r.repaired.is_none());
+        if pristine_broken {
+            println!(
+                "Repair a pristine with 'repoman verify <name> --repair refetch' (or --repair reclone)"
+            );
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(RepomanError::VerifyFailed(failed.join(", ")))
+    }
+}
@@ -1,8 +1,10 @@
-use log::debug;
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::error::RepomanError;
+
 /// Per-repo hook commands (lifecycle events). Keys match config.yaml under repos.<name>.hooks.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct HookConfig {
@@ -37,6 +39,9 @@ pub struct AuthOverride {
 pub struct CloneDefaults {
     pub branch: Option<String>,
     pub shallow: Option<bool>,
+    /// Partial clone filter for the pristine (`blob:none`, `tree:0`, `blob:limit=1m`)
+    #[serde(default)]
+    pub filter: Option<String>,
 }
 
 /// Per-repo config (hooks, build, auth, etc.). Keyed by repo name in config.yaml under repos.
@@ -53,8 +58,86 @@ pub struct RepoConfig {
     pub tags: Option<Vec<String>>,
     #[serde(default)]
     pub no_upstream_merge: Option<bool>,
+    /// Fallback URLs for the default remote, tried in order when it fails.
+    #[serde(default)]
+    pub mirrors: Option<Vec<String>>,
+    /// Named sets of directories for `repoman clone --sparse-profile`.
+    #[serde(default)]
+    pub sparse_profiles: Option<HashMap<String, Vec<String>>>,
+    /// Delete pristine branches and tags that were deleted upstream (default true).
+    #[serde(default)]
+    pub prune: Option<bool>,
+    /// Consecutive sync failures after which the agent quarantines the repo
+    /// (default 10, 0 = never).
+    #[serde(default)]
+    pub quarantine_after: Option<u32>,
+    /// Upper bound in seconds for the agent's retry backoff (default 86400).
+    #[serde(default)]
+    pub max_backoff: Option<u64>,
+}
+
+/// Limits on how hard pristine fetches hit the network.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct NetworkConfig {
+    /// Concurrent fetches allowed against any one host (default: only `max_parallel`).
+    #[serde(default)]
+    pub max_per_host: Option<usize>,
+    /// Per-host overrides of `max_per_host`, keyed by host name.
+    #[serde(default)]
+    pub hosts: Option<HashMap<String, usize>>,
+    /// Download ceiling shared by all fetches, in bytes per second (`5m`, `500k`).
+    #[serde(default)]
+    pub max_download_rate: Option<String>,
+    /// Work only from pristines: skip every fetch (also set by `--offline`).
+    #[serde(default)]
+    pub offline: Option<bool>,
+}
+
+/// Where vault and metadata state is persisted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateBackend {
+    /// `vault.json` plus one `<repo>/metadata.json` per repo (default).
+    #[default]
+    Json,
+    /// Single `state.db` SQLite database in vault_dir.
+    Sqlite,
+}
+
+/// How `repoman add` derives a vault name from a URL.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NamingStrategy {
+    /// Last path segment: `utils` (default).
+    #[default]
+    Short,
+    /// Path below the host: `alice/utils`.
+    Owner,
+    /// Host and path: `github.com/alice/utils`.
+    Host,
+    /// `utils`, or `alice/utils` when `utils` is already taken by another remote.
+    Auto,
 }
 
+/// A named profile: its own vault, pristines, clones and logs (and therefore its own agent).
+/// Unset directories default to `<parent of vault_dir>/profiles/<name>/<dir>`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ProfileConfig {
+    #[serde(default, deserialize_with = "deserialize_opt_path")]
+    pub vault_dir: Option<PathBuf>,
+    #[serde(default, deserialize_with = "deserialize_opt_path")]
+    pub pristines_dir: Option<PathBuf>,
+    #[serde(default, deserialize_with = "deserialize_opt_path")]
+    pub clones_dir: Option<PathBuf>,
+    #[serde(default, deserialize_with = "deserialize_opt_path")]
+    pub logs_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub state_backend: Option<StateBackend>,
+}
+
+/// Name that always refers to the top-level (non-profile) directories.
+pub const DEFAULT_PROFILE: &str = "default";
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
     #[serde(deserialize_with = "deserialize_path")]
@@ -77,6 +160,24 @@ pub struct Config {
     /// Per-repo overrides (hooks, etc.). Key = repo name as in vault/list.
     #[serde(default)]
     pub repos: Option<HashMap<String, RepoConfig>>,
+    /// State storage backend: "json" (default) or "sqlite".
+    #[serde(default)]
+    pub state_backend: Option<StateBackend>,
+    /// Naming strategy for new vault entries: "short" (default), "owner", "host" or "auto".
+    #[serde(default)]
+    pub naming: Option<NamingStrategy>,
+    /// Named profiles, selected with --profile / REPOMAN_PROFILE / `repoman profile use`.
+    #[serde(default)]
+    pub profiles: Option<HashMap<String, ProfileConfig>>,
+    /// Profile to use when none is selected otherwise.
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    /// Per-host fetch concurrency and download bandwidth limits.
+    #[serde(default)]
+    pub network: Option<NetworkConfig>,
+    /// Profile this config was resolved for (runtime only, never read from config.yaml).
+    #[serde(skip)]
+    pub active_profile: Option<String>,
 }
 
 fn deserialize_path<'de, D>(deserializer: D) -> Result<PathBuf, D::Error>
@@ -87,6 +188,14 @@ where
     Ok(expand_tilde(&s))
 }
 
+fn deserialize_opt_path<'de, D>(deserializer: D) -> Result<Option<PathBuf>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = Option::<String>::deserialize(deserializer)?;
+    Ok(s.as_deref().map(expand_tilde))
+}
+
 fn expand_tilde(path: &str) -> PathBuf {
     if let Some(stripped) = path.strip_prefix("~/") {
         let home = dirs::home_dir().expect("Could not determine home directory");
@@ -115,13 +224,252 @@ impl Default for Config {
             json_output: None,
             max_parallel: None,
             repos: None,
+            state_backend: None,
+            naming: None,
+            profiles: None,
+            default_profile: None,
+            network: None,
+            active_profile: None,
         }
     }
 }
 
+/// Path to `config.yaml`.
+pub fn config_file_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("repoman").join("config.yaml"))
+}
+
+/// File holding the profile chosen with `repoman profile use`.
+pub fn selected_profile_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("repoman").join("profile"))
+}
+
+/// Profile chosen with `repoman profile use`, if any.
+pub fn read_selected_profile() -> Option<String> {
+    let contents = std::fs::read_to_string(selected_profile_path()?).ok()?;
+    let name = contents.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Pick the profile to use: --profile > REPOMAN_PROFILE > `profile use` > default_profile.
+pub fn select_profile(
+    cli: Option<&str>,
+    env: Option<&str>,
+    saved: Option<&str>,
+    default: Option<&str>,
+) -> Option<String> {
+    [cli, env, saved, default]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Profile names may only contain ASCII letters, digits, '-' and '_'.
+pub fn validate_profile_name(name: &str) -> Result<(), RepomanError> {
+    if name.is_empty()
+        || name == DEFAULT_PROFILE
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(RepomanError::ConfigError(format!(
+            "invalid profile name '{}' (use letters, digits, '-' or '_'; '{}' is reserved)",
+            name, DEFAULT_PROFILE
+        )));
+    }
+    Ok(())
+}
+
+/// Insert a profile into config.yaml contents, keeping all other keys.
+///
+/// The entry is spliced into the text so comments and key order survive. Only
+/// when that isn't possible (a flow-style `profiles: {...}`) is the whole file
+/// re-serialized, which drops its comments; a warning says so.
+pub fn add_profile_to_yaml(
+    contents: &str,
+    name: &str,
+    profile: &ProfileConfig,
+) -> Result<String, RepomanError> {
+    let yaml_err = |e: serde_yml::Error| RepomanError::ConfigError(e.to_string());
+
+    let mut doc: serde_yml::Value = if contents.trim().is_empty() {
+        serde_yml::Value::Mapping(serde_yml::Mapping::new())
+    } else {
+        serde_yml::from_str(contents).map_err(yaml_err)?
+    };
+    let root = doc
+        .as_mapping_mut()
+        .ok_or_else(|| RepomanError::ConfigError("config.yaml is not a mapping".to_string()))?;
+
+    let profiles = root
+        .entry(serde_yml::Value::from("profiles"))
+        .or_insert_with(|| serde_yml::Value::Mapping(serde_yml::Mapping::new()));
+    if profiles.is_null() {
+        *profiles = serde_yml::Value::Mapping(serde_yml::Mapping::new());
+    }
+    let profiles = profiles
+        .as_mapping_mut()
+        .ok_or_else(|| RepomanError::ConfigError("'profiles' is not a mapping".to_string()))?;
+    if profiles.contains_key(name) {
+        return Err(RepomanError::ConfigError(format!(
+            "profile '{}' already exists",
+            name
+        )));
+    }
+
+    let mut entry = serde_yml::Mapping::new();
+    let dirs = [
+        ("vault_dir", &profile.vault_dir),
+        ("pristines_dir", &profile.pristines_dir),
+        ("clones_dir", &profile.clones_dir),
+        ("logs_dir", &profile.logs_dir),
+    ];
+    for (key, dir) in dirs {
+        if let Some(dir) = dir {
+            entry.insert(key.into(), dir.display().to_string().into());
+        }
+    }
+    if let Some(backend) = profile.state_backend {
+        entry.insert(
+            "state_backend".into(),
+            serde_yml::to_value(backend).map_err(yaml_err)?,
+        );
+    }
+    let mut block = serde_yml::Mapping::new();
+    block.insert(name.into(), serde_yml::Value::Mapping(entry.clone()));
+    let block = serde_yml::to_string(&block).map_err(yaml_err)?;
+    profiles.insert(name.into(), serde_yml::Value::Mapping(entry));
+
+    // Keep the splice only if it parses to exactly the intended config
+    if let Some(spliced) = splice_profile_entry(contents, &block)
+        && serde_yml::from_str::<serde_yml::Value>(&spliced).is_ok_and(|v| v == doc)
+    {
+        return Ok(spliced);
+    }
+    warn!(
+        "config: rewrote config.yaml to add profile '{}'; its comments were not kept",
+        name
+    );
+    serde_yml::to_string(&doc).map_err(yaml_err)
+}
+
+/// `contents` with `block` (a one-profile mapping) added under the top-level
+/// `profiles:` key, or under a new one at the end. `None` when `profiles` is
+/// written in flow style.
+fn splice_profile_entry(contents: &str, block: &str) -> Option<String> {
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    let indented =
+        |indent: &str| -> Vec<String> { block.lines().map(|l| format!("{indent}{l}")).collect() };
+
+    let Some(key) = lines.iter().position(|l| l.starts_with("profiles:")) else {
+        lines.push("profiles:".to_string());
+        lines.extend(indented("  "));
+        return Some(lines.join("\n") + "\n");
+    };
+    let value = lines[key]["profiles:".len()..].trim();
+    match value {
+        _ if value.is_empty() || value.starts_with('#') => {}
+        "~" | "null" | "{}" => lines[key] = "profiles:".to_string(),
+        _ => return None,
+    }
+
+    // The block is the indented lines below the key (blanks and comments may
+    // sit between them); the entry goes after its last one, at its indent
+    let is_entry = |l: &String| l.starts_with([' ', '\t']) && !l.trim_start().starts_with('#');
+    let block_lines: Vec<&String> = lines[key + 1..]
+        .iter()
+        .take_while(|l| l.trim().is_empty() || l.starts_with([' ', '\t', '#']))
+        .collect();
+    let len = block_lines
+        .iter()
+        .rposition(|l| is_entry(l))
+        .map_or(0, |i| i + 1);
+    let indent = block_lines
+        .iter()
+        .find(|l| is_entry(l))
+        .map_or("  ", |l| &l[..l.len() - l.trim_start().len()])
+        .to_string();
+    let at = key + 1 + len;
+    lines.splice(at..at, indented(&indent));
+    Some(lines.join("\n") + "\n")
+}
+
 impl Config {
+    /// Load config.yaml and apply the selected profile (see [`select_profile`]).
+    pub fn load_for_profile(cli_profile: Option<&str>) -> Result<Self, RepomanError> {
+        let mut config = Self::load();
+        let env = std::env::var("REPOMAN_PROFILE").ok();
+        let saved = read_selected_profile();
+        let selected = select_profile(
+            cli_profile,
+            env.as_deref(),
+            saved.as_deref(),
+            config.default_profile.as_deref(),
+        );
+        if let Some(name) = selected {
+            config.apply_profile(&name)?;
+        }
+        Ok(config)
+    }
+
+    /// Point the config at a named profile's directories.
+    /// `default` keeps the top-level directories.
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), RepomanError> {
+        if name == DEFAULT_PROFILE {
+            self.active_profile = None;
+            return Ok(());
+        }
+
+        let profile = self
+            .profiles
+            .as_ref()
+            .and_then(|p| p.get(name))
+            .cloned()
+            .ok_or_else(|| RepomanError::ProfileNotFound(name.to_string()))?;
+        let root = self.profile_root(name);
+
+        self.vault_dir = profile.vault_dir.unwrap_or_else(|| root.join("vault"));
+        self.pristines_dir = profile
+            .pristines_dir
+            .unwrap_or_else(|| root.join("pristines"));
+        self.clones_dir = profile.clones_dir.unwrap_or_else(|| root.join("clones"));
+        self.logs_dir = profile.logs_dir.unwrap_or_else(|| root.join("logs"));
+        if profile.state_backend.is_some() {
+            self.state_backend = profile.state_backend;
+        }
+        self.active_profile = Some(name.to_string());
+        debug!("config: using profile '{}'", name);
+        Ok(())
+    }
+
+    /// Default base directory for a profile's data.
+    pub fn profile_root(&self, name: &str) -> PathBuf {
+        self.vault_dir
+            .parent()
+            .map_or_else(|| PathBuf::from("profiles"), |p| p.join("profiles"))
+            .join(name)
+    }
+
+    /// Configured profile names, sorted.
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .profiles
+            .as_ref()
+            .map(|p| p.keys().cloned().collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Name of the active profile for display (`default` when none).
+    pub fn profile_name(&self) -> &str {
+        self.active_profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
     pub fn load() -> Self {
-        let config_path = dirs::config_dir().map(|p| p.join("repoman").join("config.yaml"));
+        let config_path = config_file_path();
 
         if let Some(ref path) = config_path
             && path.exists()
@@ -144,6 +492,16 @@ impl Config {
         self.max_parallel.map_or(8, |v| v as usize)
     }
 
+    /// Configured state backend (default JSON files).
+    pub fn state_backend(&self) -> StateBackend {
+        self.state_backend.unwrap_or_default()
+    }
+
+    /// Naming strategy for new vault entries (default: short).
+    pub fn naming_strategy(&self) -> NamingStrategy {
+        self.naming.unwrap_or_default()
+    }
+
     /// Return hooks for a repo if configured.
     pub fn hooks_for_repo(&self, repo_name: &str) -> Option<&HookConfig> {
         self.repos
@@ -180,6 +538,22 @@ impl Config {
             .or(metadata.default_branch.clone())
     }
 
+    /// URLs to fetch the pristine from, in order: the default remote, then its mirrors.
+    pub fn fetch_urls(&self, repo_name: &str, metadata: &crate::metadata::Metadata) -> Vec<String> {
+        let mut urls: Vec<String> = metadata
+            .default_url()
+            .map(String::from)
+            .into_iter()
+            .collect();
+        let mirrors = self.repo_config(repo_name).and_then(|r| r.mirrors.as_ref());
+        for mirror in mirrors.into_iter().flatten() {
+            if !urls.contains(mirror) {
+                urls.push(mirror.clone());
+            }
+        }
+        urls
+    }
+
     /// Effective auth config: merge config auth into metadata auth.
     pub fn effective_auth(
         &self,
@@ -206,6 +580,64 @@ impl Config {
             .unwrap_or(false)
     }
 
+    /// Whether sync prunes refs deleted upstream (default on).
+    pub fn prune_enabled(&self, repo_name: &str) -> bool {
+        self.repo_config(repo_name)
+            .and_then(|r| r.prune)
+            .unwrap_or(true)
+    }
+
+    /// Consecutive failures before the agent quarantines a repo (0 = never).
+    pub fn quarantine_after(&self, repo_name: &str) -> u32 {
+        self.repo_config(repo_name)
+            .and_then(|r| r.quarantine_after)
+            .unwrap_or(10)
+    }
+
+    /// Longest the agent waits between retries of a failing repo, in seconds.
+    pub fn max_backoff(&self, repo_name: &str) -> u64 {
+        self.repo_config(repo_name)
+            .and_then(|r| r.max_backoff)
+            .unwrap_or(86400)
+    }
+
+    /// Whether offline mode is on (`--offline` or `network.offline`).
+    pub fn offline(&self) -> bool {
+        self.network
+            .as_ref()
+            .and_then(|n| n.offline)
+            .unwrap_or(false)
+    }
+
+    /// Fail with `RepomanError::Offline` when `action` would need the network.
+    pub fn ensure_online(&self, action: &str) -> Result<(), RepomanError> {
+        if self.offline() {
+            Err(RepomanError::Offline(action.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Concurrent fetches allowed against `host` (`None` = no per-host limit).
+    pub fn host_limit(&self, host: &str) -> Option<usize> {
+        let network = self.network.as_ref()?;
+        network
+            .hosts
+            .as_ref()
+            .and_then(|hosts| hosts.get(host).copied())
+            .or(network.max_per_host)
+            .map(|n| n.max(1))
+    }
+
+    /// Global download ceiling in bytes per second (`None` = unthrottled).
+    pub fn download_rate_limit(&self) -> Result<Option<u64>, RepomanError> {
+        self.network
+            .as_ref()
+            .and_then(|n| n.max_download_rate.as_deref())
+            .map(crate::util::parse_byte_size)
+            .transpose()
+    }
+
     /// Whether JSON output is enabled (CLI flag or config default).
     pub fn json_enabled(&self, cli_json: bool) -> bool {
         cli_json || self.json_output.unwrap_or(false)
@@ -263,13 +695,13 @@ mod tests {
 
     #[test]
     fn test_config_yaml_parsing() {
-        let yaml = r#"
+        let yaml = r"
 vault_dir: ~/custom/vault
 pristines_dir: ~/custom/pristines
 clones_dir: ~/custom/clones
 plugins_dir: ~/custom/plugins
 logs_dir: ~/custom/logs
-"#;
+";
         let config: Config = serde_yml::from_str(yaml).unwrap();
         let home = dirs::home_dir().unwrap();
 
@@ -279,13 +711,13 @@ logs_dir: ~/custom/logs
 
     #[test]
     fn test_config_yaml_absolute_paths() {
-        let yaml = r#"
+        let yaml = r"
 vault_dir: /absolute/vault
 pristines_dir: /absolute/pristines
 clones_dir: /absolute/clones
 plugins_dir: /absolute/plugins
 logs_dir: /absolute/logs
-"#;
+";
         let config: Config = serde_yml::from_str(yaml).unwrap();
 
         assert_eq!(config.vault_dir, PathBuf::from("/absolute/vault"));
@@ -294,13 +726,13 @@ logs_dir: /absolute/logs
 
     #[test]
     fn test_config_yaml_without_repos() {
-        let yaml = r#"
+        let yaml = r"
 vault_dir: ~/custom/vault
 pristines_dir: ~/custom/pristines
 clones_dir: ~/custom/clones
 plugins_dir: ~/custom/plugins
 logs_dir: ~/custom/logs
-"#;
+";
         let config: Config = serde_yml::from_str(yaml).unwrap();
         assert!(config.repos.is_none());
         assert!(config.hooks_for_repo("any").is_none());
@@ -335,4 +767,126 @@ repos:
 
         assert!(config.hooks_for_repo("nonexistent").is_none());
     }
+
+    #[test]
+    fn test_config_yaml_network_limits() {
+        let yaml = r"
+vault_dir: ~/custom/vault
+pristines_dir: ~/custom/pristines
+clones_dir: ~/custom/clones
+plugins_dir: ~/custom/plugins
+logs_dir: ~/custom/logs
+network:
+  max_per_host: 4
+  hosts:
+    gitlab.internal: 2
+  max_download_rate: 5m
+";
+        let mut config: Config = serde_yml::from_str(yaml).unwrap();
+        assert_eq!(config.host_limit("gitlab.internal"), Some(2));
+        assert_eq!(config.host_limit("github.com"), Some(4));
+        assert_eq!(config.download_rate_limit().unwrap(), Some(5 * 1024 * 1024));
+
+        config.network.as_mut().unwrap().max_download_rate = Some("fast".to_string());
+        assert!(matches!(
+            config.download_rate_limit(),
+            Err(RepomanError::InvalidByteSize(_))
+        ));
+    }
+
+    #[test]
+    fn test_select_profile_precedence() {
+        assert_eq!(
+            select_profile(Some("cli"), Some("env"), Some("saved"), Some("dflt")).as_deref(),
+            Some("cli")
+        );
+        assert_eq!(
+            select_profile(None, Some("env"), Some("saved"), Some("dflt")).as_deref(),
+            Some("env")
+        );
+        assert_eq!(
+            select_profile(None, Some(""), Some("saved"), Some("dflt")).as_deref(),
+            Some("saved")
+        );
+        assert_eq!(
+            select_profile(None, None, None, Some("dflt")).as_deref(),
+            Some("dflt")
+        );
+        assert!(select_profile(None, None, None, None).is_none());
+    }
+
+    #[test]
+    fn test_apply_profile_dirs() {
+        let yaml = r"
+vault_dir: /base/vault
+pristines_dir: /base/pristines
+clones_dir: /base/clones
+plugins_dir: /base/plugins
+logs_dir: /base/logs
+profiles:
+  work:
+    clones_dir: /work/src
+    state_backend: sqlite
+  oss: {}
+";
+        let mut config: Config = serde_yml::from_str(yaml).unwrap();
+        assert_eq!(config.profile_names(), vec!["oss", "work"]);
+
+        config.apply_profile("work").unwrap();
+        assert_eq!(config.vault_dir, PathBuf::from("/base/profiles/work/vault"));
+        assert_eq!(config.clones_dir, PathBuf::from("/work/src"));
+        assert_eq!(config.logs_dir, PathBuf::from("/base/profiles/work/logs"));
+        assert_eq!(config.plugins_dir, PathBuf::from("/base/plugins"));
+        assert_eq!(config.state_backend(), StateBackend::Sqlite);
+        assert_eq!(config.profile_name(), "work");
+
+        let mut config: Config = serde_yml::from_str(yaml).unwrap();
+        assert!(matches!(
+            config.apply_profile("missing"),
+            Err(RepomanError::ProfileNotFound(_))
+        ));
+        config.apply_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(config.vault_dir, PathBuf::from("/base/vault"));
+    }
+
+    #[test]
+    fn test_add_profile_to_yaml() {
+        let yaml = "vault_dir: /base/vault\nmax_parallel: 4\n";
+        let profile = ProfileConfig {
+            clones_dir: Some(PathBuf::from("/work/src")),
+            ..ProfileConfig::default()
+        };
+        let out = add_profile_to_yaml(yaml, "work", &profile).unwrap();
+        let doc: serde_yml::Value = serde_yml::from_str(&out).unwrap();
+        assert_eq!(doc["max_parallel"].as_u64(), Some(4));
+        assert_eq!(
+            doc["profiles"]["work"]["clones_dir"].as_str(),
+            Some("/work/src")
+        );
+
+        assert!(add_profile_to_yaml(&out, "work", &profile).is_err());
+
+        // Spliced in as text: comments, order and the other profiles are kept
+        let yaml = "# my setup\nmax_parallel: 4\nprofiles:\n    home:  # laptop\n        clones_dir: /home/src\n\n# agent\nagent_heartbeat_interval: 60\n";
+        let out = add_profile_to_yaml(yaml, "work", &profile).unwrap();
+        assert_eq!(
+            out,
+            "# my setup\nmax_parallel: 4\nprofiles:\n    home:  # laptop\n        clones_dir: /home/src\n    work:\n      clones_dir: /work/src\n\n# agent\nagent_heartbeat_interval: 60\n"
+        );
+        let out = add_profile_to_yaml("profiles: ~ # none yet\n", "work", &profile).unwrap();
+        assert_eq!(out, "profiles:\n  work:\n    clones_dir: /work/src\n");
+
+        // Flow style can't be spliced into; the file is rewritten instead
+        let out = add_profile_to_yaml("profiles: {home: {}}\n", "work", &profile).unwrap();
+        let doc: serde_yml::Value = serde_yml::from_str(&out).unwrap();
+        assert!(doc["profiles"]["home"].is_mapping());
+        assert_eq!(
+            doc["profiles"]["work"]["clones_dir"].as_str(),
+            Some("/work/src")
+        );
+
+        assert!(validate_profile_name("client-a").is_ok());
+        assert!(validate_profile_name("default").is_err());
+        assert!(validate_profile_name("a/b").is_err());
+    }
 }
@@ -16,7 +16,7 @@ use std::io;
 use crate::agent;
 use crate::config::Config;
 use crate::error::Result;
-use crate::metadata::Metadata;
+use crate::metadata::{Metadata, SyncAttempt};
 use crate::vault::Vault;
 
 struct RepoInfo {
@@ -26,8 +26,21 @@ struct RepoInfo {
     branches: Vec<String>,
     latest_tag: Option<String>,
     last_sync: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    owner: Option<String>,
+    notes: Option<String>,
+    archived: bool,
     clone_count: usize,
     clone_names: Vec<String>,
+    /// Recent sync attempts, oldest first
+    sync_history: Vec<SyncAttempt>,
+}
+
+impl RepoInfo {
+    fn last_sync_failed(&self) -> bool {
+        self.sync_history.last().is_some_and(|a| !a.succeeded())
+    }
 }
 
 struct DashboardApp {
@@ -40,6 +53,7 @@ struct DashboardApp {
 impl DashboardApp {
     fn new(config: &Config) -> Self {
         let vault = Vault::load(config).unwrap_or_default();
+        let mut all_metadata = Metadata::load_all(config).unwrap_or_default();
         let agent_running = agent::is_agent_running(config);
 
         let mut repos = Vec::new();
@@ -48,7 +62,7 @@ impl DashboardApp {
         for entry in &vault.entries {
             let pristine_path = config.pristines_dir.join(&entry.name);
             let has_pristine = pristine_path.exists();
-            let metadata = Metadata::load(&entry.name, config).ok();
+            let metadata = all_metadata.remove(&entry.name);
 
             let branches: Vec<String> = if has_pristine {
                 if let Ok(repo) = git2::Repository::open_bare(&pristine_path) {
@@ -79,6 +93,10 @@ impl DashboardApp {
                 .as_ref()
                 .and_then(|m| m.last_sync.as_ref())
                 .map(|s| format!("{}", s.timestamp.format("%Y-%m-%d %H:%M")));
+            let sync_history = metadata
+                .as_ref()
+                .map(|m| m.sync_history.clone())
+                .unwrap_or_default();
 
             repos.push(RepoInfo {
                 name: entry.name.clone(),
@@ -87,8 +105,14 @@ impl DashboardApp {
                 branches,
                 latest_tag,
                 last_sync,
+                description: entry.description.clone(),
+                tags: entry.tags.clone(),
+                owner: entry.owner.clone(),
+                notes: entry.notes.clone(),
+                archived: entry.archived,
                 clone_count,
                 clone_names,
+                sync_history,
             });
         }
 
@@ -168,7 +192,11 @@ fn ui(frame: &mut Frame, app: &mut DashboardApp) {
                         Color::Red
                     }),
                 ),
-                Span::raw(&r.name),
+                if r.last_sync_failed() {
+                    Span::styled(&r.name, Style::default().fg(Color::Yellow))
+                } else {
+                    Span::raw(&r.name)
+                },
             ]);
             ListItem::new(line)
         })
@@ -191,17 +219,34 @@ fn ui(frame: &mut Frame, app: &mut DashboardApp) {
 
     // Right pane: detail
     let detail_text = if let Some(repo) = app.selected_repo() {
-        let mut lines = vec![
-            Line::from(Span::styled(
-                &repo.name,
-                Style::default().add_modifier(Modifier::BOLD),
-            )),
-            Line::from(format!("URL: {}", repo.url)),
-            Line::from(format!(
-                "Pristine: {}",
-                if repo.has_pristine { "yes" } else { "no" }
-            )),
-        ];
+        let mut lines = vec![Line::from(Span::styled(
+            &repo.name,
+            Style::default().add_modifier(Modifier::BOLD),
+        ))];
+
+        if let Some(ref description) = repo.description {
+            lines.push(Line::from(Span::styled(
+                description.as_str(),
+                Style::default().add_modifier(Modifier::ITALIC),
+            )));
+        }
+        if repo.archived {
+            lines.push(Line::from(Span::styled(
+                "Archived",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        lines.push(Line::from(format!("URL: {}", repo.url)));
+        if let Some(ref owner) = repo.owner {
+            lines.push(Line::from(format!("Owner: {}", owner)));
+        }
+        if !repo.tags.is_empty() {
+            lines.push(Line::from(format!("Tags: {}", repo.tags.join(", "))));
+        }
+        lines.push(Line::from(format!(
+            "Pristine: {}",
+            if repo.has_pristine { "yes" } else { "no" }
+        )));
 
         if !repo.branches.is_empty() {
             lines.push(Line::from(format!(
@@ -218,6 +263,44 @@ fn ui(frame: &mut Frame, app: &mut DashboardApp) {
             lines.push(Line::from(format!("Last sync: {}", sync)));
         }
 
+        if !repo.sync_history.is_empty() {
+            let total = repo.sync_history.len();
+            let ok = repo.sync_history.iter().filter(|a| a.succeeded()).count();
+            let avg_ms =
+                repo.sync_history.iter().map(|a| a.duration_ms).sum::<u64>() / total as u64;
+            lines.push(Line::from(format!(
+                "Syncs: {}/{} ok, avg {:.1}s",
+                ok,
+                total,
+                avg_ms as f64 / 1000.0
+            )));
+            for attempt in repo.sync_history.iter().rev().take(5) {
+                let text = format!(
+                    "  {} {:<7} {:.1}s",
+                    attempt.started.format("%m-%d %H:%M"),
+                    attempt.trigger.to_string(),
+                    attempt.duration_ms as f64 / 1000.0
+                );
+                lines.push(match attempt.error {
+                    Some(ref e) => Line::from(Span::styled(
+                        format!("{} {}", text, e),
+                        Style::default().fg(Color::Red),
+                    )),
+                    None => Line::from(match attempt.received_bytes {
+                        Some(bytes) => format!("{} {}", text, crate::util::format_bytes(bytes)),
+                        None => text,
+                    }),
+                });
+            }
+        }
+
+        if let Some(ref notes) = repo.notes {
+            lines.push(Line::from("Notes:"));
+            for line in notes.lines() {
+                lines.push(Line::from(format!("  {}", line)));
+            }
+        }
+
         if repo.clone_names.is_empty() {
             lines.push(Line::from("Clones: none"));
         } else {
@@ -318,8 +401,14 @@ mod tests {
                     branches: vec![],
                     latest_tag: None,
                     last_sync: None,
+                    description: None,
+                    tags: vec![],
+                    owner: None,
+                    notes: None,
+                    archived: false,
                     clone_count: 0,
                     clone_names: vec![],
+                    sync_history: vec![],
                 },
                 RepoInfo {
                     name: "repo2".to_string(),
@@ -328,8 +417,14 @@ mod tests {
                     branches: vec![],
                     latest_tag: None,
                     last_sync: None,
+                    description: None,
+                    tags: vec![],
+                    owner: None,
+                    notes: None,
+                    archived: false,
                     clone_count: 0,
                     clone_names: vec![],
+                    sync_history: vec![],
                 },
             ],
             list_state: ListState::default(),
@@ -395,8 +490,14 @@ mod tests {
                     branches: vec!["main".to_string()],
                     latest_tag: None,
                     last_sync: None,
+                    description: None,
+                    tags: vec![],
+                    owner: None,
+                    notes: None,
+                    archived: false,
                     clone_count: 0,
                     clone_names: vec![],
+                    sync_history: vec![],
                 },
                 RepoInfo {
                     name: "repo2".to_string(),
@@ -405,8 +506,14 @@ mod tests {
                     branches: vec![],
                     latest_tag: None,
                     last_sync: None,
+                    description: None,
+                    tags: vec![],
+                    owner: None,
+                    notes: None,
+                    archived: false,
                     clone_count: 0,
                     clone_names: vec![],
+                    sync_history: vec![],
                 },
             ],
             list_state: ListState::default(),
@@ -431,8 +538,33 @@ mod tests {
                 branches: vec!["main".to_string()],
                 latest_tag: Some("v1.0.0".to_string()),
                 last_sync: Some("2024-01-15 10:30".to_string()),
+                description: None,
+                tags: vec![],
+                owner: None,
+                notes: None,
+                archived: false,
                 clone_count: 1,
                 clone_names: vec!["dev".to_string()],
+                sync_history: vec![
+                    SyncAttempt {
+                        started: chrono::Utc::now(),
+                        finished: chrono::Utc::now(),
+                        duration_ms: 1500,
+                        trigger: crate::metadata::SyncTrigger::Agent,
+                        received_bytes: Some(2048),
+                        received_objects: Some(12),
+                        error: None,
+                    },
+                    SyncAttempt {
+                        started: chrono::Utc::now(),
+                        finished: chrono::Utc::now(),
+                        duration_ms: 500,
+                        trigger: crate::metadata::SyncTrigger::Manual,
+                        received_bytes: None,
+                        received_objects: None,
+                        error: Some("connection refused".to_string()),
+                    },
+                ],
             }],
             list_state: ListState::default(),
             agent_running: None,
@@ -446,6 +578,14 @@ mod tests {
             "should show URL in detail"
         );
         assert!(output.contains("my-project"), "should show repo name");
+        assert!(
+            output.contains("Syncs: 1/2 ok, avg 1.0s"),
+            "should show sync health"
+        );
+        assert!(
+            output.contains("connection refused"),
+            "should show the failed attempt"
+        );
     }
 
     #[test]
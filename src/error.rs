@@ -30,6 +30,18 @@ pub enum RepomanError {
     #[error("Repository '{0}' already exists in vault")]
     RepoAlreadyInVault(String),
 
+    #[error("'{0}' is already in the vault as '{1}'")]
+    RepoUrlAlreadyInVault(String, String),
+
+    #[error("Repository name '{0}' overlaps '{1}': one would be stored inside the other")]
+    RepoNameNested(String, String),
+
+    #[error("Remote '{0}' not found for '{1}'")]
+    RemoteNotFound(String, String),
+
+    #[error("Remote '{0}' already exists for '{1}'")]
+    RemoteAlreadyExists(String, String),
+
     #[error("Pristine '{0}' not found")]
     PristineNotFound(String),
 
@@ -75,6 +87,9 @@ pub enum RepomanError {
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
 
+    #[error("State database error: {0}")]
+    SqliteError(#[from] rusqlite::Error),
+
     #[error("Agent is already running (PID: {0})")]
     AgentAlreadyRunning(u32),
 
@@ -96,9 +111,94 @@ pub enum RepomanError {
     #[error("Branch '{0}' not found in pristine '{1}'")]
     BranchNotFound(String, String),
 
+    #[error(
+        "Invalid partial clone filter '{0}' (expected blob:none, tree:<depth> or blob:limit=<size>)"
+    )]
+    InvalidFilterSpec(String),
+
+    #[error(
+        "Sparse profile '{0}' not defined for '{1}' (see repos.{1}.sparse_profiles in config.yaml)"
+    )]
+    SparseProfileNotFound(String, String),
+
+    #[error("Clone '{0}' doesn't use sparse checkout (start one with 'repoman sparse add')")]
+    CloneNotSparse(String),
+
+    #[error(
+        "Invalid tracked ref pattern '{0}' (a branch or tag name with at most one '*', e.g. release/*)"
+    )]
+    InvalidRefPattern(String),
+
+    #[error(
+        "Invalid time '{0}' (expected an age like 2h, 7d or 2w, a date like 2025-06-01, or RFC 3339)"
+    )]
+    InvalidTimeSpec(String),
+
+    #[error("Invalid size '{0}' (expected bytes with an optional k, m or g suffix, e.g. 512k)")]
+    InvalidByteSize(String),
+
+    #[error("git {0} failed: {1}")]
+    GitCommandFailed(String, String),
+
     #[error("Hook '{0}' failed: {1}")]
     HookFailed(String, String),
 
+    #[error(
+        "{0} uses schema v{1} but this repoman only supports up to v{2}; upgrade repoman before modifying it"
+    )]
+    SchemaTooNew(String, u32, u32),
+
+    #[error("Repository '{0}' is archived (run 'repoman unarchive {0}' to re-enable it)")]
+    RepoArchived(String),
+
+    #[error("Profile '{0}' not found in config.yaml (see 'repoman profile list')")]
+    ProfileNotFound(String),
+
+    #[error("Journal entry #{0} not found")]
+    JournalEntryNotFound(u64),
+
+    #[error("Nothing to undo")]
+    NothingToUndo,
+
+    #[error("Cannot undo #{0}: {1}")]
+    CannotUndo(u64, String),
+
+    #[error("Vault directory is not a git repository (run 'repoman vault init' first)")]
+    VaultNotGitBacked,
+
+    #[error("Remote vault has changes that aren't merged locally (run 'repoman vault pull' first)")]
+    VaultPushRejected,
+
+    #[error("'{0}' is not a git bundle")]
+    InvalidBundle(String),
+
+    #[error("Nothing to bundle for '{0}': no history after the given point")]
+    BundleEmpty(String),
+
+    #[error(
+        "Pristine '{0}' is a partial clone and lacks the objects a bundle needs (bundle it from a full pristine)"
+    )]
+    BundlePartialPristine(String),
+
+    #[error(
+        "Pristine '{0}' is shallow and lacks the history a bundle needs (run 'repoman deepen {0} --full' first)"
+    )]
+    BundleShallowPristine(String),
+
+    #[error(
+        "Pristine '{0}' lacks {1} commit(s) this incremental bundle builds on (apply an earlier bundle first)"
+    )]
+    BundleMissingPrerequisites(String, usize),
+
+    #[error("'{0}' is not in the vault (pass --url <git-url> to register it from the bundle)")]
+    BundleNeedsUrl(String),
+
+    #[error("Verification failed for {0}")]
+    VerifyFailed(String),
+
+    #[error("Can't {0} in offline mode (drop --offline or set network.offline: false)")]
+    Offline(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -114,6 +214,32 @@ pub fn is_auth_error(err: &git2::Error) -> bool {
         || err.message().to_lowercase().contains("permission denied")
 }
 
+/// Error messages (lowercased) that mean the host couldn't be reached at all
+const NETWORK_ERROR_MARKERS: &[&str] = &[
+    "could not resolve host",
+    "failed to resolve address",
+    "name or service not known",
+    "temporary failure in name resolution",
+    "failed to connect",
+    "connection refused",
+    "network is unreachable",
+    "no route to host",
+    "timed out",
+];
+
+impl RepomanError {
+    /// Whether this error means the network is unreachable (DNS or connect
+    /// failures), as opposed to the remote answering with an error
+    pub fn is_network_error(&self) -> bool {
+        let message = match self {
+            Self::GitError(e) => e.message().to_lowercase(),
+            Self::GitCommandFailed(_, stderr) => stderr.to_lowercase(),
+            _ => return false,
+        };
+        NETWORK_ERROR_MARKERS.iter().any(|m| message.contains(m))
+    }
+}
+
 /// Convert a git2 error to a RepomanError, with special handling for auth failures
 pub fn git_error_with_context(err: git2::Error, repo_name: &str) -> RepomanError {
     if is_auth_error(&err) {
@@ -34,6 +34,7 @@ fn run_plugin_hooks(
     clone_path: Option<&Path>,
     clone_name: Option<&str>,
     new_tag: Option<&str>,
+    pruned_refs: Option<&[String]>,
 ) {
     if let Some(wrapper) = PLUGIN_MANAGER.get() {
         // Safety: the PluginManager is alive for the duration of the program,
@@ -46,6 +47,7 @@ fn run_plugin_hooks(
             clone_path: clone_path.map(|p| p.to_string_lossy().into_owned()),
             clone_name: clone_name.map(String::from),
             new_tag: new_tag.map(String::from),
+            pruned_refs: pruned_refs.map(<[String]>::to_vec),
         };
         if let Err(e) = pm.run_hook(event, &ctx) {
             warn!("plugin hook '{}' error: {}", event, e);
@@ -54,7 +56,8 @@ fn run_plugin_hooks(
 }
 
 /// Run a single hook command. Uses `sh -c "<command>"` so shell syntax works.
-/// Sets REPOMAN_REPO, REPOMAN_EVENT, and optionally pristine/clone paths and REPOMAN_NEW_TAG.
+/// Sets REPOMAN_REPO, REPOMAN_EVENT, and optionally pristine/clone paths, REPOMAN_NEW_TAG
+/// and REPOMAN_PRUNED_REFS (space-separated).
 /// If `fail_on_error` is true, non-zero exit returns HookFailed; otherwise we log and return Ok(())
 #[allow(clippy::too_many_arguments)]
 pub fn run_hook(
@@ -66,6 +69,7 @@ pub fn run_hook(
     clone_path: Option<&Path>,
     clone_name: Option<&str>,
     new_tag: Option<&str>,
+    pruned_refs: Option<&[String]>,
     fail_on_error: bool,
 ) -> Result<()> {
     let mut env: Vec<(String, String)> = vec![
@@ -90,6 +94,9 @@ pub fn run_hook(
     if let Some(t) = new_tag {
         env.push(("REPOMAN_NEW_TAG".to_string(), t.to_string()));
     }
+    if let Some(refs) = pruned_refs {
+        env.push(("REPOMAN_PRUNED_REFS".to_string(), refs.join(" ")));
+    }
 
     debug!(
         "hooks: running {} for '{}': sh -c \"{}\"",
@@ -142,6 +149,7 @@ pub fn run_post_init_pristine(
             None,
             None,
             None,
+            None,
             true,
         )?;
     }
@@ -153,6 +161,7 @@ pub fn run_post_init_pristine(
         None,
         None,
         None,
+        None,
     );
     Ok(())
 }
@@ -173,6 +182,7 @@ pub fn run_pre_clone(config: &Config, repo_name: &str, pristine_path: &Path) ->
             None,
             None,
             None,
+            None,
             true,
         )?;
     }
@@ -184,6 +194,7 @@ pub fn run_pre_clone(config: &Config, repo_name: &str, pristine_path: &Path) ->
         None,
         None,
         None,
+        None,
     );
     Ok(())
 }
@@ -210,6 +221,7 @@ pub fn run_post_clone(
             Some(clone_path),
             Some(clone_name),
             None,
+            None,
             true,
         )?;
     }
@@ -221,12 +233,19 @@ pub fn run_post_clone(
         Some(clone_path),
         Some(clone_name),
         None,
+        None,
     );
     Ok(())
 }
 
-/// Run post_sync hook if configured. Call after sync. fail_on_error: false so sync is not blocked.
-pub fn run_post_sync(config: &Config, repo_name: &str, pristine_path: &Path) -> Result<()> {
+/// Run post_sync hook if configured. Call after sync with the refs it pruned.
+/// fail_on_error: false so sync is not blocked.
+pub fn run_post_sync(
+    config: &Config,
+    repo_name: &str,
+    pristine_path: &Path,
+    pruned_refs: &[String],
+) -> Result<()> {
     let command = config
         .hooks_for_repo(repo_name)
         .and_then(|h| h.post_sync.as_deref());
@@ -241,6 +260,7 @@ pub fn run_post_sync(config: &Config, repo_name: &str, pristine_path: &Path) ->
             None,
             None,
             None,
+            Some(pruned_refs),
             false,
         )?;
     }
@@ -252,6 +272,7 @@ pub fn run_post_sync(config: &Config, repo_name: &str, pristine_path: &Path) ->
         None,
         None,
         None,
+        Some(pruned_refs),
     );
     Ok(())
 }
@@ -277,6 +298,7 @@ pub fn run_post_sync_on_new_tag(
             None,
             None,
             Some(new_tag),
+            None,
             false,
         )?;
     }
@@ -288,6 +310,7 @@ pub fn run_post_sync_on_new_tag(
         None,
         None,
         Some(new_tag),
+        None,
     );
     Ok(())
 }
@@ -314,6 +337,7 @@ pub fn run_pre_destroy(
             Some(clone_path),
             Some(clone_name),
             None,
+            None,
             false,
         )?;
     }
@@ -325,6 +349,7 @@ pub fn run_pre_destroy(
         Some(clone_path),
         Some(clone_name),
         None,
+        None,
     );
     Ok(())
 }
@@ -345,11 +370,12 @@ pub fn run_post_destroy(config: &Config, repo_name: &str, clones_dir: &Path) ->
             None,
             None,
             None,
+            None,
             false,
         )?;
     }
 
-    run_plugin_hooks("post_destroy", repo_name, None, None, None, None);
+    run_plugin_hooks("post_destroy", repo_name, None, None, None, None, None);
     Ok(())
 }
 
@@ -366,7 +392,8 @@ mod tests {
             "post_clone",
             "my-repo",
             temp.path(),
-            Some(temp.path().as_ref()),
+            Some(temp.path()),
+            None,
             None,
             None,
             None,
@@ -378,23 +405,28 @@ mod tests {
     #[test]
     fn test_run_hook_env_and_cwd() {
         let temp = tempfile::tempdir().unwrap();
-        // Write REPOMAN_EVENT and REPOMAN_REPO into marker file to prove env and cwd
-        let cmd = format!("echo \"$REPOMAN_EVENT\" > marker && echo \"$REPOMAN_REPO\" >> marker");
+        // Write REPOMAN_EVENT, REPOMAN_REPO and REPOMAN_PRUNED_REFS into marker file
+        // to prove env and cwd
+        let cmd = "echo \"$REPOMAN_EVENT\" > marker && echo \"$REPOMAN_REPO\" >> marker \
+                   && echo \"$REPOMAN_PRUNED_REFS\" >> marker";
+        let pruned = vec!["refs/heads/old".to_string(), "refs/tags/v0".to_string()];
         let result = run_hook(
-            &cmd,
+            cmd,
             "post_sync",
             "test-repo",
             temp.path(),
-            Some(temp.path().as_ref()),
+            Some(temp.path()),
             None,
             None,
             None,
+            Some(&pruned),
             true,
         );
         assert!(result.is_ok());
         let content = fs::read_to_string(temp.path().join("marker")).unwrap();
         assert!(content.contains("post_sync"));
         assert!(content.contains("test-repo"));
+        assert!(content.contains("refs/heads/old refs/tags/v0"));
     }
 
     #[test]
@@ -409,6 +441,7 @@ mod tests {
             None,
             None,
             None,
+            None,
             true,
         );
         assert!(result.is_err());
@@ -428,6 +461,7 @@ mod tests {
             None,
             None,
             None,
+            None,
             false,
         );
         assert!(result.is_ok());
@@ -0,0 +1,384 @@
+//! Append-only operation journal (`vault_dir/journal.jsonl`).
+//!
+//! Every mutating operation appends one JSON line describing who did what, what
+//! changed in the vault/metadata, and which paths were touched. `repoman history`
+//! reads it back and `repoman undo` uses the recorded state to reverse vault-level
+//! changes. Undoing appends a new `Undo` entry rather than rewriting history.
+//! Once the file passes `ROTATE_BYTES` it is moved to `journal.1.jsonl` (replacing
+//! the previous one), so the journal keeps at most two files of history.
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::metadata::{CloneEntry, Metadata};
+use crate::storage;
+use crate::vault::VaultEntry;
+use crate::vault_git;
+
+/// What a journaled operation changed. Carries enough state to reverse it where possible.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    RepoAdded {
+        name: String,
+        url: String,
+    },
+    RepoRemoved {
+        entry: VaultEntry,
+        aliases: Vec<String>,
+        metadata: Option<Box<Metadata>>,
+    },
+    RepoRenamed {
+        from: String,
+        to: String,
+    },
+    /// Descriptive fields (tags, description, ...) changed; `before` is the previous entry.
+    RepoEdited {
+        before: Box<VaultEntry>,
+    },
+    AliasAdded {
+        alias: String,
+        target: String,
+    },
+    AliasRemoved {
+        alias: String,
+        target: String,
+    },
+    PristineCreated {
+        repo: String,
+    },
+    PristineDestroyed {
+        repo: String,
+    },
+    CloneCreated {
+        repo: String,
+        clone: String,
+    },
+    CloneDestroyed {
+        /// Owning repo, if the clone was tracked in metadata.
+        repo: Option<String>,
+        /// Clone entry as it was in metadata (restored on undo).
+        entry: Option<CloneEntry>,
+        path: PathBuf,
+        /// Where the clone was moved to with `destroy --archive`.
+        archive: Option<PathBuf>,
+    },
+    Undo {
+        target: u64,
+    },
+}
+
+impl Change {
+    /// One-line human-readable summary.
+    pub fn summary(&self) -> String {
+        match self {
+            Change::RepoAdded { name, url } => format!("added '{}' ({})", name, url),
+            Change::RepoRemoved { entry, .. } => format!("removed '{}'", entry.name),
+            Change::RepoRenamed { from, to } => format!("renamed '{}' -> '{}'", from, to),
+            Change::RepoEdited { before } => format!("edited '{}'", before.name),
+            Change::AliasAdded { alias, target } => {
+                format!("added alias '{}' -> '{}'", alias, target)
+            }
+            Change::AliasRemoved { alias, target } => {
+                format!("removed alias '{}' -> '{}'", alias, target)
+            }
+            Change::PristineCreated { repo } => format!("created pristine '{}'", repo),
+            Change::PristineDestroyed { repo } => format!("destroyed pristine '{}'", repo),
+            Change::CloneCreated { repo, clone } => {
+                format!("created clone '{}' of '{}'", clone, repo)
+            }
+            Change::CloneDestroyed { path, archive, .. } => match archive {
+                Some(a) => format!("archived clone {} to {}", path.display(), a.display()),
+                None => format!("destroyed clone {}", path.display()),
+            },
+            Change::Undo { target } => format!("undid #{}", target),
+        }
+    }
+
+    /// Whether `repoman undo` knows how to reverse this change.
+    pub fn is_undoable(&self) -> bool {
+        match self {
+            Change::RepoAdded { .. }
+            | Change::RepoRemoved { .. }
+            | Change::RepoRenamed { .. }
+            | Change::RepoEdited { .. }
+            | Change::AliasAdded { .. }
+            | Change::AliasRemoved { .. } => true,
+            Change::CloneDestroyed { archive, .. } => archive.is_some(),
+            Change::PristineCreated { .. }
+            | Change::PristineDestroyed { .. }
+            | Change::CloneCreated { .. }
+            | Change::Undo { .. } => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    /// Login name of the user who ran the operation.
+    pub user: String,
+    /// Operation that produced the entry (e.g. "remove", "destroy").
+    pub operation: String,
+    pub change: Change,
+    /// Filesystem paths created, moved or deleted by the operation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathBuf>,
+}
+
+/// Size at which the journal is rotated to `journal.1.jsonl`
+const ROTATE_BYTES: u64 = 8 * 1024 * 1024;
+
+fn journal_path(config: &Config) -> PathBuf {
+    config.vault_dir.join("journal.jsonl")
+}
+
+fn rotated_path(config: &Config) -> PathBuf {
+    config.vault_dir.join("journal.1.jsonl")
+}
+
+/// Move the journal aside once it reaches `limit` bytes. Caller holds the lock.
+fn rotate_if_over(config: &Config, limit: u64) -> Result<()> {
+    let path = journal_path(config);
+    match std::fs::metadata(&path) {
+        Ok(meta) if meta.len() >= limit => {
+            std::fs::rename(&path, rotated_path(config))?;
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Id of the last parseable entry in `path`. Reads backwards from the end in
+/// growing windows, so an append doesn't cost a pass over the whole history.
+fn last_id_in(path: &Path) -> Result<Option<u64>> {
+    #[derive(Deserialize)]
+    struct IdOnly {
+        id: u64,
+    }
+
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return Ok(None);
+    };
+    let len = file.metadata()?.len();
+    let mut window: u64 = 8 * 1024;
+    loop {
+        let start = len.saturating_sub(window);
+        file.seek(SeekFrom::Start(start))?;
+        let mut buf = Vec::new();
+        Read::by_ref(&mut file)
+            .take(len - start)
+            .read_to_end(&mut buf)?;
+        let text = String::from_utf8_lossy(&buf);
+        // Unless the window reaches the start, its first line may be cut off
+        let skip = usize::from(start > 0);
+        let id = text
+            .split('\n')
+            .skip(skip)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .find_map(|line| serde_json::from_str::<IdOnly>(line.trim()).ok());
+        if let Some(entry) = id {
+            return Ok(Some(entry.id));
+        }
+        if start == 0 {
+            return Ok(None);
+        }
+        window = window.saturating_mul(4);
+    }
+}
+
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Append an entry and return its id.
+pub fn append(
+    config: &Config,
+    operation: &str,
+    change: Change,
+    paths: Vec<PathBuf>,
+) -> Result<u64> {
+    std::fs::create_dir_all(&config.vault_dir)?;
+    let _lock = storage::lock_exclusive(&config.vault_dir.join(".journal.lock"))?;
+
+    rotate_if_over(config, ROTATE_BYTES)?;
+    let last = match last_id_in(&journal_path(config))? {
+        Some(id) => Some(id),
+        None => last_id_in(&rotated_path(config))?,
+    };
+    let id = last.map_or(1, |id| id + 1);
+    let message = change.summary();
+    let entry = JournalEntry {
+        id,
+        timestamp: Utc::now(),
+        user: current_user(),
+        operation: operation.to_string(),
+        change,
+        paths,
+    };
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(journal_path(config))?;
+    let mut line = serde_json::to_string(&entry)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())?;
+    file.sync_all()?;
+
+    // Git-backed vaults get one commit per change that touched vault.json
+    if let Err(e) = vault_git::commit_if_changed(config, &message) {
+        warn!("journal: failed to commit vault change: {}", e);
+    }
+    Ok(id)
+}
+
+/// Journal an operation, logging (not failing) if the journal can't be written.
+/// The operation itself has already succeeded at this point.
+pub fn record(config: &Config, operation: &str, change: Change, paths: Vec<PathBuf>) {
+    if let Err(e) = append(config, operation, change, paths) {
+        warn!("journal: failed to record '{}': {}", operation, e);
+    }
+}
+
+/// Read every journal entry (the rotated file, then the current one), oldest
+/// first. Unparseable lines are skipped.
+pub fn read_entries(config: &Config) -> Result<Vec<JournalEntry>> {
+    let mut entries = Vec::new();
+    for path in [rotated_path(config), journal_path(config)] {
+        if !path.exists() {
+            continue;
+        }
+        let reader = BufReader::new(std::fs::File::open(&path)?);
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => warn!(
+                    "journal: skipping malformed line {} of {}: {}",
+                    i + 1,
+                    path.display(),
+                    e
+                ),
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Ids of entries that have already been reversed by an `Undo` entry.
+pub fn undone_ids(entries: &[JournalEntry]) -> Vec<u64> {
+    entries
+        .iter()
+        .filter_map(|e| match e.change {
+            Change::Undo { target } => Some(target),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_config;
+
+    #[test]
+    fn test_append_assigns_sequential_ids() {
+        let (_temp, config) = create_test_config();
+        let a = append(
+            &config,
+            "alias",
+            Change::AliasAdded {
+                alias: "r".to_string(),
+                target: "repo".to_string(),
+            },
+            Vec::new(),
+        )
+        .unwrap();
+        let b = append(&config, "undo", Change::Undo { target: a }, Vec::new()).unwrap();
+        assert_eq!((a, b), (1, 2));
+
+        let entries = read_entries(&config).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(undone_ids(&entries), vec![1]);
+    }
+
+    #[test]
+    fn test_malformed_lines_skipped() {
+        let (_temp, config) = create_test_config();
+        record(
+            &config,
+            "init",
+            Change::PristineCreated {
+                repo: "repo".to_string(),
+            },
+            Vec::new(),
+        );
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(journal_path(&config))
+            .unwrap();
+        writeln!(file, "{{not json").unwrap();
+
+        assert_eq!(read_entries(&config).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_ids_continue_past_malformed_tail_and_rotation() {
+        let (_temp, config) = create_test_config();
+        let added = |alias: &str| Change::AliasAdded {
+            alias: alias.to_string(),
+            target: "repo".to_string(),
+        };
+        assert_eq!(append(&config, "alias", added("a"), Vec::new()).unwrap(), 1);
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(journal_path(&config))
+            .unwrap();
+        writeln!(file, "{{not json").unwrap();
+        assert_eq!(append(&config, "alias", added("b"), Vec::new()).unwrap(), 2);
+
+        rotate_if_over(&config, 0).unwrap();
+        assert!(!journal_path(&config).exists());
+        assert_eq!(append(&config, "alias", added("c"), Vec::new()).unwrap(), 3);
+
+        let ids: Vec<u64> = read_entries(&config)
+            .unwrap()
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_undoable_kinds() {
+        let archived = Change::CloneDestroyed {
+            repo: None,
+            entry: None,
+            path: PathBuf::from("/c"),
+            archive: Some(PathBuf::from("/a")),
+        };
+        let deleted = Change::CloneDestroyed {
+            repo: None,
+            entry: None,
+            path: PathBuf::from("/c"),
+            archive: None,
+        };
+        assert!(archived.is_undoable());
+        assert!(!deleted.is_undoable());
+        assert!(!Change::Undo { target: 1 }.is_undoable());
+    }
+}
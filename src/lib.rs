@@ -18,11 +18,20 @@
 
 pub mod config;
 pub mod error;
+pub mod journal;
 pub mod metadata;
 pub mod operations;
+pub mod schema;
+pub mod storage;
+pub mod store;
 pub mod vault;
+pub mod vault_git;
 
 // Internal modules needed by operations but not public API
 pub(crate) mod hooks;
 pub(crate) mod plugins;
+pub(crate) mod throttle;
 pub(crate) mod util;
+
+#[cfg(test)]
+mod test_support;
@@ -20,7 +20,7 @@ use log::{LevelFilter, debug, error, info, warn};
 use simplelog::{
     CombinedLogger, Config as LogConfig, SharedLogger, TermLogger, TerminalMode, WriteLogger,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 mod agent;
 mod commands;
@@ -28,12 +28,20 @@ mod config;
 mod dashboard;
 mod error;
 mod hooks;
+mod journal;
 mod mcp;
 mod metadata;
 mod operations;
 mod plugins;
+mod schema;
+mod storage;
+mod store;
+#[cfg(test)]
+mod test_support;
+mod throttle;
 mod util;
 mod vault;
+mod vault_git;
 
 use config::Config;
 
@@ -42,6 +50,7 @@ use config::Config;
 #[command(about = "A git repository manager with disposable workspaces")]
 #[command(version)]
 #[command(after_help = concat!("version ", env!("CARGO_PKG_VERSION")))]
+#[allow(clippy::struct_excessive_bools)] // independent global flags
 struct Cli {
     /// Print debug logs to console
     #[arg(long, global = true)]
@@ -55,6 +64,14 @@ struct Cli {
     #[arg(short = 'y', long, global = true)]
     yes: bool,
 
+    /// Use a named profile from config.yaml (overrides REPOMAN_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Work only from pristines and skip every fetch
+    #[arg(long, global = true)]
+    offline: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -83,6 +100,125 @@ enum ConfigAction {
     Init,
 }
 
+#[derive(Subcommand)]
+enum ProfileAction {
+    /// List profiles (the active one is marked with *)
+    List,
+    /// Make a profile the default for future commands
+    Use {
+        /// Profile name ("default" for the top-level directories)
+        name: String,
+    },
+    /// Add a profile to config.yaml
+    Create {
+        /// Profile name
+        name: String,
+        /// Base directory for the profile's vault, pristines, clones and logs
+        #[arg(long)]
+        root: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum VaultAction {
+    /// Make the vault directory a git repository (optionally setting its remote)
+    Init {
+        /// URL of the shared vault remote (set as 'origin')
+        remote: Option<String>,
+    },
+    /// Commit pending vault changes and push them to origin
+    Push,
+    /// Merge vault changes from origin and initialize new pristines
+    Pull {
+        /// Don't initialize pristines for entries that arrive
+        #[arg(long)]
+        no_init: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BundleAction {
+    /// Write a git bundle of a pristine's branches and tags
+    Create {
+        /// Repository name (or alias)
+        name: String,
+        /// Only history after this ref, commit or time (2w, 2025-06-01)
+        #[arg(long)]
+        since: Option<String>,
+        /// Bundle file to write (default: <name>.bundle)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Create or update a pristine from a bundle
+    Apply {
+        /// Bundle file
+        file: PathBuf,
+        /// Repository to apply it to (default: the file name before its first '.')
+        #[arg(long)]
+        name: Option<String>,
+        /// Git URL to register the repo with if it isn't in the vault
+        #[arg(long)]
+        url: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SparseAction {
+    /// List the directories checked out in a clone
+    List {
+        /// Clone suffix or clone directory name
+        clone: String,
+    },
+    /// Check out more directories (enables sparse checkout on a full clone)
+    Add {
+        /// Clone suffix or clone directory name
+        clone: String,
+        /// Directories to add
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// Stop checking out directories
+    Remove {
+        /// Clone suffix or clone directory name
+        clone: String,
+        /// Directories to remove
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RemoteAction {
+    /// List a repo's remotes (the default one is marked with *)
+    List {
+        /// Repository name (or alias)
+        name: String,
+    },
+    /// Add a named remote (e.g. 'upstream' next to your fork as 'origin')
+    Add {
+        /// Repository name (or alias)
+        name: String,
+        /// Remote name
+        remote: String,
+        /// Git URL of the remote
+        url: String,
+    },
+    /// Remove a named remote and its refs in the pristine
+    Remove {
+        /// Repository name (or alias)
+        name: String,
+        /// Remote name
+        remote: String,
+    },
+    /// Make a remote the one the pristine mirrors and new clones track
+    SetDefault {
+        /// Repository name (or alias)
+        name: String,
+        /// Remote name
+        remote: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Add repository to vault
@@ -98,6 +234,12 @@ enum Commands {
         /// Shallow clone depth (number of commits to fetch)
         #[arg(long)]
         depth: Option<i32>,
+        /// Partial clone filter: blob:none, tree:0 or blob:limit=<size>
+        #[arg(long)]
+        filter: Option<String>,
+        /// Only initialize repos with this tag (repeatable; all must match)
+        #[arg(long = "tag", conflicts_with = "vault_name")]
+        tags: Vec<String>,
     },
 
     /// Create clone from a pristine
@@ -109,12 +251,36 @@ enum Commands {
         /// Branch to check out (defaults to HEAD)
         #[arg(short, long)]
         branch: Option<String>,
+        /// Check out only these directories (cone-mode sparse checkout)
+        #[arg(long, num_args = 1.., value_name = "DIR")]
+        sparse: Vec<String>,
+        /// Check out the directories of a profile from repos.<name>.sparse_profiles
+        #[arg(long, value_name = "NAME")]
+        sparse_profile: Option<String>,
+        /// Clone even if the repository is archived
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Adjust the directories checked out in a sparse clone
+    Sparse {
+        #[command(subcommand)]
+        action: SparseAction,
     },
 
     /// Update pristine(s) from origin
     Sync {
         /// Pristine to sync. If not provided, syncs all.
         pristine: Option<String>,
+        /// Only sync repos with this tag (repeatable; all must match)
+        #[arg(long = "tag", conflicts_with = "pristine")]
+        tags: Vec<String>,
+        /// What started this sync, as recorded in the sync history (manual, webhook)
+        #[arg(long, default_value = "manual")]
+        trigger: metadata::SyncTrigger,
+        /// Clear consecutive-failure counters and quarantine before syncing
+        #[arg(long)]
+        reset_failures: bool,
     },
 
     /// Destroy target clone or pristine
@@ -130,6 +296,9 @@ enum Commands {
         /// Destroy clones with HEAD older than N days
         #[arg(long)]
         stale: Option<u64>,
+        /// Move the clone to clones/.archive instead of deleting it (restorable with undo)
+        #[arg(long)]
+        archive: bool,
         /// Skip confirmation prompt
         #[arg(short = 'y', long)]
         yes: bool,
@@ -141,6 +310,27 @@ enum Commands {
         /// Show verbose details
         #[arg(short, long)]
         verbose: bool,
+        /// Only list repos with this tag (repeatable; all must match)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+
+    /// Manage named vault profiles
+    Profile {
+        #[command(subcommand)]
+        action: Option<ProfileAction>,
+    },
+
+    /// Share the vault across machines through git
+    Vault {
+        #[command(subcommand)]
+        action: VaultAction,
+    },
+
+    /// Manage named remotes of a repository (fork workflows)
+    Remote {
+        #[command(subcommand)]
+        action: RemoteAction,
     },
 
     /// Background agent management
@@ -153,6 +343,9 @@ enum Commands {
     Status {
         /// Repository name (or alias)
         name: String,
+        /// Also show recent sync attempts with timing and transfer stats
+        #[arg(long)]
+        history: bool,
     },
 
     /// Print filesystem path for a pristine or clone
@@ -176,6 +369,9 @@ enum Commands {
     Update {
         /// Repository to update. If not provided, updates all.
         name: Option<String>,
+        /// Only update repos with this tag (repeatable; all must match)
+        #[arg(long = "tag", conflicts_with = "name")]
+        tags: Vec<String>,
     },
 
     /// Garbage-collect stale clones and compact pristines
@@ -186,6 +382,9 @@ enum Commands {
         /// Show what would be done without making changes
         #[arg(long)]
         dry_run: bool,
+        /// Only collect repos with this tag (repeatable; all must match)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
     },
 
     /// Remove repository from vault and delete all its data
@@ -210,6 +409,12 @@ enum Commands {
         shell: clap_complete::Shell,
     },
 
+    /// Move pristines to machines without network access as git bundles
+    Bundle {
+        #[command(subcommand)]
+        action: BundleAction,
+    },
+
     /// Export vault to YAML
     Export,
 
@@ -232,9 +437,104 @@ enum Commands {
     /// Run health checks on the repoman installation
     Doctor,
 
+    /// Check pristines and their clones for missing or corrupt objects
+    Verify {
+        /// Repository to verify. If not provided, verifies all pristines.
+        name: Option<String>,
+        /// Only check that every reachable object exists (skip reading contents)
+        #[arg(long)]
+        quick: bool,
+        /// Repair a pristine that fails: refetch (fetch all objects again) or
+        /// reclone (clone it again in place)
+        #[arg(long, value_name = "HOW")]
+        repair: Option<operations::Repair>,
+    },
+
+    /// Fetch more history into a shallow pristine
+    #[command(group(clap::ArgGroup::new("how").required(true).args(["depth", "since", "full"])))]
+    Deepen {
+        /// Repository name (or alias)
+        name: String,
+        /// Keep this many commits from each branch tip
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        depth: Option<u32>,
+        /// Keep every commit after this date (e.g. 2024-01-01 or "2 years ago")
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+        /// Fetch the complete history
+        #[arg(long)]
+        full: bool,
+    },
+
+    /// Report vault entries that point at the same remote
+    Dedupe,
+
     /// Init missing pristines and sync existing ones in one pass
     Refresh,
 
+    /// Show, add or remove tags on a vault entry
+    Tag {
+        /// Repository name (or alias)
+        name: String,
+        /// Tags to add
+        tags: Vec<String>,
+        /// Tag to remove (repeatable)
+        #[arg(short, long)]
+        remove: Vec<String>,
+    },
+
+    /// Show what recent syncs changed in a pristine, with the commits that arrived
+    Changes {
+        /// Repository name (or alias)
+        name: String,
+        /// Only syncs after this time: an age (2h, 7d, 2w), a date or RFC 3339
+        #[arg(long)]
+        since: Option<String>,
+    },
+
+    /// Limit which branches and tags a pristine fetches (lists patterns when none given)
+    Track {
+        /// Repository name (or alias)
+        name: String,
+        /// Branch globs such as `main` or `release/*` (tag globs with --tags)
+        patterns: Vec<String>,
+        /// Patterns match tags instead of branches
+        #[arg(long)]
+        tags: bool,
+        /// Stop tracking the given patterns
+        #[arg(short, long)]
+        remove: bool,
+    },
+
+    /// Show or set a vault entry's description, owner and notes
+    Describe {
+        /// Repository name (or alias)
+        name: String,
+        /// One-line description ("" clears it)
+        description: Option<String>,
+        /// Owning team or person ("" clears it)
+        #[arg(long)]
+        owner: Option<String>,
+        /// Free-text notes ("" clears them)
+        #[arg(long)]
+        notes: Option<String>,
+    },
+
+    /// Archive a repository: keep it in the vault but stop syncing it
+    Archive {
+        /// Repository name (or alias)
+        name: String,
+        /// Aggressively repack the pristine (git gc --aggressive --prune=now)
+        #[arg(long)]
+        compact: bool,
+    },
+
+    /// Re-enable syncing for an archived repository
+    Unarchive {
+        /// Repository name (or alias)
+        name: String,
+    },
+
     /// Rename a vault entry
     Rename {
         /// Current name (or alias)
@@ -243,6 +543,35 @@ enum Commands {
         new_name: String,
     },
 
+    /// Show the operation journal (most recent first)
+    History {
+        /// Number of entries to show
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+    },
+
+    /// Reverse a journaled operation (defaults to the most recent undoable one)
+    Undo {
+        /// Journal entry id (see `repoman history`)
+        id: Option<u64>,
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Upgrade vault.json and metadata.json to the current schema version
+    Migrate {
+        /// Report what would change without modifying anything
+        #[arg(long)]
+        check: bool,
+        /// Copy all state from JSON files into vault_dir/state.db
+        #[arg(long, conflicts_with_all = ["check", "to_json"])]
+        to_sqlite: bool,
+        /// Copy all state from vault_dir/state.db back into JSON files
+        #[arg(long, conflicts_with = "check")]
+        to_json: bool,
+    },
+
     /// Start MCP server for LLM agent integration
     Mcp,
 
@@ -257,8 +586,24 @@ enum Commands {
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
-    let config = Config::load();
+    let mut config = match Config::load_for_profile(cli.profile.as_deref()) {
+        Ok(config) => config,
+        // Still allow switching away from a profile that no longer exists
+        Err(_) if cli.profile.is_none() && matches!(cli.command, Commands::Profile { .. }) => {
+            Config::load()
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if cli.offline {
+        config.network.get_or_insert_with(Default::default).offline = Some(true);
+    }
     init_logging(&config, cli.debug);
+    // Partial pristines carry extensions.partialClone, which libgit2 refuses by default
+    operations::enable_partial_clone_support();
+    let fallback_config = config.clone();
 
     if let Err(e) = run(cli, config).await {
         error!("{}", e);
@@ -267,12 +612,29 @@ async fn main() {
         // Suggest similar repo names for "not in vault" errors
         if let Some(e) = e.downcast_ref::<error::RepomanError>()
             && let error::RepomanError::RepoNotInVault(name) = e
+            && let Ok(vault) = vault::Vault::load(&fallback_config)
         {
-            let cfg = config::Config::load();
-            if let Ok(vault) = vault::Vault::load(&cfg) {
-                let names: Vec<&str> = vault.get_all_names();
+            let ambiguous = vault.short_name_matches(name);
+            if ambiguous.len() > 1 {
+                eprintln!(
+                    "  '{}' matches several repositories: {}",
+                    name,
+                    ambiguous.join(", ")
+                );
+            } else {
+                // Match against short names too, so 'utlis' finds 'alice/utils'
+                let mut names: Vec<&str> = vault.get_all_names();
+                names.extend(vault.get_all_names().into_iter().filter_map(|n| {
+                    let short = vault::short_name(n);
+                    (short != n).then_some(short)
+                }));
                 if let Some(suggestion) = util::suggest_similar(name, &names) {
-                    eprintln!("  Did you mean '{}'?", suggestion);
+                    let matches = vault.short_name_matches(suggestion);
+                    if matches.is_empty() {
+                        eprintln!("  Did you mean '{}'?", suggestion);
+                    } else {
+                        eprintln!("  Did you mean '{}'?", matches.join("' or '"));
+                    }
                 }
             }
         }
@@ -365,6 +727,14 @@ async fn run(cli: Cli, config: Config) -> Result<(), Box<dyn std::error::Error>>
             | Commands::ShellInit { .. }
             | Commands::Config { .. }
             | Commands::Doctor
+            | Commands::Dedupe
+            | Commands::Remote { .. }
+            | Commands::Sparse { .. }
+            | Commands::Track { .. }
+            | Commands::Changes { .. }
+            | Commands::Migrate { .. }
+            | Commands::History { .. }
+            | Commands::Profile { .. }
             | Commands::Upgrade
             | Commands::Mcp
             | Commands::ManPage
@@ -388,38 +758,77 @@ async fn run(cli: Cli, config: Config) -> Result<(), Box<dyn std::error::Error>>
         Commands::Init {
             ref vault_name,
             depth,
+            ref filter,
+            ref tags,
         } => {
             info!(
-                "command: init (vault_name={:?}, depth={:?})",
-                vault_name, depth
+                "command: init (vault_name={:?}, depth={:?}, filter={:?}, tags={:?})",
+                vault_name, depth, filter, tags
             );
-            commands::handle_init(vault_name.clone(), depth, &config).await?;
+            commands::handle_init(vault_name.clone(), depth, filter.clone(), tags, &config).await?;
         }
         Commands::Clone {
             ref pristine,
             ref clone_name,
             ref branch,
+            ref sparse,
+            ref sparse_profile,
+            force,
         } => {
             info!(
-                "command: clone (pristine={}, clone_name={:?}, branch={:?})",
-                pristine, clone_name, branch
+                "command: clone (pristine={}, clone_name={:?}, branch={:?}, sparse={:?}, sparse_profile={:?}, force={})",
+                pristine, clone_name, branch, sparse, sparse_profile, force
             );
-            commands::handle_clone(pristine, clone_name.clone(), branch.clone(), &config)?;
+            commands::handle_clone(
+                pristine,
+                clone_name.clone(),
+                branch.clone(),
+                sparse,
+                sparse_profile.as_deref(),
+                force,
+                &config,
+            )?;
         }
-        Commands::Sync { ref pristine } => {
-            info!("command: sync (pristine={:?})", pristine);
-            commands::handle_sync(pristine.clone(), &config).await?;
+        Commands::Sparse { ref action } => match action {
+            SparseAction::List { clone } => {
+                info!("command: sparse list (clone={})", clone);
+                commands::handle_sparse_list(clone, json, &config)?;
+            }
+            SparseAction::Add { clone, paths } => {
+                info!("command: sparse add (clone={}, paths={:?})", clone, paths);
+                commands::handle_sparse_add(clone, paths, &config)?;
+            }
+            SparseAction::Remove { clone, paths } => {
+                info!(
+                    "command: sparse remove (clone={}, paths={:?})",
+                    clone, paths
+                );
+                commands::handle_sparse_remove(clone, paths, &config)?;
+            }
+        },
+        Commands::Sync {
+            ref pristine,
+            ref tags,
+            trigger,
+            reset_failures,
+        } => {
+            info!(
+                "command: sync (pristine={:?}, tags={:?}, trigger={}, reset_failures={})",
+                pristine, tags, trigger, reset_failures
+            );
+            commands::handle_sync(pristine.clone(), tags, trigger, reset_failures, &config).await?;
         }
         Commands::Destroy {
             ref target,
             ref all_clones,
             all_pristines,
             ref stale,
+            archive,
             yes,
         } => {
             info!(
-                "command: destroy (target={:?}, all_clones={:?}, all_pristines={}, stale={:?})",
-                target, all_clones, all_pristines, stale
+                "command: destroy (target={:?}, all_clones={:?}, all_pristines={}, stale={:?}, archive={})",
+                target, all_clones, all_pristines, stale, archive
             );
             let confirmed = yes || skip_confirm;
             commands::handle_destroy(
@@ -427,14 +836,64 @@ async fn run(cli: Cli, config: Config) -> Result<(), Box<dyn std::error::Error>>
                 all_clones.clone(),
                 all_pristines,
                 *stale,
+                archive,
                 confirmed,
                 &config,
             )?;
         }
-        Commands::List { verbose } => {
-            debug!("command: list (verbose={})", verbose);
-            commands::handle_list(verbose, json, &config)?;
+        Commands::List { verbose, ref tags } => {
+            debug!("command: list (verbose={}, tags={:?})", verbose, tags);
+            commands::handle_list(verbose, tags, json, &config)?;
         }
+        Commands::Profile { ref action } => match action {
+            Some(ProfileAction::List) | None => {
+                debug!("command: profile list");
+                commands::handle_profile_list(json, &config)?;
+            }
+            Some(ProfileAction::Use { name }) => {
+                info!("command: profile use ({})", name);
+                commands::handle_profile_use(name, &config)?;
+            }
+            Some(ProfileAction::Create { name, root }) => {
+                info!("command: profile create ({}, root={:?})", name, root);
+                commands::handle_profile_create(name, root.clone())?;
+            }
+        },
+        Commands::Vault { ref action } => match action {
+            VaultAction::Init { remote } => {
+                info!("command: vault init (remote={:?})", remote);
+                commands::handle_vault_init(remote.as_deref(), &config)?;
+            }
+            VaultAction::Push => {
+                info!("command: vault push");
+                commands::handle_vault_push(&config)?;
+            }
+            VaultAction::Pull { no_init } => {
+                info!("command: vault pull (no_init={})", no_init);
+                commands::handle_vault_pull(!no_init, json, &config)?;
+            }
+        },
+        Commands::Remote { ref action } => match action {
+            RemoteAction::List { name } => {
+                info!("command: remote list (name={})", name);
+                commands::handle_remote_list(name, json, &config)?;
+            }
+            RemoteAction::Add { name, remote, url } => {
+                info!("command: remote add (name={}, remote={})", name, remote);
+                commands::handle_remote_add(name, remote, url, &config)?;
+            }
+            RemoteAction::Remove { name, remote } => {
+                info!("command: remote remove (name={}, remote={})", name, remote);
+                commands::handle_remote_remove(name, remote, &config)?;
+            }
+            RemoteAction::SetDefault { name, remote } => {
+                info!(
+                    "command: remote set-default (name={}, remote={})",
+                    name, remote
+                );
+                commands::handle_remote_set_default(name, remote, &config)?;
+            }
+        },
         Commands::Agent { ref action } => match action {
             AgentAction::Run => {
                 info!("command: agent run");
@@ -453,9 +912,9 @@ async fn run(cli: Cli, config: Config) -> Result<(), Box<dyn std::error::Error>>
                 commands::handle_agent("status", &config)?;
             }
         },
-        Commands::Status { ref name } => {
-            info!("command: status (name={})", name);
-            commands::handle_status(name, json, &config)?;
+        Commands::Status { ref name, history } => {
+            info!("command: status (name={}, history={})", name, history);
+            commands::handle_status(name, history, json, &config)?;
         }
         Commands::Open { ref target } => {
             info!("command: open (target={:?})", target);
@@ -477,13 +936,20 @@ async fn run(cli: Cli, config: Config) -> Result<(), Box<dyn std::error::Error>>
                 commands::handle_alias_list(&config)?;
             }
         }
-        Commands::Update { ref name } => {
-            info!("command: update (name={:?})", name);
-            commands::handle_update(name.clone(), &config).await?;
+        Commands::Update { ref name, ref tags } => {
+            info!("command: update (name={:?}, tags={:?})", name, tags);
+            commands::handle_update(name.clone(), tags, &config).await?;
         }
-        Commands::Gc { days, dry_run } => {
-            info!("command: gc (days={}, dry_run={})", days, dry_run);
-            commands::handle_gc(days, dry_run, skip_confirm, &config)?;
+        Commands::Gc {
+            days,
+            dry_run,
+            ref tags,
+        } => {
+            info!(
+                "command: gc (days={}, dry_run={}, tags={:?})",
+                days, dry_run, tags
+            );
+            commands::handle_gc(days, dry_run, tags, skip_confirm, &config)?;
         }
         Commands::Remove { ref name, yes } => {
             info!("command: remove (name={})", name);
@@ -501,6 +967,28 @@ async fn run(cli: Cli, config: Config) -> Result<(), Box<dyn std::error::Error>>
         Commands::ShellInit { shell } => {
             commands::handle_shell_init(shell, &mut Cli::command());
         }
+        Commands::Bundle { ref action } => match action {
+            BundleAction::Create {
+                name,
+                since,
+                output,
+            } => {
+                info!(
+                    "command: bundle create (name={}, since={:?}, output={:?})",
+                    name, since, output
+                );
+                commands::handle_bundle_create(name, since.as_deref(), output.as_deref(), &config)?;
+            }
+            BundleAction::Apply { file, name, url } => {
+                info!(
+                    "command: bundle apply (file={}, name={:?}, url={:?})",
+                    file.display(),
+                    name,
+                    url
+                );
+                commands::handle_bundle_apply(file, name.as_deref(), url.as_deref(), &config)?;
+            }
+        },
         Commands::Export => {
             info!("command: export");
             commands::handle_export(&config)?;
@@ -527,6 +1015,36 @@ async fn run(cli: Cli, config: Config) -> Result<(), Box<dyn std::error::Error>>
             info!("command: doctor");
             commands::handle_doctor(&config)?;
         }
+        Commands::Verify {
+            ref name,
+            quick,
+            repair,
+        } => {
+            info!(
+                "command: verify (name={:?}, quick={}, repair={:?})",
+                name, quick, repair
+            );
+            commands::handle_verify(name.as_deref(), quick, repair, json, &config)?;
+        }
+        Commands::Deepen {
+            ref name,
+            depth,
+            ref since,
+            full: _,
+        } => {
+            let how = match (depth, since) {
+                (Some(n), _) => operations::Deepen::Depth(n),
+                (None, Some(date)) => operations::Deepen::Since(date.clone()),
+                // clap requires one of --depth, --since and --full
+                (None, None) => operations::Deepen::Full,
+            };
+            info!("command: deepen (name={}, how={})", name, how);
+            commands::handle_deepen(name, &how, json, &config)?;
+        }
+        Commands::Dedupe => {
+            debug!("command: dedupe");
+            commands::handle_dedupe(json, &config)?;
+        }
         Commands::Refresh => {
             info!("command: refresh");
             commands::handle_refresh(&config).await?;
@@ -538,6 +1056,84 @@ async fn run(cli: Cli, config: Config) -> Result<(), Box<dyn std::error::Error>>
             info!("command: rename ({} -> {})", old_name, new_name);
             commands::handle_rename(old_name, new_name, &config)?;
         }
+        Commands::Tag {
+            ref name,
+            ref tags,
+            ref remove,
+        } => {
+            info!(
+                "command: tag (name={}, add={:?}, remove={:?})",
+                name, tags, remove
+            );
+            commands::handle_tag(name, tags, remove, &config)?;
+        }
+        Commands::Changes {
+            ref name,
+            ref since,
+        } => {
+            info!("command: changes (name={}, since={:?})", name, since);
+            commands::handle_changes(name, since.as_deref(), json, &config)?;
+        }
+        Commands::Track {
+            ref name,
+            ref patterns,
+            tags,
+            remove,
+        } => {
+            info!(
+                "command: track (name={}, patterns={:?}, tags={}, remove={})",
+                name, patterns, tags, remove
+            );
+            commands::handle_track(name, patterns, tags, remove, json, &config)?;
+        }
+        Commands::Describe {
+            ref name,
+            ref description,
+            ref owner,
+            ref notes,
+        } => {
+            info!("command: describe (name={})", name);
+            let details = operations::EntryDetails {
+                description: description.clone(),
+                owner: owner.clone(),
+                notes: notes.clone(),
+            };
+            commands::handle_describe(name, &details, &config)?;
+        }
+        Commands::Archive { ref name, compact } => {
+            info!("command: archive (name={}, compact={})", name, compact);
+            commands::handle_archive(name, compact, &config)?;
+        }
+        Commands::Unarchive { ref name } => {
+            info!("command: unarchive (name={})", name);
+            commands::handle_unarchive(name, &config)?;
+        }
+        Commands::History { limit } => {
+            debug!("command: history (limit={:?})", limit);
+            commands::handle_history(limit, json, &config)?;
+        }
+        Commands::Undo { id, yes } => {
+            info!("command: undo (id={:?})", id);
+            commands::handle_undo(id, yes || skip_confirm, &config)?;
+        }
+        Commands::Migrate {
+            check,
+            to_sqlite,
+            to_json,
+        } => {
+            info!(
+                "command: migrate (check={}, to_sqlite={}, to_json={})",
+                check, to_sqlite, to_json
+            );
+            let target = if to_sqlite {
+                Some(config::StateBackend::Sqlite)
+            } else if to_json {
+                Some(config::StateBackend::Json)
+            } else {
+                None
+            };
+            commands::handle_migrate(check, target, json, &config)?;
+        }
         Commands::Upgrade => {
             info!("command: upgrade");
             commands::handle_upgrade(skip_confirm).await?;
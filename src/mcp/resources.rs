@@ -27,7 +27,7 @@ pub fn list_resource_templates() -> Vec<ResourceTemplate> {
             uri_template: "vault://repo/{name}/metadata".into(),
             name: "Repository metadata".into(),
             mime_type: "application/json".into(),
-            description: "Per-repository metadata (URLs, sync history, branches, clones)".into(),
+            description: "Per-repository metadata (URLs, branches, clones, sync history with timing, transfer stats and errors)".into(),
         },
         ResourceTemplate {
             uri_template: "vault://repo/{name}/clones".into(),
@@ -266,7 +266,7 @@ fn handle_clone_create(args: &Value, config: &Config) -> ToolResult {
     };
     let name = get_string(args, "name");
     let branch = get_string(args, "branch");
-    match operations::clone_from_pristine(&repo, name, branch, config) {
+    match operations::clone_from_pristine(&repo, name, branch, &[], false, config) {
         Ok(path) => tool_result_text(&format!("Clone created at {}", path.display())),
         Err(e) => tool_result_error(&format!("failed to create clone: {}", e)),
     }
@@ -277,7 +277,7 @@ fn handle_clone_destroy(args: &Value, config: &Config) -> ToolResult {
         Ok(t) => t,
         Err(r) => return r,
     };
-    match operations::destroy_target(&target, config) {
+    match operations::destroy_target(&target, false, config) {
         Ok(path) => tool_result_text(&format!("Destroyed {}", path.display())),
         Err(e) => tool_result_error(&format!("failed to destroy: {}", e)),
     }
@@ -350,7 +350,7 @@ fn handle_update(args: &Value, config: &Config) -> ToolResult {
 fn handle_gc(args: &Value, config: &Config) -> ToolResult {
     let days = get_u64(args, "days").unwrap_or(30);
     let dry_run = get_bool(args, "dry_run").unwrap_or(false);
-    match operations::run_gc(days, dry_run, config) {
+    match operations::run_gc(days, dry_run, &[], config) {
         Ok(report) => match serde_json::to_string_pretty(&report) {
             Ok(json) => tool_result_text(&json),
             Err(e) => tool_result_error(&format!("serialization error: {}", e)),
@@ -1,10 +1,14 @@
 use chrono::{DateTime, Utc};
-use fs2::FileExt;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use crate::config::Config;
 use crate::error::{RepomanError, Result};
+use crate::schema::METADATA_SCHEMA_VERSION;
+use crate::store;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CloneEntry {
@@ -15,10 +19,256 @@ pub struct CloneEntry {
     pub upstream_conflicts: bool,
 }
 
+/// A named git remote of a repo (`origin` = your fork, `upstream` = canonical, ...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub url: String,
+}
+
+/// Remote name repoman reserves in clones for the local pristine.
+pub const PRISTINE_REMOTE: &str = "pristine";
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SyncInfo {
     pub timestamp: DateTime<Utc>,
-    pub sync_type: String, // "auto" or "manual"
+    pub sync_type: String, // "auto", "manual" or "webhook"
+    /// Refs the sync deleted (gone upstream or no longer tracked)
+    #[serde(default)]
+    pub pruned_refs: Vec<String>,
+}
+
+/// What started a sync
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncTrigger {
+    /// `repoman sync`, `update`, `refresh` or an MCP client
+    #[default]
+    Manual,
+    /// The background agent's schedule
+    Agent,
+    /// An external webhook handler (`repoman sync --trigger webhook`)
+    Webhook,
+}
+
+impl SyncTrigger {
+    /// Value kept in `SyncInfo::sync_type` ("manual", "auto", "webhook")
+    pub fn sync_type(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Agent => "auto",
+            Self::Webhook => "webhook",
+        }
+    }
+}
+
+impl fmt::Display for SyncTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Manual => "manual",
+            Self::Agent => "agent",
+            Self::Webhook => "webhook",
+        })
+    }
+}
+
+impl FromStr for SyncTrigger {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "manual" => Ok(Self::Manual),
+            "agent" => Ok(Self::Agent),
+            "webhook" => Ok(Self::Webhook),
+            _ => Err(format!(
+                "unknown sync trigger '{}' (expected manual, agent or webhook)",
+                s
+            )),
+        }
+    }
+}
+
+/// One sync attempt, successful or not
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncAttempt {
+    pub started: DateTime<Utc>,
+    pub finished: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub trigger: SyncTrigger,
+    /// Transfer stats of the default-remote fetch (`None` when the git CLI
+    /// fetched a partial pristine, or the fetch never completed)
+    pub received_bytes: Option<u64>,
+    pub received_objects: Option<u64>,
+    /// Why the sync failed (`None` = success)
+    pub error: Option<String>,
+}
+
+impl SyncAttempt {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Sync attempts kept per repo (oldest dropped first)
+pub const MAX_SYNC_HISTORY: usize = 100;
+
+/// Consecutive sync failures of a repo, cleared by the next successful sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncFailures {
+    pub consecutive: u32,
+    pub first_failed: DateTime<Utc>,
+    pub last_failed: DateTime<Utc>,
+    pub last_error: String,
+    /// When the agent stopped retrying (`None` = still backing off)
+    pub quarantined_at: Option<DateTime<Utc>>,
+}
+
+impl SyncFailures {
+    /// Seconds the agent waits after the last failure: the sync interval,
+    /// doubled for every failure after the first, capped at `max_secs`
+    pub fn backoff_secs(&self, interval: u64, max_secs: u64) -> u64 {
+        let doublings = self.consecutive.saturating_sub(1).min(32);
+        interval
+            .saturating_mul(1 << doublings)
+            .min(max_secs.max(interval))
+    }
+
+    /// When the agent retries, given the repo's interval and backoff cap
+    pub fn next_retry(&self, interval: u64, max_secs: u64) -> DateTime<Utc> {
+        let secs = i64::try_from(self.backoff_secs(interval, max_secs)).unwrap_or(i64::MAX);
+        self.last_failed + chrono::Duration::seconds(secs)
+    }
+}
+
+/// A URL that failed while fetching a pristine, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchFailure {
+    pub url: String,
+    pub reason: String,
+}
+
+/// How a branch or tag moved during a sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefChangeKind {
+    Created,
+    Updated,
+    /// Moved to a commit that doesn't descend from the old tip
+    ForceUpdated,
+    Deleted,
+}
+
+/// One branch or tag that changed in a sync
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefChange {
+    /// Full ref name (`refs/heads/main`, `refs/tags/v1.0`)
+    pub name: String,
+    pub kind: RefChangeKind,
+    pub old: Option<String>,
+    pub new: Option<String>,
+    /// Commits that arrived with this ref (0 for deletions and tags)
+    #[serde(default)]
+    pub commits: usize,
+}
+
+impl RefChange {
+    pub fn is_tag(&self) -> bool {
+        self.name.starts_with("refs/tags/")
+    }
+
+    /// Branch or tag name without its `refs/heads/` / `refs/tags/` prefix
+    pub fn short_name(&self) -> &str {
+        self.name
+            .strip_prefix("refs/heads/")
+            .or_else(|| self.name.strip_prefix("refs/tags/"))
+            .unwrap_or(&self.name)
+    }
+}
+
+/// What a single sync changed in the pristine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeReport {
+    pub timestamp: DateTime<Utc>,
+    pub changes: Vec<RefChange>,
+}
+
+/// Change reports kept per repo (oldest dropped first)
+pub const MAX_CHANGE_REPORTS: usize = 50;
+
+impl ChangeReport {
+    /// One-line summary: "1 new branch, 2 updated (1 forced), 3 new tags, 12 new commits"
+    pub fn summary(&self) -> String {
+        let count = |tag: bool, kinds: &[RefChangeKind]| {
+            self.changes
+                .iter()
+                .filter(|c| c.is_tag() == tag && kinds.contains(&c.kind))
+                .count()
+        };
+        let plural =
+            |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+
+        let mut parts = Vec::new();
+        let created = count(false, &[RefChangeKind::Created]);
+        if created > 0 {
+            parts.push(plural(created, "new branch", "new branches"));
+        }
+        let updated = count(
+            false,
+            &[RefChangeKind::Updated, RefChangeKind::ForceUpdated],
+        );
+        if updated > 0 {
+            let forced = count(false, &[RefChangeKind::ForceUpdated]);
+            if forced > 0 {
+                parts.push(format!("{} updated ({} forced)", updated, forced));
+            } else {
+                parts.push(format!("{} updated", updated));
+            }
+        }
+        let deleted = count(false, &[RefChangeKind::Deleted]);
+        if deleted > 0 {
+            parts.push(format!("{} deleted", deleted));
+        }
+        let tags = count(true, &[RefChangeKind::Created]);
+        if tags > 0 {
+            parts.push(plural(tags, "new tag", "new tags"));
+        }
+        let moved_tags = count(
+            true,
+            &[
+                RefChangeKind::Updated,
+                RefChangeKind::ForceUpdated,
+                RefChangeKind::Deleted,
+            ],
+        );
+        if moved_tags > 0 {
+            parts.push(plural(
+                moved_tags,
+                "moved/deleted tag",
+                "moved/deleted tags",
+            ));
+        }
+        let commits: usize = self.changes.iter().map(|c| c.commits).sum();
+        if commits > 0 {
+            parts.push(plural(commits, "new commit", "new commits"));
+        }
+
+        if parts.is_empty() {
+            "no changes".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// Outcome of the last pristine fetch across the default URL and its mirrors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchSource {
+    pub timestamp: DateTime<Utc>,
+    /// URL the fetch succeeded from (`None` if every URL failed)
+    pub url: Option<String>,
+    /// URLs tried before `url`, in order
+    #[serde(default)]
+    pub failures: Vec<FetchFailure>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -29,100 +279,236 @@ pub struct AuthConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Metadata {
-    /// Git URLs - element 0 is the default remote
-    pub git_urls: Vec<String>,
+    /// On-disk schema version (0 = written before versioning existed).
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Named remotes - element 0 is the default remote. The pristine mirrors the
+    /// default remote's branches; other remotes land under `refs/remotes/<name>/`.
+    pub remotes: Vec<RemoteEntry>,
     pub created_on: DateTime<Utc>,
     pub last_updated: DateTime<Utc>,
     pub default_branch: Option<String>,
+    /// Branch globs the pristine fetches (`main`, `release/*`); empty = all branches
     pub tracked_branches: Vec<String>,
+    /// Tag globs the pristine fetches (`v*`); empty = all tags
+    #[serde(default)]
+    pub tracked_tags: Vec<String>,
     pub clones: Vec<CloneEntry>,
     pub sync_interval: Option<u64>, // seconds between syncs
     pub last_sync: Option<SyncInfo>,
     pub auth_config: Option<AuthConfig>,
     pub latest_tag: Option<String>,
     pub pristine_created: Option<DateTime<Utc>>,
+    /// Which URL (default or mirror) the last init/sync fetched from
+    #[serde(default)]
+    pub last_fetch: Option<FetchSource>,
+    /// What recent syncs changed, oldest first (syncs that changed nothing are skipped)
+    #[serde(default)]
+    pub change_reports: Vec<ChangeReport>,
+    /// Recent sync attempts with timing and transfer stats, oldest first
+    #[serde(default)]
+    pub sync_history: Vec<SyncAttempt>,
+    /// Failure streak since the last successful sync (`None` = healthy)
+    #[serde(default)]
+    pub sync_failures: Option<SyncFailures>,
 }
 
 impl Default for Metadata {
     fn default() -> Self {
         let now = Utc::now();
         Self {
-            git_urls: Vec::new(),
+            schema_version: METADATA_SCHEMA_VERSION,
+            remotes: Vec::new(),
             created_on: now,
             last_updated: now,
             default_branch: None,
             tracked_branches: Vec::new(),
+            tracked_tags: Vec::new(),
             clones: Vec::new(),
             sync_interval: None,
             last_sync: None,
             auth_config: None,
             latest_tag: None,
             pristine_created: None,
+            last_fetch: None,
+            change_reports: Vec::new(),
+            sync_history: Vec::new(),
+            sync_failures: None,
         }
     }
 }
 
+/// Name given to the `i`th URL when remotes aren't named explicitly
+pub fn default_remote_name(i: usize) -> String {
+    if i == 0 {
+        "origin".to_string()
+    } else {
+        format!("remote{}", i + 1)
+    }
+}
+
 impl Metadata {
-    /// Create new metadata with the given URLs (urls[0] is default)
+    /// Create new metadata with the given URLs (urls[0] is default). Remotes are
+    /// named `origin`, `remote2`, `remote3`, ...
     pub fn new(urls: Vec<String>) -> Self {
+        let remotes = urls
+            .into_iter()
+            .enumerate()
+            .map(|(i, url)| RemoteEntry {
+                name: default_remote_name(i),
+                url,
+            })
+            .collect();
+        Self::with_remotes(remotes)
+    }
+
+    /// Create new metadata with named remotes (remotes[0] is default)
+    pub fn with_remotes(remotes: Vec<RemoteEntry>) -> Self {
         let now = Utc::now();
         Self {
-            git_urls: urls,
+            schema_version: METADATA_SCHEMA_VERSION,
+            remotes,
             created_on: now,
             last_updated: now,
             default_branch: None,
             tracked_branches: Vec::new(),
+            tracked_tags: Vec::new(),
             clones: Vec::new(),
             sync_interval: Some(3600), // default 1 hour
             last_sync: None,
             auth_config: None,
             latest_tag: None,
             pristine_created: None,
+            last_fetch: None,
+            change_reports: Vec::new(),
+            sync_history: Vec::new(),
+            sync_failures: None,
         }
     }
 
-    /// Load metadata from disk for a given repo
+    /// Load metadata for a given repo from the configured state store.
     pub fn load(repo_name: &str, config: &Config) -> Result<Self> {
-        let metadata_path = config.vault_dir.join(repo_name).join("metadata.json");
-
-        if !metadata_path.exists() {
-            return Err(RepomanError::MetadataLoadError(
-                repo_name.to_string(),
-                "File not found".to_string(),
-            ));
-        }
+        store::open(config).load_metadata(repo_name)
+    }
 
-        let contents = std::fs::read_to_string(&metadata_path)
-            .map_err(|e| RepomanError::MetadataLoadError(repo_name.to_string(), e.to_string()))?;
+    /// Save metadata for a given repo to the configured state store.
+    /// Refuses to overwrite metadata written by a newer schema version.
+    pub fn save(&self, repo_name: &str, config: &Config) -> Result<()> {
+        store::open(config).save_metadata(repo_name, self)
+    }
 
-        serde_json::from_str(&contents)
-            .map_err(|e| RepomanError::MetadataLoadError(repo_name.to_string(), e.to_string()))
+    /// Load-modify-save as a single transaction, so the agent and CLI
+    /// commands can't lose each other's updates (e.g. a new `CloneEntry`).
+    /// `f` must not call `Metadata::load`/`save` for the same repo. Nothing is
+    /// written if `f` fails.
+    pub fn update<T>(
+        repo_name: &str,
+        config: &Config,
+        f: impl FnOnce(&mut Metadata) -> Result<T>,
+    ) -> Result<T> {
+        let mut f = Some(f);
+        let mut out = None;
+        store::open(config).update_metadata(repo_name, &mut |metadata| {
+            if let Some(f) = f.take() {
+                out = Some(f(metadata)?);
+            }
+            Ok(())
+        })?;
+        out.ok_or_else(|| {
+            RepomanError::MetadataSaveError(repo_name.to_string(), "update not run".to_string())
+        })
     }
 
-    /// Save metadata to disk for a given repo (with file locking)
-    pub fn save(&self, repo_name: &str, config: &Config) -> Result<()> {
-        let metadata_dir = config.vault_dir.join(repo_name);
-        std::fs::create_dir_all(&metadata_dir)?;
+    /// Apply `f` to several repos as one unit (a single transaction on the SQLite
+    /// backend). Repos without metadata are skipped.
+    pub fn update_many(
+        repo_names: &[String],
+        config: &Config,
+        mut f: impl FnMut(&str, &mut Metadata) -> Result<()>,
+    ) -> Result<()> {
+        store::open(config).update_metadata_many(repo_names, &mut f)
+    }
 
-        let lock_path = metadata_dir.join(".metadata.lock");
-        let lock_file = std::fs::File::create(&lock_path)
-            .map_err(|e| RepomanError::MetadataSaveError(repo_name.to_string(), e.to_string()))?;
-        lock_file
-            .lock_exclusive()
-            .map_err(|e| RepomanError::MetadataSaveError(repo_name.to_string(), e.to_string()))?;
+    /// Load metadata for every repo in one pass, keyed by repo name.
+    pub fn load_all(config: &Config) -> Result<HashMap<String, Metadata>> {
+        store::open(config).load_all_metadata()
+    }
 
-        let metadata_path = metadata_dir.join("metadata.json");
-        let contents = serde_json::to_string_pretty(self)
-            .map_err(|e| RepomanError::MetadataSaveError(repo_name.to_string(), e.to_string()))?;
+    /// Delete a repo's stored metadata (no-op if absent).
+    pub fn remove(repo_name: &str, config: &Config) -> Result<()> {
+        store::open(config).remove_metadata(repo_name)
+    }
 
-        std::fs::write(&metadata_path, contents)
-            .map_err(|e| RepomanError::MetadataSaveError(repo_name.to_string(), e.to_string()))
-        // lock released on drop
+    /// Find which repo has a clone entry named `clone_name`.
+    pub fn find_clone_owner(clone_name: &str, config: &Config) -> Result<Option<String>> {
+        store::open(config).find_clone_owner(clone_name)
     }
 
     /// Get the default (primary) git URL
     pub fn default_url(&self) -> Option<&str> {
-        self.git_urls.first().map(std::string::String::as_str)
+        self.remotes.first().map(|r| r.url.as_str())
+    }
+
+    /// Name of the default remote (`origin` unless changed with `remote set-default`)
+    pub fn default_remote(&self) -> Option<&str> {
+        self.remotes.first().map(|r| r.name.as_str())
+    }
+
+    /// Get a remote by name
+    pub fn get_remote(&self, name: &str) -> Option<&RemoteEntry> {
+        self.remotes.iter().find(|r| r.name == name)
+    }
+
+    /// Add a named remote (appended; it does not become the default)
+    pub fn add_remote(&mut self, repo_name: &str, name: &str, url: &str) -> Result<()> {
+        if name == PRISTINE_REMOTE || name.is_empty() || name.contains(['/', ' ']) {
+            return Err(RepomanError::Other(format!(
+                "'{}' is not a valid remote name",
+                name
+            )));
+        }
+        if self.get_remote(name).is_some() {
+            return Err(RepomanError::RemoteAlreadyExists(
+                name.to_string(),
+                repo_name.to_string(),
+            ));
+        }
+        self.remotes.push(RemoteEntry {
+            name: name.to_string(),
+            url: url.to_string(),
+        });
+        self.touch();
+        Ok(())
+    }
+
+    /// Remove a named remote. The last remaining remote can't be removed.
+    pub fn remove_remote(&mut self, repo_name: &str, name: &str) -> Result<RemoteEntry> {
+        let pos = self
+            .remotes
+            .iter()
+            .position(|r| r.name == name)
+            .ok_or_else(|| RepomanError::RemoteNotFound(name.to_string(), repo_name.to_string()))?;
+        if self.remotes.len() == 1 {
+            return Err(RepomanError::Other(format!(
+                "'{}' is the only remote of '{}'",
+                name, repo_name
+            )));
+        }
+        self.touch();
+        Ok(self.remotes.remove(pos))
+    }
+
+    /// Make a remote the default by moving it to the front
+    pub fn set_default_remote(&mut self, repo_name: &str, name: &str) -> Result<()> {
+        let pos = self
+            .remotes
+            .iter()
+            .position(|r| r.name == name)
+            .ok_or_else(|| RepomanError::RemoteNotFound(name.to_string(), repo_name.to_string()))?;
+        let remote = self.remotes.remove(pos);
+        self.remotes.insert(0, remote);
+        self.touch();
+        Ok(())
     }
 
     /// Update the last_updated timestamp
@@ -161,10 +547,98 @@ impl Metadata {
         self.last_sync = Some(SyncInfo {
             timestamp: Utc::now(),
             sync_type: sync_type.to_string(),
+            pruned_refs: Vec::new(),
+        });
+        self.touch();
+    }
+
+    /// Record the refs the last sync pruned (after `mark_synced`)
+    pub fn record_pruned(&mut self, refs: Vec<String>) {
+        if let Some(ref mut sync) = self.last_sync {
+            sync.pruned_refs = refs;
+        }
+    }
+
+    /// Keep a sync's change report, unless it changed nothing
+    pub fn record_changes(&mut self, report: ChangeReport) {
+        if report.changes.is_empty() {
+            return;
+        }
+        self.change_reports.push(report);
+        if self.change_reports.len() > MAX_CHANGE_REPORTS {
+            let excess = self.change_reports.len() - MAX_CHANGE_REPORTS;
+            self.change_reports.drain(..excess);
+        }
+        self.touch();
+    }
+
+    /// Append a sync attempt to the bounded history
+    pub fn record_sync_attempt(&mut self, attempt: SyncAttempt) {
+        self.sync_history.push(attempt);
+        if self.sync_history.len() > MAX_SYNC_HISTORY {
+            let excess = self.sync_history.len() - MAX_SYNC_HISTORY;
+            self.sync_history.drain(..excess);
+        }
+        self.touch();
+    }
+
+    /// Count a failed sync. Returns true when this failure reaches
+    /// `quarantine_after` and quarantines the repo (0 never quarantines).
+    pub fn record_sync_failure(&mut self, error: &str, quarantine_after: u32) -> bool {
+        let now = Utc::now();
+        let failures = self.sync_failures.get_or_insert_with(|| SyncFailures {
+            consecutive: 0,
+            first_failed: now,
+            last_failed: now,
+            last_error: String::new(),
+            quarantined_at: None,
+        });
+        failures.consecutive += 1;
+        failures.last_failed = now;
+        failures.last_error = error.to_string();
+        let quarantine = failures.quarantined_at.is_none()
+            && quarantine_after > 0
+            && failures.consecutive >= quarantine_after;
+        if quarantine {
+            failures.quarantined_at = Some(now);
+        }
+        self.touch();
+        quarantine
+    }
+
+    /// Forget the failure streak (after a successful sync or `sync --reset-failures`)
+    pub fn clear_sync_failures(&mut self) -> Option<SyncFailures> {
+        let cleared = self.sync_failures.take();
+        if cleared.is_some() {
+            self.touch();
+        }
+        cleared
+    }
+
+    /// Whether the agent has stopped syncing this repo after repeated failures
+    pub fn is_quarantined(&self) -> bool {
+        self.sync_failures
+            .as_ref()
+            .is_some_and(|f| f.quarantined_at.is_some())
+    }
+
+    /// Record which URL a fetch ended up using and which ones failed before it
+    pub fn record_fetch(&mut self, url: Option<String>, failures: Vec<FetchFailure>) {
+        self.last_fetch = Some(FetchSource {
+            timestamp: Utc::now(),
+            url,
+            failures,
         });
         self.touch();
     }
 
+    /// Whether the last fetch had to fall back to a mirror (or failed outright)
+    pub fn used_mirror(&self) -> bool {
+        self.last_fetch
+            .as_ref()
+            .is_some_and(|f| f.url.as_deref() != self.default_url())
+    }
+
     /// Mark pristine as created
     pub fn mark_pristine_created(&mut self) {
         self.pristine_created = Some(Utc::now());
@@ -175,25 +649,7 @@ impl Metadata {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::TempDir;
-
-    fn create_test_config() -> (TempDir, Config) {
-        let temp_dir = TempDir::new().unwrap();
-        let base = temp_dir.path().to_path_buf();
-        let config = Config {
-            vault_dir: base.join("vault"),
-            pristines_dir: base.join("pristines"),
-            clones_dir: base.join("clones"),
-            plugins_dir: base.join("plugins"),
-            logs_dir: base.join("logs"),
-            agent_heartbeat_interval: None,
-            json_output: None,
-            max_parallel: None,
-            repos: None,
-        };
-        std::fs::create_dir_all(&config.vault_dir).unwrap();
-        (temp_dir, config)
-    }
+    use crate::test_support::create_test_config;
 
     #[test]
     fn test_metadata_new() {
@@ -203,7 +659,9 @@ mod tests {
         ];
         let metadata = Metadata::new(urls.clone());
 
-        assert_eq!(metadata.git_urls, urls);
+        let names: Vec<&str> = metadata.remotes.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["origin", "remote2"]);
+        assert_eq!(metadata.remotes[1].url, urls[1]);
         assert_eq!(metadata.sync_interval, Some(3600));
         assert!(metadata.clones.is_empty());
         assert!(metadata.pristine_created.is_none());
@@ -279,6 +737,33 @@ mod tests {
         assert_eq!(metadata.last_sync.as_ref().unwrap().sync_type, "manual");
     }
 
+    #[test]
+    fn test_metadata_sync_failures_backoff_and_quarantine() {
+        let mut metadata = Metadata::new(vec!["url".to_string()]);
+        assert!(!metadata.record_sync_failure("timeout", 3));
+        assert!(!metadata.record_sync_failure("timeout", 3));
+        let failures = metadata.sync_failures.as_ref().unwrap();
+        assert_eq!(failures.consecutive, 2);
+        assert_eq!(failures.backoff_secs(600, 86400), 1200);
+        assert_eq!(failures.backoff_secs(3600, 5000), 5000);
+        // The cap never shortens the regular interval
+        assert_eq!(failures.backoff_secs(3600, 60), 3600);
+
+        assert!(metadata.record_sync_failure("refused", 3));
+        assert!(metadata.is_quarantined());
+        // Only the failure that crosses the threshold reports it
+        assert!(!metadata.record_sync_failure("refused", 3));
+        assert_eq!(
+            metadata.sync_failures.as_ref().unwrap().last_error,
+            "refused"
+        );
+
+        assert!(metadata.clear_sync_failures().is_some());
+        assert!(!metadata.is_quarantined());
+        assert!(!metadata.record_sync_failure("timeout", 0));
+        assert!(!metadata.is_quarantined());
+    }
+
     #[test]
     fn test_metadata_mark_pristine_created() {
         let mut metadata = Metadata::new(vec!["url".to_string()]);
@@ -306,7 +791,7 @@ mod tests {
 
         // Load and verify
         let loaded = Metadata::load(repo_name, &config).unwrap();
-        assert_eq!(loaded.git_urls.len(), 2);
+        assert_eq!(loaded.remotes.len(), 2);
         assert_eq!(
             loaded.default_url(),
             Some("https://github.com/user/repo.git")
@@ -316,6 +801,21 @@ mod tests {
         assert!(loaded.pristine_created.is_some());
     }
 
+    #[test]
+    fn test_metadata_load_all_includes_namespaced() {
+        let (_temp, config) = create_test_config();
+        for name in ["plain", "alice/utils", "github.com/bob/utils"] {
+            Metadata::new(vec![format!("url-{}", name)])
+                .save(name, &config)
+                .unwrap();
+        }
+
+        let all = Metadata::load_all(&config).unwrap();
+        let mut names: Vec<&str> = all.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["alice/utils", "github.com/bob/utils", "plain"]);
+    }
+
     #[test]
     fn test_metadata_load_nonexistent() {
         let (_temp, config) = create_test_config();
@@ -333,4 +833,47 @@ mod tests {
 
         assert!(metadata.last_updated > original);
     }
+
+    #[test]
+    fn test_metadata_update_concurrent_writers() {
+        let (_temp, config) = create_test_config();
+        Metadata::new(vec!["url".to_string()])
+            .save("repo", &config)
+            .unwrap();
+
+        // Many threads each add their own clone; none may be lost
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let config = config.clone();
+                std::thread::spawn(move || {
+                    Metadata::update("repo", &config, |m| {
+                        m.add_clone(format!("c{}", i), PathBuf::from(format!("/tmp/c{}", i)));
+                        Ok(())
+                    })
+                    .unwrap();
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        let loaded = Metadata::load("repo", &config).unwrap();
+        assert_eq!(loaded.clones.len(), 8);
+    }
+
+    #[test]
+    fn test_metadata_update_error_writes_nothing() {
+        let (_temp, config) = create_test_config();
+        Metadata::new(vec!["url".to_string()])
+            .save("repo", &config)
+            .unwrap();
+
+        let result: Result<()> = Metadata::update("repo", &config, |m| {
+            m.add_clone("c1".to_string(), PathBuf::from("/tmp/c1"));
+            Err(RepomanError::Other("abort".to_string()))
+        });
+        assert!(result.is_err());
+        assert!(Metadata::load("repo", &config).unwrap().clones.is_empty());
+    }
 }
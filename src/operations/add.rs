@@ -1,18 +1,19 @@
 use git2::Repository;
-use log::{debug, info};
+use log::{debug, info, warn};
 use std::env;
 
 use crate::config::Config;
 use crate::error::{RepomanError, Result};
-use crate::metadata::Metadata;
-use crate::vault::{Vault, extract_repo_name};
+use crate::journal::{self, Change};
+use crate::metadata::{Metadata, PRISTINE_REMOTE, RemoteEntry, default_remote_name};
+use crate::vault::{Vault, normalize_url, repo_name_for_url};
 
-/// Detect remote URLs from the current directory's git repository
-/// Returns Vec<String> with the default remote URL as the first element
-pub fn detect_current_repo_urls() -> Result<Vec<String>> {
+/// Detect named remotes from the current directory's git repository
+/// Returns the remotes with the default remote as the first element
+pub fn detect_current_repo_remotes() -> Result<Vec<RemoteEntry>> {
     let current_dir = env::current_dir()?;
     debug!(
-        "detect_current_repo_urls: scanning {}",
+        "detect_current_repo_remotes: scanning {}",
         current_dir.display()
     );
 
@@ -20,7 +21,7 @@ pub fn detect_current_repo_urls() -> Result<Vec<String>> {
     let repo =
         Repository::discover(&current_dir).map_err(|_| RepomanError::NotAGitRepo(current_dir))?;
 
-    let mut urls = Vec::new();
+    let mut detected = Vec::new();
     let mut default_remote_name: Option<String> = None;
 
     // Determine the default remote:
@@ -56,7 +57,8 @@ pub fn detect_current_repo_urls() -> Result<Vec<String>> {
 
     let mut remote_urls: Vec<(String, String)> = Vec::new();
 
-    for remote_name in remotes.iter().flatten() {
+    // A repoman clone's `pristine` remote points at local state, not a real remote
+    for remote_name in remotes.iter().flatten().filter(|n| *n != PRISTINE_REMOTE) {
         if let Ok(remote) = repo.find_remote(remote_name)
             && let Some(url) = remote.url()
         {
@@ -64,6 +66,19 @@ pub fn detect_current_repo_urls() -> Result<Vec<String>> {
         }
     }
 
+    // Several remotes may point at the same repo in different URL forms
+    let mut seen = std::collections::HashSet::new();
+    remote_urls.retain(|(name, url)| {
+        let keep = seen.insert(normalize_url(url));
+        if !keep {
+            debug!(
+                "detect_current_repo_remotes: skipping remote '{}' ({} is a duplicate)",
+                name, url
+            );
+        }
+        keep
+    });
+
     if remote_urls.is_empty() {
         return Err(RepomanError::NoRemotesFound);
     }
@@ -82,70 +97,91 @@ pub fn detect_current_repo_urls() -> Result<Vec<String>> {
     // Build the result with default first
     let default_name = default_remote_name.unwrap_or_default();
 
-    // Add default URL first
-    if let Some((_, url)) = remote_urls.iter().find(|(name, _)| name == &default_name) {
-        urls.push(url.clone());
+    // Add default remote first, then the rest
+    if let Some((name, url)) = remote_urls.iter().find(|(name, _)| name == &default_name) {
+        detected.push(RemoteEntry {
+            name: name.clone(),
+            url: url.clone(),
+        });
     }
-
-    // Add remaining URLs
-    for (name, url) in &remote_urls {
-        if name != &default_name {
-            urls.push(url.clone());
+    for (name, url) in remote_urls {
+        if name != default_name {
+            detected.push(RemoteEntry { name, url });
         }
     }
 
-    Ok(urls)
+    Ok(detected)
 }
 
 /// Add a repository to the vault
 pub fn add_repo(url: Option<String>, config: &Config) -> Result<String> {
-    let urls: Vec<String>;
+    let remotes: Vec<RemoteEntry>;
     let detected_from_current: bool;
 
     if let Some(u) = url {
-        urls = vec![u];
+        remotes = vec![RemoteEntry {
+            name: default_remote_name(0),
+            url: u,
+        }];
         detected_from_current = false;
     } else {
-        urls = detect_current_repo_urls()?;
+        remotes = detect_current_repo_remotes()?;
         detected_from_current = true;
     }
 
     debug!(
-        "add_repo: resolved {} url(s), detected_from_cwd={}",
-        urls.len(),
+        "add_repo: resolved {} remote(s), detected_from_cwd={}",
+        remotes.len(),
         detected_from_current
     );
 
     // Warn about multiple remotes
-    if detected_from_current && urls.len() > 1 {
-        let default_url = urls.first().map_or("", std::string::String::as_str);
+    if detected_from_current && remotes.len() > 1 {
+        let names: Vec<&str> = remotes.iter().map(|r| r.name.as_str()).collect();
         println!(
-            "Multiple remotes detected. Adding all remotes with '{}' as default.",
-            default_url
+            "Multiple remotes detected ({}). Adding all with '{}' as default.",
+            names.join(", "),
+            names[0]
         );
-        println!("You can change defaults later by editing metadata.");
+        println!("Change the default later with 'repoman remote set-default'.");
     }
 
     // Extract repo name from default URL
-    let default_url = urls.first().ok_or(RepomanError::NoRemotesFound)?;
-    let repo_name = extract_repo_name(default_url)?;
-
-    // Load vault and check for duplicates
-    let mut vault = Vault::load(config)?;
-
-    if vault.contains(&repo_name) {
-        return Err(RepomanError::RepoAlreadyInVault(repo_name));
-    }
-
-    // Add to vault
-    vault.add_entry(repo_name.clone(), default_url.clone())?;
-    vault.save(config)?;
+    let default_url = &remotes.first().ok_or(RepomanError::NoRemotesFound)?.url;
+
+    // Add to vault (add_entry rejects duplicate names; the same remote under
+    // another URL form or name is rejected here)
+    let repo_name = Vault::update(config, |vault| {
+        if let Some(existing) = vault.find_by_url(default_url) {
+            warn!(
+                "add_repo: '{}' is the same remote as '{}' ({})",
+                default_url, existing.name, existing.url
+            );
+            return Err(RepomanError::RepoUrlAlreadyInVault(
+                default_url.clone(),
+                existing.name.clone(),
+            ));
+        }
+        let repo_name = repo_name_for_url(default_url, config.naming_strategy(), vault)?;
+        vault.add_entry(repo_name.clone(), default_url.clone())?;
+        Ok(repo_name)
+    })?;
 
     // Create metadata directory and save metadata
     let default_url_owned = default_url.clone();
-    let metadata = Metadata::new(urls);
+    let metadata = Metadata::with_remotes(remotes.clone());
     metadata.save(&repo_name, config)?;
 
+    journal::record(
+        config,
+        "add",
+        Change::RepoAdded {
+            name: repo_name.clone(),
+            url: default_url_owned.clone(),
+        },
+        Vec::new(),
+    );
+
     info!(
         "add_repo: '{}' added to vault (url={})",
         repo_name, default_url_owned
@@ -1,20 +1,41 @@
 use crate::config::Config;
 use crate::error::Result;
+use crate::journal::{self, Change};
 use crate::vault::Vault;
 
 /// Add an alias for a repo
 pub fn add_alias(alias: &str, repo_name: &str, config: &Config) -> Result<()> {
-    let mut vault = Vault::load(config)?;
-    vault.add_alias(alias.to_string(), repo_name.to_string())?;
-    vault.save(config)?;
+    Vault::update(config, |vault| {
+        vault.add_alias(alias.to_string(), repo_name.to_string())
+    })?;
+    journal::record(
+        config,
+        "alias",
+        Change::AliasAdded {
+            alias: alias.to_string(),
+            target: repo_name.to_string(),
+        },
+        Vec::new(),
+    );
     Ok(())
 }
 
 /// Remove an alias
 pub fn remove_alias(alias: &str, config: &Config) -> Result<()> {
-    let mut vault = Vault::load(config)?;
-    vault.remove_alias(alias)?;
-    vault.save(config)?;
+    let target = Vault::update(config, |vault| {
+        let target = vault.list_aliases().get(alias).cloned();
+        vault.remove_alias(alias)?;
+        Ok(target.unwrap_or_default())
+    })?;
+    journal::record(
+        config,
+        "alias",
+        Change::AliasRemoved {
+            alias: alias.to_string(),
+            target,
+        },
+        Vec::new(),
+    );
     Ok(())
 }
 
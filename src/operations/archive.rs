@@ -0,0 +1,144 @@
+use log::{info, warn};
+use std::path::Path;
+use std::process::Command;
+
+use super::describe::edit_entry;
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::vault::{Vault, VaultEntry};
+
+/// Mark a vault entry archived: bulk sync, update, refresh and the agent skip it,
+/// and new clones need `--force`. With `compact`, the pristine is repacked
+/// aggressively since it is not expected to change again. Clone pins are
+/// refreshed first; if that fails the pristine is left uncompacted.
+pub fn archive_repo(name: &str, compact: bool, config: &Config) -> Result<VaultEntry> {
+    let entry = edit_entry(name, "archive", config, |entry| entry.archived = true)?;
+
+    if compact {
+        let pristine_path = config.pristines_dir.join(&entry.name);
+        if !pristine_path.exists() {
+            warn!("archive_repo: '{}' has no pristine to compact", entry.name);
+        } else if let Err(e) = super::pins::pin_clones(&entry.name, config) {
+            // Pruning now could drop commits the clones still borrow
+            warn!(
+                "archive_repo: not compacting '{}', couldn't pin its clones' objects: {}",
+                entry.name, e
+            );
+            println!(
+                "Skipping compaction of {}: couldn't pin its clones ({})",
+                entry.name, e
+            );
+        } else {
+            compact_pristine(&pristine_path)?;
+        }
+    }
+    info!("archive_repo: '{}' archived", entry.name);
+    Ok(entry)
+}
+
+/// Clear the archived flag so the repo is synced again
+pub fn unarchive_repo(name: &str, config: &Config) -> Result<VaultEntry> {
+    edit_entry(name, "unarchive", config, |entry| entry.archived = false)
+}
+
+/// Fail with `RepoArchived` if `name` is an archived vault entry
+pub fn ensure_not_archived(name: &str, vault: &Vault) -> Result<()> {
+    match vault.get_entry(name) {
+        Some(entry) if entry.archived => Err(RepomanError::RepoArchived(entry.name.clone())),
+        _ => Ok(()),
+    }
+}
+
+/// Expire reflogs and run an aggressive gc that prunes everything unreachable
+fn compact_pristine(pristine_path: &Path) -> Result<()> {
+    println!("Compacting pristine: {}", pristine_path.display());
+    let steps: [&[&str]; 2] = [
+        &["reflog", "expire", "--expire=now", "--all"],
+        &["gc", "--aggressive", "--prune=now"],
+    ];
+    for args in steps {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(pristine_path)
+            .output()?;
+        if !output.status.success() {
+            return Err(RepomanError::Other(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::Metadata;
+    use crate::test_support::{commit, create_test_config};
+    use git2::Repository;
+
+    #[test]
+    fn test_archive_excludes_from_bulk_sync() {
+        let (_temp, config) = create_test_config();
+        Vault::update(&config, |v| {
+            v.add_entry("live".to_string(), "url1".to_string())?;
+            v.add_entry("dead".to_string(), "url2".to_string())
+        })
+        .unwrap();
+        std::fs::create_dir_all(config.pristines_dir.join("live")).unwrap();
+        std::fs::create_dir_all(config.pristines_dir.join("dead")).unwrap();
+
+        archive_repo("dead", false, &config).unwrap();
+
+        let syncable = crate::operations::get_syncable_repos(&[], &config).unwrap();
+        assert_eq!(syncable, vec!["live"]);
+        let vault = Vault::load(&config).unwrap();
+        assert!(matches!(
+            ensure_not_archived("dead", &vault),
+            Err(RepomanError::RepoArchived(_))
+        ));
+
+        unarchive_repo("dead", &config).unwrap();
+        let syncable = crate::operations::get_syncable_repos(&[], &config).unwrap();
+        assert_eq!(syncable.len(), 2);
+    }
+
+    #[test]
+    fn test_compact_keeps_clones_on_force_pushed_commits() {
+        let (temp, config) = create_test_config();
+
+        let source = temp.path().join("source");
+        let source_repo = Repository::init(&source).unwrap();
+        let base = commit(&source_repo, "a.txt", &[]);
+        commit(&source_repo, "b.txt", &[base]);
+
+        let url = source.to_string_lossy().to_string();
+        Vault::update(&config, |v| v.add_entry("proj".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url]).save("proj", &config).unwrap();
+        crate::operations::init_pristine("proj", None, None, &config).unwrap();
+        let clone_path = crate::operations::clone_from_pristine(
+            "proj",
+            Some("work".to_string()),
+            None,
+            &[],
+            false,
+            &config,
+        )
+        .unwrap();
+
+        // Upstream rewrites history: the clone's HEAD is on no pristine ref
+        let branch = source_repo.head().unwrap().shorthand().unwrap().to_string();
+        source_repo
+            .reference(&format!("refs/heads/{}", branch), base, true, "reset")
+            .unwrap();
+        commit(&source_repo, "c.txt", &[base]);
+        crate::operations::sync_pristine("proj", &config).unwrap();
+
+        archive_repo("proj", true, &config).unwrap();
+
+        let check = crate::operations::check_objects("work", &clone_path, false).unwrap();
+        assert!(check.is_ok(), "{:?}", check);
+    }
+}
@@ -0,0 +1,542 @@
+//! Git bundles of pristines, for moving repos to machines without network access.
+//! libgit2 can neither write nor fetch bundles, so these paths use the git CLI.
+
+use git2::{Oid, Repository};
+use log::{debug, info};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, NamingStrategy};
+use crate::error::{RepomanError, Result};
+use crate::hooks;
+use crate::journal::{self, Change};
+use crate::metadata::{ChangeReport, Metadata};
+use crate::util::{parse_since, run_git};
+use crate::vault::{Vault, repo_name_for_url};
+
+/// Refs and prerequisite commits listed in a bundle's header
+#[derive(Debug, Default)]
+pub struct BundleHeader {
+    /// Commits the receiving repository must already have (incremental bundles)
+    pub prerequisites: Vec<Oid>,
+    /// `(ref name, target)` pairs, `HEAD` included when bundled
+    pub refs: Vec<(String, Oid)>,
+}
+
+/// Read the header of a v2/v3 bundle (everything before the packfile)
+pub fn read_bundle_header(path: &Path) -> Result<BundleHeader> {
+    let invalid = || RepomanError::InvalidBundle(path.display().to_string());
+    let mut reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut line = Vec::new();
+
+    reader.read_until(b'\n', &mut line)?;
+    if line != b"# v2 git bundle\n" && line != b"# v3 git bundle\n" {
+        return Err(invalid());
+    }
+
+    let mut header = BundleHeader::default();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Err(invalid());
+        }
+        let text = std::str::from_utf8(&line)
+            .map_err(|_| invalid())?
+            .trim_end();
+        if text.is_empty() {
+            return Ok(header);
+        }
+        if text.starts_with('@') {
+            // v3 capability (object-format, filter)
+            continue;
+        }
+        if let Some(rest) = text.strip_prefix('-') {
+            let oid = rest.split(' ').next().unwrap_or_default();
+            header
+                .prerequisites
+                .push(Oid::from_str(oid).map_err(|_| invalid())?);
+        } else {
+            let (oid, name) = text.split_once(' ').ok_or_else(invalid)?;
+            let oid = Oid::from_str(oid).map_err(|_| invalid())?;
+            header.refs.push((name.to_string(), oid));
+        }
+    }
+}
+
+/// A bundle written by `create_bundle`
+#[derive(Debug)]
+pub struct BundleSummary {
+    pub path: PathBuf,
+    pub refs: usize,
+    pub prerequisites: usize,
+    pub bytes: u64,
+}
+
+/// Write a bundle of a pristine's branches and tags to `output` (default
+/// `<name>.bundle`). With `since`, only history after that point is included:
+/// a ref or commit in the pristine, or a time as accepted by `parse_since`.
+pub fn create_bundle(
+    name: &str,
+    since: Option<&str>,
+    output: Option<&Path>,
+    config: &Config,
+) -> Result<BundleSummary> {
+    let vault = Vault::load(config)?;
+    if !vault.contains(name) {
+        return Err(RepomanError::RepoNotInVault(name.to_string()));
+    }
+    let name = vault.resolve_name(name);
+    let pristine_path = config.pristines_dir.join(name);
+    if !pristine_path.exists() {
+        return Err(RepomanError::PristineNotFound(name.to_string()));
+    }
+    // Filtered objects aren't in the pristine to put in a pack
+    if super::partial::pristine_filter(&pristine_path).is_some() {
+        return Err(RepomanError::BundlePartialPristine(name.to_string()));
+    }
+    // A bundle can't record a shallow boundary; git rejects it on apply
+    if super::shallow::is_shallow(&pristine_path) {
+        return Err(RepomanError::BundleShallowPristine(name.to_string()));
+    }
+    let repo = Repository::open_bare(&pristine_path)?;
+
+    let output = output.map_or_else(|| default_bundle_file(name), Path::to_path_buf);
+    // git resolves the output path against the pristine, not our cwd
+    let output = std::path::absolute(&output)?;
+    info!(
+        "create_bundle: '{}' -> {} (since={:?})",
+        name,
+        output.display(),
+        since
+    );
+
+    let path = pristine_path.to_string_lossy();
+    let output_arg = output.to_string_lossy();
+    let mut args = vec!["-C", &path, "bundle", "create", "--quiet", &output_arg];
+    // HEAD tells `apply` which branch a new pristine should point at
+    if repo.head().is_ok() {
+        args.push("HEAD");
+    }
+    args.extend(["--branches", "--tags"]);
+
+    let limit = match since {
+        Some(spec) => Some(match repo.revparse_single(spec) {
+            Ok(object) => format!("^{}", object.peel_to_commit()?.id()),
+            Err(_) => format!("--since={}", parse_since(spec)?.to_rfc3339()),
+        }),
+        None => None,
+    };
+    if let Some(ref limit) = limit {
+        debug!("create_bundle: limiting history with {}", limit);
+        args.push(limit);
+    }
+
+    match run_git(&args) {
+        Err(RepomanError::GitCommandFailed(_, stderr)) if stderr.contains("empty bundle") => {
+            return Err(RepomanError::BundleEmpty(name.to_string()));
+        }
+        result => result?,
+    };
+
+    let header = read_bundle_header(&output)?;
+    Ok(BundleSummary {
+        refs: header.refs.iter().filter(|(r, _)| r != "HEAD").count(),
+        prerequisites: header.prerequisites.len(),
+        bytes: std::fs::metadata(&output)?.len(),
+        path: output,
+    })
+}
+
+/// `create`'s default output file: `<name>.bundle`, with a namespaced name's `/`
+/// turned into `-`
+pub fn default_bundle_file(name: &str) -> PathBuf {
+    PathBuf::from(format!("{}.bundle", name.replace('/', "-")))
+}
+
+/// The repo a bundle is for when no name is given. The file name without
+/// `.bundle` is tried first, then with each `.suffix` dropped in turn
+/// (`neovim.0615.bundle` -> `neovim`). A candidate matches a vault name or
+/// alias, or a vault name (or a name `url` would be added under) with `/` written
+/// as `-`. With no match the whole stem is the name.
+fn bundle_repo_name(
+    file: &Path,
+    url: Option<&str>,
+    vault: &Vault,
+    config: &Config,
+) -> Result<String> {
+    let file_name = file.file_name().and_then(|f| f.to_str()).unwrap_or("");
+    let stem = file_name.strip_suffix(".bundle").unwrap_or(file_name);
+    if stem.is_empty() {
+        return Err(RepomanError::InvalidBundle(file.display().to_string()));
+    }
+
+    let strategies = [
+        config.naming_strategy(),
+        NamingStrategy::Owner,
+        NamingStrategy::Host,
+    ];
+    let url_names: Vec<String> = url.map_or_else(Vec::new, |url| {
+        strategies
+            .iter()
+            .filter_map(|s| repo_name_for_url(url, *s, vault).ok())
+            .collect()
+    });
+
+    let candidates = std::iter::successors(Some(stem), |s| s.rsplit_once('.').map(|(h, _)| h));
+    for candidate in candidates {
+        if vault.contains(candidate) {
+            return Ok(candidate.to_string());
+        }
+        let mut known = vault
+            .entries
+            .iter()
+            .map(|e| e.name.as_str())
+            .chain(url_names.iter().map(String::as_str));
+        if let Some(name) = known.find(|name| name.replace('/', "-") == candidate) {
+            return Ok(name.to_string());
+        }
+    }
+    Ok(stem.to_string())
+}
+
+/// What `apply_bundle` did
+#[derive(Debug)]
+pub struct BundleApplied {
+    pub name: String,
+    /// The vault entry was added from `--url`
+    pub registered: bool,
+    /// The pristine didn't exist and was created from the bundle
+    pub created: bool,
+    pub report: ChangeReport,
+}
+
+/// Create or update a pristine from a bundle. The repo is `name` (default: see
+/// `bundle_repo_name`); one missing from the vault is registered with `url`. An incremental bundle needs its prerequisite commits already in
+/// the pristine.
+pub fn apply_bundle(
+    file: &Path,
+    name: Option<&str>,
+    url: Option<&str>,
+    config: &Config,
+) -> Result<BundleApplied> {
+    let header = read_bundle_header(file)?;
+    let vault = Vault::load(config)?;
+    let name = match name {
+        Some(n) => n.to_string(),
+        None => bundle_repo_name(file, url, &vault, config)?,
+    };
+    info!("apply_bundle: {} -> '{}'", file.display(), name);
+
+    let name = vault.resolve_name(&name).to_string();
+    let pristine_path = config.pristines_dir.join(&name);
+    let created = !pristine_path.exists();
+    // Nothing to build on; refuse before registering or creating anything
+    if created && !header.prerequisites.is_empty() {
+        return Err(RepomanError::BundleMissingPrerequisites(
+            name,
+            header.prerequisites.len(),
+        ));
+    }
+    super::archive::ensure_not_archived(&name, &vault)?;
+    let registered = register_if_missing(&name, url, config)?;
+    let metadata = Metadata::load(&name, config)?;
+
+    let repo = if created {
+        if let Some(parent) = pristine_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let repo = Repository::init_bare(&pristine_path)?;
+        let remote_name = metadata.default_remote().unwrap_or("origin");
+        if let Some(url) = metadata.default_url() {
+            super::sync::ensure_remote(&repo, remote_name, url)?;
+        }
+        repo
+    } else {
+        let repo = Repository::open_bare(&pristine_path)?;
+        let missing = header
+            .prerequisites
+            .iter()
+            .filter(|oid| repo.find_commit(**oid).is_err())
+            .count();
+        if missing > 0 {
+            return Err(RepomanError::BundleMissingPrerequisites(name, missing));
+        }
+        repo
+    };
+
+    let result = fetch_bundle(&repo, file, &header, &metadata, created);
+    if result.is_err() && created {
+        drop(repo);
+        std::fs::remove_dir_all(&pristine_path)?;
+    }
+    let report = result?;
+
+    Metadata::update(&name, config, |m| {
+        if created {
+            m.mark_pristine_created();
+        }
+        m.mark_synced("bundle");
+        m.record_changes(report.clone());
+        Ok(())
+    })?;
+
+    if created {
+        journal::record(
+            config,
+            "bundle apply",
+            Change::PristineCreated { repo: name.clone() },
+            vec![pristine_path.clone()],
+        );
+        hooks::run_post_init_pristine(config, &name, &pristine_path)?;
+    } else {
+        hooks::run_post_sync(config, &name, &pristine_path, &[])?;
+    }
+
+    info!("apply_bundle: '{}' updated ({})", name, report.summary());
+    Ok(BundleApplied {
+        name,
+        registered,
+        created,
+        report,
+    })
+}
+
+/// Add `name` to the vault with `url` unless it's already there
+fn register_if_missing(name: &str, url: Option<&str>, config: &Config) -> Result<bool> {
+    let added = Vault::update(config, |vault| {
+        if vault.contains(name) {
+            return Ok(None);
+        }
+        let url = url.ok_or_else(|| RepomanError::BundleNeedsUrl(name.to_string()))?;
+        if let Some(existing) = vault.find_by_url(url) {
+            return Err(RepomanError::RepoUrlAlreadyInVault(
+                url.to_string(),
+                existing.name.clone(),
+            ));
+        }
+        vault.add_entry(name.to_string(), url.to_string())?;
+        Ok(Some(url.to_string()))
+    })?;
+    let Some(url) = added else {
+        return Ok(false);
+    };
+
+    Metadata::new(vec![url.clone()]).save(name, config)?;
+    journal::record(
+        config,
+        "bundle apply",
+        Change::RepoAdded {
+            name: name.to_string(),
+            url,
+        },
+        Vec::new(),
+    );
+    Ok(true)
+}
+
+/// Fetch the bundle's tracked branches and tags into the pristine and report
+/// what moved. A new pristine's HEAD follows the bundle's.
+fn fetch_bundle(
+    repo: &Repository,
+    file: &Path,
+    header: &BundleHeader,
+    metadata: &Metadata,
+    created: bool,
+) -> Result<ChangeReport> {
+    let bundle_head = header
+        .refs
+        .iter()
+        .find(|(r, _)| r == "HEAD")
+        .map(|(_, oid)| *oid);
+    let head_branch = if created {
+        bundle_head.and_then(|head| {
+            header
+                .refs
+                .iter()
+                .filter(|(r, oid)| *oid == head && r.starts_with("refs/heads/"))
+                .map(|(r, _)| r.trim_start_matches("refs/heads/").to_string())
+                .min_by_key(|b| !matches!(b.as_str(), "main" | "master"))
+        })
+    } else {
+        super::track::head_branch(repo)
+    };
+
+    let refs_before = super::changes::ref_tips(repo)?;
+    let refspecs = super::track::pristine_refspecs(metadata, head_branch.as_deref());
+    let path = repo.path().to_string_lossy();
+    let file = std::path::absolute(file)?;
+    let file_arg = file.to_string_lossy();
+    let mut args = super::pins::fetch_args(&path);
+    args.push(&file_arg);
+    args.extend(refspecs.iter().map(String::as_str));
+    run_git(&args)?;
+
+    if created && let Some(ref branch) = head_branch {
+        repo.set_head(&format!("refs/heads/{}", branch))?;
+    }
+    super::track::prune_untracked_refs(repo, metadata)?;
+
+    let refs_after = super::changes::ref_tips(repo)?;
+    Ok(super::changes::diff_ref_tips(
+        repo,
+        &refs_before,
+        &refs_after,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{commit_on_head, create_test_config};
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_bundle_roundtrip_full_and_incremental() {
+        let (temp, config) = create_test_config();
+
+        // Connected machine: a pristine of a source repo
+        let source = temp.path().join("source");
+        let source_repo = Repository::init(&source).unwrap();
+        let first = commit_on_head(&source_repo, "first");
+        let url = source.to_string_lossy().to_string();
+        Vault::update(&config, |v| v.add_entry("proj".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url.clone()])
+            .save("proj", &config)
+            .unwrap();
+        crate::operations::init_pristine("proj", None, None, &config).unwrap();
+
+        let full = temp.path().join("proj.bundle");
+        let summary = create_bundle("proj", None, Some(&full), &config).unwrap();
+        assert_eq!(summary.refs, 1);
+        assert_eq!(summary.prerequisites, 0);
+        let header = read_bundle_header(&full).unwrap();
+        assert!(
+            header
+                .refs
+                .iter()
+                .any(|(r, oid)| r == "HEAD" && *oid == first)
+        );
+
+        // Nothing new since the current tip
+        let empty = temp.path().join("empty.bundle");
+        assert!(matches!(
+            create_bundle("proj", Some("HEAD"), Some(&empty), &config),
+            Err(RepomanError::BundleEmpty(_))
+        ));
+
+        let second = commit_on_head(&source_repo, "second");
+        let tip = source_repo.find_object(second, None).unwrap();
+        source_repo.tag_lightweight("v1", &tip, false).unwrap();
+        crate::operations::sync_pristine("proj", &config).unwrap();
+        let incremental = temp.path().join("proj.2.bundle");
+        let summary = create_bundle(
+            "proj",
+            Some(&first.to_string()),
+            Some(&incremental),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(summary.prerequisites, 1);
+
+        // Air-gapped machine: empty state
+        let (_offline_temp, mut offline) = create_test_config();
+        offline.network = Some(crate::config::NetworkConfig {
+            offline: Some(true),
+            ..Default::default()
+        });
+
+        // Incremental first: nothing to build on, and nothing left behind
+        assert!(matches!(
+            apply_bundle(&incremental, Some("proj"), Some(&url), &offline),
+            Err(RepomanError::BundleMissingPrerequisites(_, 1))
+        ));
+        assert!(!Vault::load(&offline).unwrap().contains("proj"));
+        assert!(!offline.pristines_dir.join("proj").exists());
+        assert!(matches!(
+            apply_bundle(&full, None, None, &offline),
+            Err(RepomanError::BundleNeedsUrl(_))
+        ));
+
+        let applied = apply_bundle(&full, None, Some(&url), &offline).unwrap();
+        assert_eq!(applied.name, "proj");
+        assert!(applied.registered);
+        assert!(applied.created);
+        let pristine = Repository::open_bare(offline.pristines_dir.join("proj")).unwrap();
+        assert_eq!(pristine.head().unwrap().target(), Some(first));
+        assert_eq!(
+            pristine.find_remote("origin").unwrap().url(),
+            Some(url.as_str())
+        );
+
+        let applied = apply_bundle(&incremental, Some("proj"), None, &offline).unwrap();
+        assert!(!applied.created);
+        assert_eq!(pristine.head().unwrap().target(), Some(second));
+        assert!(pristine.find_reference("refs/tags/v1").is_ok());
+        let metadata = Metadata::load("proj", &offline).unwrap();
+        assert_eq!(metadata.last_sync.unwrap().sync_type, "bundle");
+        assert!(metadata.pristine_created.is_some());
+
+        // A clone works from the bundled pristine without the network
+        let clone =
+            crate::operations::clone_from_pristine("proj", None, None, &[], false, &offline)
+                .unwrap();
+        assert!(clone.exists());
+    }
+
+    #[test]
+    fn test_bundle_file_names_round_trip() {
+        let (temp, config) = create_test_config();
+
+        let mut urls = Vec::new();
+        for name in ["alice/utils", "socket.io"] {
+            let source = temp.path().join(name.replace('/', "_"));
+            let source_repo = Repository::init(&source).unwrap();
+            commit_on_head(&source_repo, "first");
+            let url = source.to_string_lossy().to_string();
+            Vault::update(&config, |v| v.add_entry(name.to_string(), url.clone())).unwrap();
+            Metadata::new(vec![url.clone()])
+                .save(name, &config)
+                .unwrap();
+            crate::operations::init_pristine(name, None, None, &config).unwrap();
+            urls.push(url);
+        }
+        let namespaced = temp.path().join(default_bundle_file("alice/utils"));
+        assert!(namespaced.ends_with("alice-utils.bundle"));
+        create_bundle("alice/utils", None, Some(&namespaced), &config).unwrap();
+        let dotted = temp.path().join(default_bundle_file("socket.io"));
+        create_bundle("socket.io", None, Some(&dotted), &config).unwrap();
+
+        let (_offline_temp, offline) = create_test_config();
+        // `/` is recovered from the name the URL gets under a namespaced strategy
+        let remote = "https://github.com/alice/utils.git";
+        let applied = apply_bundle(&namespaced, None, Some(remote), &offline).unwrap();
+        assert_eq!(applied.name, "alice/utils");
+        let applied = apply_bundle(&dotted, None, Some(&urls[1]), &offline).unwrap();
+        assert_eq!(applied.name, "socket.io");
+
+        // Later bundles resolve against the vault; extra suffixes are dropped
+        let later = temp.path().join("alice-utils.0615.bundle");
+        std::fs::copy(&namespaced, &later).unwrap();
+        let applied = apply_bundle(&later, None, None, &offline).unwrap();
+        assert_eq!(applied.name, "alice/utils");
+        assert!(!applied.created);
+        let applied = apply_bundle(&dotted, None, None, &offline).unwrap();
+        assert_eq!(applied.name, "socket.io");
+
+        let vault = Vault::load(&offline).unwrap();
+        let names: Vec<&str> = vault.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["alice/utils", "socket.io"]);
+    }
+
+    #[test]
+    fn test_read_bundle_header_rejects_other_files() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("not.bundle");
+        std::fs::write(&path, "hello\n").unwrap();
+        assert!(matches!(
+            read_bundle_header(&path),
+            Err(RepomanError::InvalidBundle(_))
+        ));
+    }
+}
@@ -0,0 +1,370 @@
+//! Per-sync change reports: what a sync did to the pristine's branches and tags,
+//! and the commit log of what arrived.
+
+use chrono::{DateTime, TimeZone, Utc};
+use git2::{Oid, Repository, Sort};
+use log::debug;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::metadata::{ChangeReport, Metadata, RefChange, RefChangeKind};
+use crate::util::run_git;
+use crate::vault::Vault;
+
+/// Tips of a pristine's branches, tags and remote-tracking refs
+pub(crate) fn ref_tips(repo: &Repository) -> Result<BTreeMap<String, Oid>> {
+    let mut tips = BTreeMap::new();
+    for reference in repo.references()? {
+        let reference = reference?;
+        if let (Some(name), Some(target)) = (reference.name(), reference.target())
+            && (name.starts_with("refs/heads/")
+                || name.starts_with("refs/tags/")
+                || name.starts_with("refs/remotes/"))
+        {
+            tips.insert(name.to_string(), target);
+        }
+    }
+    Ok(tips)
+}
+
+/// Compare branch and tag tips from before and after a sync
+pub(crate) fn diff_ref_tips(
+    repo: &Repository,
+    before: &BTreeMap<String, Oid>,
+    after: &BTreeMap<String, Oid>,
+) -> ChangeReport {
+    let tracked = |name: &str| name.starts_with("refs/heads/") || name.starts_with("refs/tags/");
+    let shallow = super::shallow::is_shallow(repo.path());
+    let mut changes = Vec::new();
+
+    for (name, &new) in after.iter().filter(|(name, _)| tracked(name)) {
+        let old = before.get(name).copied();
+        if old == Some(new) {
+            continue;
+        }
+        let is_branch = name.starts_with("refs/heads/");
+        let kind = match old {
+            None => RefChangeKind::Created,
+            Some(old) if is_descendant(repo, shallow, new, old) => RefChangeKind::Updated,
+            Some(_) => RefChangeKind::ForceUpdated,
+        };
+        // New branches count commits that neither the old refs nor the updated
+        // ones already account for
+        let commits = if is_branch {
+            let hide: Vec<Oid> = match old {
+                Some(old) => vec![old],
+                None => before
+                    .values()
+                    .copied()
+                    .chain(
+                        after
+                            .iter()
+                            .filter(|(n, _)| before.contains_key(*n))
+                            .map(|(_, &oid)| oid),
+                    )
+                    .collect(),
+            };
+            count_commits(repo, shallow, new, &hide)
+        } else {
+            0
+        };
+        changes.push(RefChange {
+            name: name.clone(),
+            kind,
+            old: old.map(|o| o.to_string()),
+            new: Some(new.to_string()),
+            commits,
+        });
+    }
+
+    for (name, old) in before.iter().filter(|(name, _)| tracked(name)) {
+        if !after.contains_key(name) {
+            changes.push(RefChange {
+                name: name.clone(),
+                kind: RefChangeKind::Deleted,
+                old: Some(old.to_string()),
+                new: None,
+                commits: 0,
+            });
+        }
+    }
+
+    ChangeReport {
+        timestamp: Utc::now(),
+        changes,
+    }
+}
+
+/// Whether `new` fast-forwards `old`. libgit2's graph walks fail at a shallow
+/// boundary, so shallow pristines ask git.
+fn is_descendant(repo: &Repository, shallow: bool, new: Oid, old: Oid) -> bool {
+    if !shallow {
+        return repo.graph_descendant_of(new, old).unwrap_or(false);
+    }
+    let path = repo.path().to_string_lossy();
+    let (old, new) = (old.to_string(), new.to_string());
+    run_git(&["-C", &path, "merge-base", "--is-ancestor", &old, &new]).is_ok()
+}
+
+/// Commits reachable from `tip` but from none of `hide`. Walk errors (objects
+/// cut off by a shallow history, say) count as zero rather than failing the sync.
+fn count_commits(repo: &Repository, shallow: bool, tip: Oid, hide: &[Oid]) -> usize {
+    if shallow {
+        let path = repo.path().to_string_lossy();
+        let tip = tip.to_string();
+        // Tags of blobs/trees can't be hidden; they don't matter for the count
+        let hide: Vec<String> = hide
+            .iter()
+            .filter(|oid| {
+                repo.find_object(**oid, None)
+                    .and_then(|o| o.peel_to_commit())
+                    .is_ok()
+            })
+            .map(|oid| format!("^{}", oid))
+            .collect();
+        let mut args = vec!["-C", &path, "rev-list", "--count", &tip];
+        args.extend(hide.iter().map(String::as_str));
+        return run_git(&args)
+            .ok()
+            .and_then(|count| count.trim().parse().ok())
+            .unwrap_or(0);
+    }
+    let walk = || -> std::result::Result<usize, git2::Error> {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(tip)?;
+        for &oid in hide {
+            // Tags of blobs/trees can't be hidden; they don't matter for the count
+            let _ = revwalk.hide(oid);
+        }
+        Ok(revwalk.flatten().count())
+    };
+    walk().unwrap_or_else(|e| {
+        debug!("count_commits: walk from {} failed: {}", tip, e);
+        0
+    })
+}
+
+/// A commit that arrived in a sync
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitSummary {
+    pub id: String,
+    pub summary: String,
+    pub author: String,
+    pub time: DateTime<Utc>,
+}
+
+/// A changed ref together with the commits it brought in
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedRef {
+    #[serde(flatten)]
+    pub change: RefChange,
+    pub log: Vec<CommitSummary>,
+}
+
+/// One sync's changes, as shown by `repoman changes`
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncChanges {
+    pub timestamp: DateTime<Utc>,
+    pub summary: String,
+    pub refs: Vec<ChangedRef>,
+}
+
+/// Change reports of a repo's recent syncs (oldest first), optionally only those
+/// after `since`, with the commit log of each changed branch
+pub fn list_changes(
+    name: &str,
+    since: Option<DateTime<Utc>>,
+    config: &Config,
+) -> Result<Vec<SyncChanges>> {
+    let vault = Vault::load(config)?;
+    if !vault.contains(name) {
+        return Err(RepomanError::RepoNotInVault(name.to_string()));
+    }
+    let name = vault.resolve_name(name);
+    let metadata = Metadata::load(name, config)?;
+    let repo = Repository::open_bare(config.pristines_dir.join(name)).ok();
+
+    Ok(metadata
+        .change_reports
+        .iter()
+        .filter(|r| since.is_none_or(|since| r.timestamp >= since))
+        .map(|report| SyncChanges {
+            timestamp: report.timestamp,
+            summary: report.summary(),
+            refs: report
+                .changes
+                .iter()
+                .map(|change| ChangedRef {
+                    change: change.clone(),
+                    log: repo
+                        .as_ref()
+                        .map(|repo| commit_log(repo, change))
+                        .unwrap_or_default(),
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+/// Commits a branch change brought in, newest first. New branches show their
+/// newest `commits` commits; objects gone since (gc) yield an empty log.
+fn commit_log(repo: &Repository, change: &RefChange) -> Vec<CommitSummary> {
+    if change.is_tag() || change.commits == 0 {
+        return Vec::new();
+    }
+    let Some(new) = change.new.as_deref().and_then(|n| Oid::from_str(n).ok()) else {
+        return Vec::new();
+    };
+    let walk = || -> std::result::Result<Vec<CommitSummary>, git2::Error> {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        revwalk.push(new)?;
+        if let Some(old) = change.old.as_deref().and_then(|o| Oid::from_str(o).ok()) {
+            let _ = revwalk.hide(old);
+        }
+        revwalk
+            .take(change.commits)
+            .map(|oid| {
+                let commit = repo.find_commit(oid?)?;
+                Ok(CommitSummary {
+                    id: commit.id().to_string(),
+                    summary: commit.summary().unwrap_or_default().to_string(),
+                    author: commit.author().name().unwrap_or_default().to_string(),
+                    time: Utc
+                        .timestamp_opt(commit.time().seconds(), 0)
+                        .single()
+                        .unwrap_or_default(),
+                })
+            })
+            .collect()
+    };
+    walk().unwrap_or_else(|e| {
+        debug!("commit_log: {} unavailable: {}", change.name, e);
+        Vec::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_config;
+    use git2::Signature;
+    use std::path::Path;
+
+    /// Commit `file` with `content` on top of `parent` and move `branch` to it
+    fn commit(repo: &Repository, branch: &str, parent: Option<Oid>, content: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        std::fs::write(workdir.join("file.txt"), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        let parents: Vec<git2::Commit> = parent
+            .map(|p| repo.find_commit(p).unwrap())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        let oid = repo
+            .commit(None, &sig, &sig, content, &tree, &parent_refs)
+            .unwrap();
+        repo.reference(&format!("refs/heads/{}", branch), oid, true, "test")
+            .unwrap();
+        oid
+    }
+
+    #[test]
+    fn test_sync_records_change_report() {
+        let (temp, config) = create_test_config();
+
+        let source = temp.path().join("source");
+        let repo = Repository::init(&source).unwrap();
+        let base = commit(&repo, "main", None, "base");
+        commit(&repo, "rewrite", Some(base), "draft");
+        commit(&repo, "stale", Some(base), "stale");
+        repo.set_head("refs/heads/main").unwrap();
+
+        let url = source.to_string_lossy().to_string();
+        Vault::update(&config, |v| v.add_entry("proj".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url]).save("proj", &config).unwrap();
+        crate::operations::init_pristine("proj", None, None, &config).unwrap();
+        crate::operations::sync_pristine("proj", &config).unwrap();
+        let before_second_sync = Utc::now();
+
+        // Two commits on main, a force-push, a new branch + tag, a deleted branch
+        let one = commit(&repo, "main", Some(base), "one");
+        let two = commit(&repo, "main", Some(one), "two");
+        commit(&repo, "rewrite", Some(base), "rewritten");
+        commit(&repo, "feature", Some(two), "feature");
+        repo.tag_lightweight("v1.0", &repo.find_object(two, None).unwrap(), false)
+            .unwrap();
+        repo.find_reference("refs/heads/stale")
+            .unwrap()
+            .delete()
+            .unwrap();
+        crate::operations::sync_pristine("proj", &config).unwrap();
+
+        let changes = list_changes("proj", Some(before_second_sync), &config).unwrap();
+        assert_eq!(changes.len(), 1);
+        let sync = &changes[0];
+        let find = |name: &str| {
+            sync.refs
+                .iter()
+                .find(|r| r.change.name == name)
+                .unwrap_or_else(|| panic!("{} missing", name))
+        };
+
+        let main = find("refs/heads/main");
+        assert_eq!(main.change.kind, RefChangeKind::Updated);
+        assert_eq!(main.change.commits, 2);
+        let log: Vec<&str> = main.log.iter().map(|c| c.summary.as_str()).collect();
+        assert_eq!(log, vec!["two", "one"]);
+
+        assert_eq!(
+            find("refs/heads/rewrite").change.kind,
+            RefChangeKind::ForceUpdated
+        );
+        let feature = find("refs/heads/feature");
+        assert_eq!(feature.change.kind, RefChangeKind::Created);
+        assert_eq!(feature.change.commits, 1);
+        assert_eq!(find("refs/tags/v1.0").change.kind, RefChangeKind::Created);
+        assert_eq!(find("refs/heads/stale").change.kind, RefChangeKind::Deleted);
+        assert_eq!(
+            sync.summary,
+            "1 new branch, 2 updated (1 forced), 1 deleted, 1 new tag, 4 new commits"
+        );
+
+        // A sync that changes nothing isn't recorded
+        crate::operations::sync_pristine("proj", &config).unwrap();
+        let metadata = Metadata::load("proj", &config).unwrap();
+        assert_eq!(
+            list_changes("proj", None, &config).unwrap().len(),
+            metadata.change_reports.len()
+        );
+        assert!(
+            list_changes("proj", Some(Utc::now()), &config)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = Utc::now();
+        let two_days = crate::util::parse_since("2d").unwrap();
+        assert!((now - two_days).num_hours() >= 47);
+        assert!(crate::util::parse_since("90m").is_ok());
+        assert_eq!(
+            crate::util::parse_since("2025-06-01").unwrap().to_rfc3339(),
+            "2025-06-01T00:00:00+00:00"
+        );
+        assert!(crate::util::parse_since("2025-06-01T12:00:00Z").is_ok());
+        for bad in ["", "d", "3y", "yesterday"] {
+            assert!(matches!(
+                crate::util::parse_since(bad),
+                Err(RepomanError::InvalidTimeSpec(_))
+            ));
+        }
+    }
+}
@@ -6,7 +6,8 @@ use std::path::PathBuf;
 use crate::config::Config;
 use crate::error::{RepomanError, Result};
 use crate::hooks;
-use crate::metadata::Metadata;
+use crate::journal::{self, Change};
+use crate::metadata::{Metadata, PRISTINE_REMOTE};
 use crate::vault::Vault;
 
 /// Generate a random clone name suffix (6 random alphanumeric chars)
@@ -22,12 +23,51 @@ fn generate_clone_suffix() -> String {
         .collect()
 }
 
+/// Point the clone's remote-tracking refs at the pristine's commits without a
+/// fetch: heads become `pristine/*` and `<default>/*`, secondary remotes and tags
+/// keep their names
+fn copy_pristine_refs(
+    pristine_repo: &Repository,
+    clone_repo: &Repository,
+    default_remote: &str,
+    metadata: &Metadata,
+) -> Result<()> {
+    let secondary: Vec<String> = metadata
+        .remotes
+        .iter()
+        .skip(1)
+        .map(|r| format!("refs/remotes/{}/", r.name))
+        .collect();
+    for reference in pristine_repo.references()?.flatten() {
+        let (Some(name), Some(oid)) = (reference.name(), reference.target()) else {
+            continue;
+        };
+        let targets: Vec<String> = if let Some(branch) = name.strip_prefix("refs/heads/") {
+            vec![
+                format!("refs/remotes/{}/{}", PRISTINE_REMOTE, branch),
+                format!("refs/remotes/{}/{}", default_remote, branch),
+            ]
+        } else if name.starts_with("refs/tags/") || secondary.iter().any(|p| name.starts_with(p)) {
+            vec![name.to_string()]
+        } else {
+            continue;
+        };
+        for target in targets {
+            clone_repo.reference(&target, oid, true, "repoman: copy from partial pristine")?;
+        }
+    }
+    Ok(())
+}
+
 /// Create a clone from a pristine
-/// Uses git reference clone for space efficiency
+/// Uses git reference clone for space efficiency. A non-empty `sparse` checks out
+/// only those directories (cone mode).
 pub fn clone_from_pristine(
     pristine_name: &str,
     clone_name: Option<String>,
     branch: Option<String>,
+    sparse: &[String],
+    force: bool,
     config: &Config,
 ) -> Result<PathBuf> {
     info!(
@@ -44,13 +84,18 @@ pub fn clone_from_pristine(
         );
         return Err(RepomanError::RepoNotInVault(pristine_name.to_string()));
     }
+    let resolved = vault.resolve_name(pristine_name).to_string();
+    let pristine_name = resolved.as_str();
+    if !force {
+        super::archive::ensure_not_archived(pristine_name, &vault)?;
+    }
 
     // Check if pristine exists — auto-init if missing (lazy init)
     let pristine_path = config.pristines_dir.join(pristine_name);
     if !pristine_path.exists() {
         info!("clone_from_pristine: pristine not found, auto-initializing");
         println!("Pristine not found — initializing from vault...");
-        super::init::init_pristine(pristine_name, None, config)?;
+        super::init::init_pristine(pristine_name, None, None, config)?;
     }
 
     // Generate or use provided clone name
@@ -72,12 +117,16 @@ pub fn clone_from_pristine(
     }
 
     // Load metadata
-    let mut metadata = Metadata::load(pristine_name, config)?;
+    let metadata = Metadata::load(pristine_name, config)?;
 
     println!("Creating clone {} from pristine...", full_clone_name);
 
     hooks::run_pre_clone(config, pristine_name, &pristine_path)?;
 
+    // Partial pristines are missing objects; check before opening so libgit2
+    // accepts their partialClone extension
+    let filter = super::partial::pristine_filter(&pristine_path);
+
     // Create a reference clone from the pristine
     // This uses git's alternates mechanism for space efficiency
     let pristine_repo = Repository::open_bare(&pristine_path)?;
@@ -114,28 +163,52 @@ pub fn clone_from_pristine(
         pristine_objects.to_string_lossy().as_bytes(),
     )?;
 
-    // origin → source URL (for user's git push/pull)
-    let source_url = metadata.default_url().ok_or_else(|| {
-        RepomanError::Other(format!(
-            "No source URL found in metadata for '{}'",
-            pristine_name
-        ))
-    })?;
-    clone_repo.remote("origin", source_url)?;
+    // Every named remote → its URL (origin = fork, upstream = canonical, ...)
+    let default_remote = metadata
+        .default_remote()
+        .ok_or_else(|| {
+            RepomanError::Other(format!(
+                "No source URL found in metadata for '{}'",
+                pristine_name
+            ))
+        })?
+        .to_string();
+    for entry in &metadata.remotes {
+        clone_repo.remote(&entry.name, &entry.url)?;
+    }
 
     // pristine → local pristine path (for repoman's fast internal ops)
-    clone_repo.remote("pristine", &pristine_path.to_string_lossy())?;
-
-    // Fetch from pristine (fast, local) into both remote namespaces
-    let mut pristine_remote = clone_repo.find_remote("pristine")?;
-    pristine_remote.fetch(
-        &[
-            "refs/heads/*:refs/remotes/pristine/*",
-            "refs/heads/*:refs/remotes/origin/*",
-        ],
-        None,
-        None,
-    )?;
+    clone_repo.remote(PRISTINE_REMOTE, &pristine_path.to_string_lossy())?;
+
+    // Fetch from pristine (fast, local) into every remote namespace: the pristine's
+    // heads mirror the default remote, the others live under refs/remotes/<name>/
+    let mut refspecs = vec![
+        "refs/heads/*:refs/remotes/pristine/*".to_string(),
+        format!("refs/heads/*:refs/remotes/{}/*", default_remote),
+    ];
+    for entry in metadata.remotes.iter().skip(1) {
+        refspecs.push(format!("refs/remotes/{0}/*:refs/remotes/{0}/*", entry.name));
+    }
+    let refspecs: Vec<&str> = refspecs.iter().map(String::as_str).collect();
+    // Persist the secondary namespaces so a plain `git fetch pristine` keeps them fresh
+    for entry in metadata.remotes.iter().skip(1) {
+        clone_repo.remote_add_fetch(
+            PRISTINE_REMOTE,
+            &format!("+refs/remotes/{0}/*:refs/remotes/{0}/*", entry.name),
+        )?;
+    }
+    if let Some(ref f) = filter {
+        // A partial pristine can't serve a pack for its missing objects, but the
+        // alternates already give the clone every object it has: copy the refs.
+        copy_pristine_refs(&pristine_repo, &clone_repo, &default_remote, &metadata)?;
+        super::partial::configure_partial_clone(&clone_repo, &default_remote, f)?;
+    } else {
+        let mut pristine_remote = clone_repo.find_remote(PRISTINE_REMOTE)?;
+        pristine_remote.fetch(&refspecs, None, None)?;
+    }
+    // A shallow pristine's boundary, without which git fails on the first missing parent
+    let shallow = super::shallow::shallow_info(&pristine_path);
+    super::shallow::sync_clone_shallow(&pristine_path, &clone_path)?;
 
     // Determine which branch to check out:
     // 1. Explicit --branch flag
@@ -159,27 +232,46 @@ pub fn clone_from_pristine(
     // Owned copy for later use
     let branch_name = branch_name.to_string();
 
-    // Create local branch tracking origin/<branch> so git push/pull work
-    let remote_ref_name = format!("refs/remotes/origin/{}", branch_name);
+    // Create local branch tracking <default remote>/<branch> so git push/pull work
+    let remote_ref_name = format!("refs/remotes/{}/{}", default_remote, branch_name);
     if let Ok(remote_ref) = clone_repo.find_reference(&remote_ref_name) {
         let commit = remote_ref.peel_to_commit()?;
         let mut local_branch = clone_repo.branch(&branch_name, &commit, false)?;
-        local_branch.set_upstream(Some(&format!("origin/{}", branch_name)))?;
+        local_branch.set_upstream(Some(&format!("{}/{}", default_remote, branch_name)))?;
 
         // Set HEAD to the branch
         clone_repo.set_head(&format!("refs/heads/{}", branch_name))?;
-
-        // Check out the working tree
-        clone_repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
     } else {
         // Fallback: try to checkout HEAD directly
         clone_repo.set_head_detached(head_commit.id())?;
+    }
+
+    // Check out the working tree: sparse and partial clones go through the git CLI
+    // (cone patterns; fetching missing blobs)
+    if !sparse.is_empty() {
+        super::sparse::init_sparse_checkout(&clone_path, sparse)?;
+    } else if filter.is_some() {
+        super::partial::populate_partial_worktree(&clone_path)?;
+    } else {
         clone_repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
     }
 
-    // Update metadata with clone entry
-    metadata.add_clone(clone_suffix.clone(), clone_path.clone());
-    metadata.save(pristine_name, config)?;
+    // Record the clone entry against the latest on-disk metadata (the agent may have
+    // written in the meantime)
+    Metadata::update(pristine_name, config, |m| {
+        m.add_clone(clone_suffix.clone(), clone_path.clone());
+        Ok(())
+    })?;
+    journal::record(
+        config,
+        "clone",
+        Change::CloneCreated {
+            repo: pristine_name.to_string(),
+            clone: clone_suffix.clone(),
+        },
+        vec![clone_path.clone()],
+    );
+    super::pins::pin_clones_or_warn(pristine_name, config);
 
     hooks::run_post_clone(
         config,
@@ -194,6 +286,14 @@ pub fn clone_from_pristine(
         clone_path.display()
     );
     println!("Clone created: {}", clone_path.display());
+    if let Some(info) = shallow {
+        println!(
+            "Note: {} is shallow ({}); 'git log' and 'git blame' stop there. Run 'repoman deepen {} --full' for the whole history.",
+            pristine_name,
+            info.summary(),
+            pristine_name
+        );
+    }
 
     Ok(clone_path)
 }
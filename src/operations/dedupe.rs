@@ -0,0 +1,79 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::vault::{Vault, normalize_url};
+
+/// Vault entries that point at the same remote
+#[derive(Debug, Serialize)]
+pub struct DuplicateGroup {
+    /// Normalized URL shared by every entry in the group.
+    pub remote: String,
+    /// `(name, url)` pairs, oldest entry first.
+    pub entries: Vec<(String, String)>,
+}
+
+/// Find entries whose URLs normalize to the same remote, in vault order.
+pub fn find_duplicates(config: &Config) -> Result<Vec<DuplicateGroup>> {
+    let vault = Vault::load(config)?;
+
+    let mut order: Vec<String> = Vec::new();
+    let mut groups: HashMap<String, Vec<&crate::vault::VaultEntry>> = HashMap::new();
+    for entry in &vault.entries {
+        let remote = normalize_url(&entry.url);
+        if !groups.contains_key(&remote) {
+            order.push(remote.clone());
+        }
+        groups.entry(remote).or_default().push(entry);
+    }
+
+    let duplicates = order
+        .into_iter()
+        .filter_map(|remote| {
+            let mut entries = groups.remove(&remote)?;
+            if entries.len() < 2 {
+                return None;
+            }
+            entries.sort_by_key(|e| e.added_date);
+            Some(DuplicateGroup {
+                remote,
+                entries: entries
+                    .into_iter()
+                    .map(|e| (e.name.clone(), e.url.clone()))
+                    .collect(),
+            })
+        })
+        .collect();
+    Ok(duplicates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_config;
+
+    #[test]
+    fn test_find_duplicates_groups_url_forms() {
+        let (_temp, config) = create_test_config();
+        // Written directly: add_repo would refuse the second form
+        Vault::update(&config, |v| {
+            v.add_entry("bar".to_string(), "git@github.com:foo/bar.git".to_string())?;
+            v.add_entry(
+                "other".to_string(),
+                "https://github.com/foo/other".to_string(),
+            )?;
+            v.add_entry(
+                "bar-https".to_string(),
+                "https://github.com/foo/bar".to_string(),
+            )
+        })
+        .unwrap();
+
+        let groups = find_duplicates(&config).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].remote, "github.com/foo/bar");
+        let names: Vec<&str> = groups[0].entries.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["bar", "bar-https"]);
+    }
+}
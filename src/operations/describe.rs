@@ -0,0 +1,105 @@
+use log::info;
+
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::journal::{self, Change};
+use crate::vault::{Vault, VaultEntry};
+
+/// Fields `repoman describe` can set. `None` leaves a field unchanged;
+/// an empty string clears it.
+#[derive(Debug, Default)]
+pub struct EntryDetails {
+    pub description: Option<String>,
+    pub owner: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl EntryDetails {
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none() && self.owner.is_none() && self.notes.is_none()
+    }
+}
+
+/// Apply `f` to a vault entry in one transaction and journal the previous state
+/// so `repoman undo` can restore it. Returns the updated entry.
+pub(crate) fn edit_entry(
+    name: &str,
+    operation: &str,
+    config: &Config,
+    f: impl FnOnce(&mut VaultEntry),
+) -> Result<VaultEntry> {
+    let (before, after) = Vault::update(config, |vault| {
+        let entry = vault
+            .get_entry_mut(name)
+            .ok_or_else(|| RepomanError::RepoNotInVault(name.to_string()))?;
+        let before = entry.clone();
+        f(entry);
+        Ok((before, entry.clone()))
+    })?;
+
+    info!("{}: updated vault entry '{}'", operation, after.name);
+    journal::record(
+        config,
+        operation,
+        Change::RepoEdited {
+            before: Box::new(before),
+        },
+        Vec::new(),
+    );
+    Ok(after)
+}
+
+fn apply_field(field: &mut Option<String>, value: Option<&String>) {
+    if let Some(value) = value {
+        let value = value.trim();
+        *field = (!value.is_empty()).then(|| value.to_string());
+    }
+}
+
+/// Set description, owner and/or notes on a vault entry
+pub fn describe_repo(name: &str, details: &EntryDetails, config: &Config) -> Result<VaultEntry> {
+    edit_entry(name, "describe", config, |entry| {
+        apply_field(&mut entry.description, details.description.as_ref());
+        apply_field(&mut entry.owner, details.owner.as_ref());
+        apply_field(&mut entry.notes, details.notes.as_ref());
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_config;
+
+    #[test]
+    fn test_describe_sets_and_clears_fields() {
+        let (_temp, config) = create_test_config();
+        Vault::update(&config, |v| {
+            v.add_entry("repo".to_string(), "url".to_string())
+        })
+        .unwrap();
+
+        let details = EntryDetails {
+            description: Some("Payments API".to_string()),
+            owner: Some("team-pay".to_string()),
+            notes: None,
+        };
+        let entry = describe_repo("repo", &details, &config).unwrap();
+        assert_eq!(entry.description.as_deref(), Some("Payments API"));
+        assert_eq!(entry.owner.as_deref(), Some("team-pay"));
+
+        let clear = EntryDetails {
+            owner: Some(String::new()),
+            ..EntryDetails::default()
+        };
+        let entry = describe_repo("repo", &clear, &config).unwrap();
+        assert!(entry.owner.is_none());
+        assert_eq!(entry.description.as_deref(), Some("Payments API"));
+    }
+
+    #[test]
+    fn test_describe_unknown_repo() {
+        let (_temp, config) = create_test_config();
+        let result = describe_repo("missing", &EntryDetails::default(), &config);
+        assert!(matches!(result, Err(RepomanError::RepoNotInVault(_))));
+    }
+}
@@ -1,57 +1,115 @@
 use log::{debug, info, warn};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::gc;
 use crate::config::Config;
 use crate::error::{RepomanError, Result};
 use crate::hooks;
-use crate::metadata::Metadata;
+use crate::journal::{self, Change};
+use crate::metadata::{CloneEntry, Metadata};
+use crate::util;
 use crate::vault::Vault;
 
+/// Remove a clone directory, or move it under `clones_dir/.archive/` when `archive`
+/// is set so it can be restored with `repoman undo`. Returns the archive path.
+fn discard_clone_dir(clone_path: &Path, archive: bool, config: &Config) -> Result<Option<PathBuf>> {
+    if !archive {
+        println!("Removing clone directory: {}", clone_path.display());
+        fs::remove_dir_all(clone_path)?;
+        return Ok(None);
+    }
+
+    let archive_dir = config.clones_dir.join(".archive");
+    fs::create_dir_all(&archive_dir)?;
+    let dir_name = clone_path
+        .file_name()
+        .map_or_else(|| "clone".into(), |n| n.to_string_lossy().into_owned());
+    let dest = archive_dir.join(format!(
+        "{}-{}",
+        dir_name,
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    println!(
+        "Archiving clone directory: {} -> {}",
+        clone_path.display(),
+        dest.display()
+    );
+    fs::rename(clone_path, &dest)?;
+    Ok(Some(dest))
+}
+
+fn record_clone_destroyed(
+    config: &Config,
+    repo: Option<&str>,
+    entry: Option<CloneEntry>,
+    path: &Path,
+    archive: Option<PathBuf>,
+) {
+    // An archived clone still borrows the pristine's objects and can come back
+    if archive.is_none()
+        && let (Some(repo), Some(entry)) = (repo, entry.as_ref())
+        && let Err(e) = super::pins::unpin_clone(&config.pristines_dir.join(repo), &entry.name)
+    {
+        warn!("destroy: failed to unpin clone '{}': {}", entry.name, e);
+    }
+    let mut paths = vec![path.to_path_buf()];
+    paths.extend(archive.clone());
+    journal::record(
+        config,
+        "destroy",
+        Change::CloneDestroyed {
+            repo: repo.map(str::to_string),
+            entry,
+            path: path.to_path_buf(),
+            archive,
+        },
+        paths,
+    );
+}
+
 /// Destroy a clone
-/// Removes the clone from disk and updates metadata
-pub fn destroy_clone(clone_name: &str, config: &Config) -> Result<PathBuf> {
+/// Removes the clone from disk (or archives it) and updates metadata
+pub fn destroy_clone(clone_name: &str, archive: bool, config: &Config) -> Result<PathBuf> {
     info!("destroy_clone: destroying '{}'", clone_name);
-    let vault = Vault::load(config)?;
-
-    // Find which repo this clone belongs to
-    // Clone names are in format: <pristine-name>-<suffix>
-    for repo_name in vault.get_all_names() {
-        if let Ok(mut metadata) = Metadata::load(repo_name, config) {
-            // Check if this clone exists in this repo's metadata
-            if metadata.get_clone(clone_name).is_some() {
-                // Found the clone - get the path before removing
-                let clone_entry = metadata.get_clone(clone_name).unwrap();
-                let clone_path = clone_entry.path.clone();
-                let pristine_path = config.pristines_dir.join(repo_name);
-
-                // Run pre_destroy hook (e.g. backup) while clone still exists
-                if clone_path.exists() {
-                    hooks::run_pre_destroy(
-                        config,
-                        repo_name,
-                        &clone_path,
-                        clone_name,
-                        &pristine_path,
-                    )?;
-                }
-
-                // Remove from filesystem
-                if clone_path.exists() {
-                    println!("Removing clone directory: {}", clone_path.display());
-                    fs::remove_dir_all(&clone_path)?;
-                }
-
-                // Update metadata
-                metadata.remove_clone(clone_name);
-                metadata.save(repo_name, config)?;
-
-                hooks::run_post_destroy(config, repo_name, &config.clones_dir)?;
-
-                println!("Clone '{}' destroyed", clone_name);
-                return Ok(clone_path);
+    // Find which repo this clone belongs to (indexed lookup on the SQLite backend)
+    if let Some(repo_name) = Metadata::find_clone_owner(clone_name, config)? {
+        let repo_name = repo_name.as_str();
+        let metadata = Metadata::load(repo_name, config)?;
+        if let Some(clone_entry) = metadata.get_clone(clone_name) {
+            let clone_entry = clone_entry.clone();
+            let clone_path = clone_entry.path.clone();
+            let pristine_path = config.pristines_dir.join(repo_name);
+
+            // Run pre_destroy hook (e.g. backup) while clone still exists
+            if clone_path.exists() {
+                hooks::run_pre_destroy(config, repo_name, &clone_path, clone_name, &pristine_path)?;
             }
+
+            // Remove (or archive) from filesystem
+            let archived = if clone_path.exists() {
+                discard_clone_dir(&clone_path, archive, config)?
+            } else {
+                None
+            };
+
+            // Update metadata
+            Metadata::update(repo_name, config, |m| {
+                m.remove_clone(clone_name);
+                Ok(())
+            })?;
+            record_clone_destroyed(
+                config,
+                Some(repo_name),
+                Some(clone_entry),
+                &clone_path,
+                archived,
+            );
+
+            hooks::run_post_destroy(config, repo_name, &config.clones_dir)?;
+
+            println!("Clone '{}' destroyed", clone_name);
+            return Ok(clone_path);
         }
     }
 
@@ -80,23 +138,26 @@ pub fn destroy_clone(clone_name: &str, config: &Config) -> Result<PathBuf> {
                 &pristine_path,
             )?;
         }
+        let mut removed_entry = None;
         if let Some(ref pristine_name) = pristine_name_opt
             && !pristine_name.is_empty()
-            && let Ok(mut metadata) = Metadata::load(pristine_name, config)
-        {
             // Try to find by path
-            let clone_suffix = clone_name.strip_prefix(&format!("{}-", pristine_name));
-            if let Some(suffix) = clone_suffix
-                && metadata.get_clone(suffix).is_some()
-            {
-                metadata.remove_clone(suffix);
-                metadata.save(pristine_name, config)?;
-            }
+            && let Some(suffix) = clone_name.strip_prefix(&format!("{}-", pristine_name))
+        {
+            removed_entry = Metadata::update(pristine_name, config, |m| Ok(m.remove_clone(suffix)))
+                .ok()
+                .flatten();
         }
 
         // Remove the directory even if we couldn't update metadata
-        println!("Removing clone directory: {}", clone_path.display());
-        fs::remove_dir_all(&clone_path)?;
+        let archived = discard_clone_dir(&clone_path, archive, config)?;
+        record_clone_destroyed(
+            config,
+            pristine_name_opt.as_deref().filter(|n| !n.is_empty()),
+            removed_entry,
+            &clone_path,
+            archived,
+        );
         if let Some(ref pristine_name) = pristine_name_opt
             && !pristine_name.is_empty()
         {
@@ -118,6 +179,8 @@ pub fn destroy_pristine(pristine_name: &str, config: &Config) -> Result<PathBuf>
     if !vault.contains(pristine_name) {
         return Err(RepomanError::RepoNotInVault(pristine_name.to_string()));
     }
+    let resolved = vault.resolve_name(pristine_name).to_string();
+    let pristine_name = resolved.as_str();
 
     // Check if pristine exists
     let pristine_path = config.pristines_dir.join(pristine_name);
@@ -127,26 +190,39 @@ pub fn destroy_pristine(pristine_name: &str, config: &Config) -> Result<PathBuf>
 
     // Remove from filesystem
     println!("Removing pristine directory: {}", pristine_path.display());
-    fs::remove_dir_all(&pristine_path)?;
+    util::remove_dir_keeping(&pristine_path, &vault.nested_dirs(pristine_name))?;
 
     // Update metadata to clear pristine_created
-    if let Ok(mut metadata) = Metadata::load(pristine_name, config) {
-        metadata.pristine_created = None;
-        metadata.save(pristine_name, config)?;
+    if let Err(e) = Metadata::update(pristine_name, config, |m| {
+        m.pristine_created = None;
+        Ok(())
+    }) {
+        warn!(
+            "destroy_pristine: failed to update metadata for '{}': {}",
+            pristine_name, e
+        );
     }
+    journal::record(
+        config,
+        "destroy",
+        Change::PristineDestroyed {
+            repo: pristine_name.to_string(),
+        },
+        vec![pristine_path.clone()],
+    );
 
     println!("Pristine '{}' destroyed (vault entry kept)", pristine_name);
     Ok(pristine_path)
 }
 
 /// Determine if a target is a clone or pristine and destroy accordingly
-pub fn destroy_target(target: &str, config: &Config) -> Result<PathBuf> {
+pub fn destroy_target(target: &str, archive: bool, config: &Config) -> Result<PathBuf> {
     debug!("destroy_target: resolving target '{}'", target);
     let vault = Vault::load(config)?;
 
     // Check if it's a pristine (repo name in vault with existing pristine)
     if vault.contains(target) {
-        let pristine_path = config.pristines_dir.join(target);
+        let pristine_path = config.pristines_dir.join(vault.resolve_name(target));
         if pristine_path.exists() {
             return destroy_pristine(target, config);
         }
@@ -155,7 +231,7 @@ pub fn destroy_target(target: &str, config: &Config) -> Result<PathBuf> {
     // Check if it's a clone directory
     let clone_path = config.clones_dir.join(target);
     if clone_path.exists() {
-        return destroy_clone(target, config);
+        return destroy_clone(target, archive, config);
     }
 
     // Check if it's a clone suffix in any repo's metadata
@@ -163,7 +239,7 @@ pub fn destroy_target(target: &str, config: &Config) -> Result<PathBuf> {
         if let Ok(metadata) = Metadata::load(repo_name, config)
             && metadata.get_clone(target).is_some()
         {
-            return destroy_clone(target, config);
+            return destroy_clone(target, archive, config);
         }
     }
 
@@ -183,30 +259,41 @@ pub fn destroy_all_clones(pristine_name: &str, config: &Config) -> Result<Vec<Pa
         return Err(RepomanError::RepoNotInVault(pristine_name.to_string()));
     }
 
-    let mut metadata = Metadata::load(pristine_name, config)?;
+    let metadata = Metadata::load(pristine_name, config)?;
     let mut removed = Vec::new();
+    let mut removed_names = Vec::new();
 
-    let clone_names: Vec<String> = metadata.clones.iter().map(|c| c.name.clone()).collect();
-    for name in &clone_names {
-        if let Some(entry) = metadata.get_clone(name) {
-            let path = entry.path.clone();
-            if path.exists() {
-                println!("Removing clone: {}", path.display());
-                if let Err(e) = fs::remove_dir_all(&path) {
-                    warn!(
-                        "destroy_all_clones: failed to remove '{}': {}",
-                        path.display(),
-                        e
-                    );
-                    continue;
-                }
+    for entry in &metadata.clones {
+        let path = entry.path.clone();
+        if path.exists() {
+            println!("Removing clone: {}", path.display());
+            if let Err(e) = fs::remove_dir_all(&path) {
+                warn!(
+                    "destroy_all_clones: failed to remove '{}': {}",
+                    path.display(),
+                    e
+                );
+                continue;
             }
-            removed.push(path);
         }
-        metadata.remove_clone(name);
+        removed_names.push(entry.name.clone());
+        record_clone_destroyed(
+            config,
+            Some(pristine_name),
+            Some(entry.clone()),
+            &path,
+            None,
+        );
+        removed.push(path);
     }
 
-    metadata.save(pristine_name, config)?;
+    // Drop the entries from metadata as it is now; clones added meanwhile stay
+    Metadata::update(pristine_name, config, |m| {
+        for name in &removed_names {
+            m.remove_clone(name);
+        }
+        Ok(())
+    })?;
 
     println!("Destroyed {} clones for '{}'", removed.len(), pristine_name);
     Ok(removed)
@@ -219,7 +306,7 @@ pub fn destroy_stale_clones(days: u64, config: &Config) -> Result<Vec<PathBuf>>
         days
     );
 
-    let stale = gc::find_stale_clones(days, config)?;
+    let stale = gc::find_stale_clones(days, &[], config)?;
     let mut removed = Vec::new();
 
     for sc in &stale {
@@ -241,10 +328,14 @@ pub fn destroy_stale_clones(days: u64, config: &Config) -> Result<Vec<PathBuf>>
         }
 
         // Update metadata
-        if let Ok(mut metadata) = Metadata::load(&sc.repo_name, config) {
-            metadata.remove_clone(&sc.clone_name);
-            let _ = metadata.save(&sc.repo_name, config);
-        }
+        let entry = Metadata::update(
+            &sc.repo_name,
+            config,
+            |m| Ok(m.remove_clone(&sc.clone_name)),
+        )
+        .ok()
+        .flatten();
+        record_clone_destroyed(config, Some(&sc.repo_name), entry, &sc.path, None);
 
         removed.push(sc.path.clone());
     }
@@ -260,12 +351,13 @@ pub fn destroy_all_pristines(config: &Config) -> Result<Vec<PathBuf>> {
 
     let vault = Vault::load(config)?;
     let mut removed = Vec::new();
+    let mut cleared = Vec::new();
 
     for name in vault.get_all_names() {
         let pristine_path = config.pristines_dir.join(name);
         if pristine_path.exists() {
             println!("Removing pristine: {}", pristine_path.display());
-            if let Err(e) = fs::remove_dir_all(&pristine_path) {
+            if let Err(e) = util::remove_dir_keeping(&pristine_path, &vault.nested_dirs(name)) {
                 warn!(
                     "destroy_all_pristines: failed to remove '{}': {}",
                     pristine_path.display(),
@@ -273,14 +365,25 @@ pub fn destroy_all_pristines(config: &Config) -> Result<Vec<PathBuf>> {
                 );
                 continue;
             }
+            journal::record(
+                config,
+                "destroy",
+                Change::PristineDestroyed {
+                    repo: name.to_string(),
+                },
+                vec![pristine_path.clone()],
+            );
             removed.push(pristine_path);
         }
+        cleared.push(name.to_string());
+    }
 
-        // Clear pristine_created in metadata (best-effort)
-        if let Ok(mut metadata) = Metadata::load(name, config) {
-            metadata.pristine_created = None;
-            let _ = metadata.save(name, config);
-        }
+    // Clear pristine_created in one transaction (best-effort)
+    if let Err(e) = Metadata::update_many(&cleared, config, |_, m| {
+        m.pristine_created = None;
+        Ok(())
+    }) {
+        warn!("destroy_all_pristines: failed to update metadata: {}", e);
     }
 
     println!("Destroyed {} pristines", removed.len());
@@ -290,27 +393,7 @@ pub fn destroy_all_pristines(config: &Config) -> Result<Vec<PathBuf>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::TempDir;
-
-    fn create_test_config() -> (TempDir, Config) {
-        let temp_dir = TempDir::new().unwrap();
-        let base = temp_dir.path().to_path_buf();
-        let config = Config {
-            vault_dir: base.join("vault"),
-            pristines_dir: base.join("pristines"),
-            clones_dir: base.join("clones"),
-            plugins_dir: base.join("plugins"),
-            logs_dir: base.join("logs"),
-            agent_heartbeat_interval: None,
-            json_output: None,
-            max_parallel: None,
-            repos: None,
-        };
-        fs::create_dir_all(&config.vault_dir).unwrap();
-        fs::create_dir_all(&config.pristines_dir).unwrap();
-        fs::create_dir_all(&config.clones_dir).unwrap();
-        (temp_dir, config)
-    }
+    use crate::test_support::create_test_config;
 
     #[test]
     fn test_destroy_pristine_success() {
@@ -387,7 +470,7 @@ mod tests {
         metadata.save("test-repo", &config).unwrap();
 
         // Destroy clone by suffix
-        let result = destroy_clone("abc123", &config);
+        let result = destroy_clone("abc123", false, &config);
         assert!(result.is_ok());
 
         // Clone should be removed from metadata
@@ -404,7 +487,7 @@ mod tests {
         fs::create_dir_all(&clone_path).unwrap();
 
         // Destroy by directory name
-        let result = destroy_clone("standalone-clone", &config);
+        let result = destroy_clone("standalone-clone", false, &config);
         assert!(result.is_ok());
         assert!(!clone_path.exists());
     }
@@ -413,7 +496,7 @@ mod tests {
     fn test_destroy_clone_not_found() {
         let (_temp, config) = create_test_config();
 
-        let result = destroy_clone("nonexistent", &config);
+        let result = destroy_clone("nonexistent", false, &config);
         assert!(result.is_err());
     }
 
@@ -432,7 +515,7 @@ mod tests {
         fs::create_dir_all(&pristine_path).unwrap();
 
         // destroy_target should recognize this as a pristine
-        let result = destroy_target("test-repo", &config);
+        let result = destroy_target("test-repo", false, &config);
         assert!(result.is_ok());
         assert!(!pristine_path.exists());
     }
@@ -444,7 +527,7 @@ mod tests {
         let clone_path = config.clones_dir.join("some-clone");
         fs::create_dir_all(&clone_path).unwrap();
 
-        let result = destroy_target("some-clone", &config);
+        let result = destroy_target("some-clone", false, &config);
         assert!(result.is_ok());
         assert!(!clone_path.exists());
     }
@@ -453,7 +536,7 @@ mod tests {
     fn test_destroy_target_not_found() {
         let (_temp, config) = create_test_config();
 
-        let result = destroy_target("nonexistent", &config);
+        let result = destroy_target("nonexistent", false, &config);
         assert!(result.is_err());
     }
 
@@ -1,8 +1,9 @@
-use log::{debug, info};
+use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
 use crate::error::Result;
+use crate::journal::{self, Change};
 use crate::metadata::Metadata;
 use crate::vault::Vault;
 
@@ -12,6 +13,16 @@ struct ExportEntry {
     url: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    archived: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,6 +47,11 @@ pub fn export_vault(config: &Config) -> Result<String> {
             name: entry.name.clone(),
             url: entry.url.clone(),
             aliases,
+            description: entry.description.clone(),
+            tags: entry.tags.clone(),
+            owner: entry.owner.clone(),
+            notes: entry.notes.clone(),
+            archived: entry.archived,
         });
     }
 
@@ -58,34 +74,76 @@ pub fn import_vault_from_string(yaml: &str, config: &Config) -> Result<usize> {
     let data: ExportData = serde_yml::from_str(yaml)
         .map_err(|e| crate::error::RepomanError::ConfigError(e.to_string()))?;
 
-    let mut vault = Vault::load(config)?;
-    let mut count = 0;
-
-    for entry in &data.repositories {
-        if vault.contains(&entry.name) {
-            debug!("import_vault: skipping '{}' (already in vault)", entry.name);
-            println!("  Skipping {} (already in vault)", entry.name);
-            continue;
+    // Vault changes happen in one transaction; metadata and journal entries
+    // for what was added follow it, as in `add`
+    let added = Vault::update(config, |vault| {
+        let mut added = Vec::new();
+        for entry in &data.repositories {
+            if vault.contains(&entry.name) {
+                debug!("import_vault: skipping '{}' (already in vault)", entry.name);
+                println!("  Skipping {} (already in vault)", entry.name);
+                continue;
+            }
+            if let Some(existing) = vault.find_by_url(&entry.url) {
+                warn!(
+                    "import_vault: skipping '{}' (same remote as '{}')",
+                    entry.name, existing.name
+                );
+                println!(
+                    "  Skipping {} (same remote as '{}': {})",
+                    entry.name, existing.name, existing.url
+                );
+                continue;
+            }
+            if let Some(other) = vault.nesting_conflict(&entry.name) {
+                warn!(
+                    "import_vault: skipping '{}' (overlaps '{}')",
+                    entry.name, other
+                );
+                println!(
+                    "  Skipping {} (overlaps '{}': one would be stored inside the other)",
+                    entry.name, other
+                );
+                continue;
+            }
+
+            vault.add_entry(entry.name.clone(), entry.url.clone())?;
+            if let Some(imported) = vault.get_entry_mut(&entry.name) {
+                imported.description.clone_from(&entry.description);
+                imported.tags.clone_from(&entry.tags);
+                imported.owner.clone_from(&entry.owner);
+                imported.notes.clone_from(&entry.notes);
+                imported.archived = entry.archived;
+            }
+
+            // Add aliases
+            for alias in &entry.aliases {
+                let _ = vault.add_alias(alias.clone(), entry.name.clone());
+            }
+            added.push(entry);
         }
+        Ok(added)
+    })?;
 
-        vault.add_entry(entry.name.clone(), entry.url.clone())?;
-
+    for entry in &added {
         // Create metadata
         let metadata = Metadata::new(vec![entry.url.clone()]);
         metadata.save(&entry.name, config)?;
 
-        // Add aliases
-        for alias in &entry.aliases {
-            let _ = vault.add_alias(alias.clone(), entry.name.clone());
-        }
-
+        journal::record(
+            config,
+            "import",
+            Change::RepoAdded {
+                name: entry.name.clone(),
+                url: entry.url.clone(),
+            },
+            Vec::new(),
+        );
         println!("  Added {}", entry.name);
-        count += 1;
     }
 
-    vault.save(config)?;
-    info!("import_vault: imported {} new repositories", count);
-    Ok(count)
+    info!("import_vault: imported {} new repositories", added.len());
+    Ok(added.len())
 }
 
 /// Import repositories from a YAML file. Skips duplicates. Returns count of newly added repos.
@@ -97,25 +155,7 @@ pub fn import_vault(path: &str, config: &Config) -> Result<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::TempDir;
-
-    fn create_test_config() -> (TempDir, Config) {
-        let temp_dir = TempDir::new().unwrap();
-        let base = temp_dir.path().to_path_buf();
-        let config = Config {
-            vault_dir: base.join("vault"),
-            pristines_dir: base.join("pristines"),
-            clones_dir: base.join("clones"),
-            plugins_dir: base.join("plugins"),
-            logs_dir: base.join("logs"),
-            agent_heartbeat_interval: None,
-            json_output: None,
-            max_parallel: None,
-            repos: None,
-        };
-        std::fs::create_dir_all(&config.vault_dir).unwrap();
-        (temp_dir, config)
-    }
+    use crate::test_support::create_test_config;
 
     #[test]
     fn test_export_empty_vault() {
@@ -145,6 +185,9 @@ mod tests {
         vault
             .add_alias("r1".to_string(), "repo1".to_string())
             .unwrap();
+        vault.entries[0].tags = vec!["work".to_string()];
+        vault.entries[0].description = Some("First repo".to_string());
+        vault.entries[1].archived = true;
         vault.save(&config).unwrap();
 
         // Create metadata
@@ -174,6 +217,10 @@ mod tests {
         assert!(vault2.contains("repo1"));
         assert!(vault2.contains("repo2"));
         assert_eq!(vault2.resolve_name("r1"), "repo1");
+        let repo1 = vault2.get_entry("repo1").unwrap();
+        assert_eq!(repo1.tags, vec!["work"]);
+        assert_eq!(repo1.description.as_deref(), Some("First repo"));
+        assert!(vault2.get_entry("repo2").unwrap().archived);
     }
 
     #[test]
@@ -188,13 +235,13 @@ mod tests {
         vault.save(&config).unwrap();
 
         // Write import file with repo1 and repo2
-        let yaml = r#"
+        let yaml = r"
 repositories:
   - name: repo1
     url: url1
   - name: repo2
     url: url2
-"#;
+";
         let import_path = temp.path().join("import.yaml");
         std::fs::write(&import_path, yaml).unwrap();
 
@@ -205,4 +252,39 @@ repositories:
         assert!(vault.contains("repo1"));
         assert!(vault.contains("repo2"));
     }
+
+    #[test]
+    fn test_import_skips_duplicate_remotes() {
+        let (temp, config) = create_test_config();
+
+        let mut vault = Vault::default();
+        vault
+            .add_entry(
+                "repo1".to_string(),
+                "git@github.com:user/repo1.git".to_string(),
+            )
+            .unwrap();
+        vault.save(&config).unwrap();
+
+        // Same remote as repo1 under another name and URL spelling
+        let yaml = r"
+repositories:
+  - name: repo1-https
+    url: https://github.com/user/repo1
+  - name: repo1-ssh
+    url: ssh://git@GitHub.com:22/user/repo1.git/
+  - name: repo2
+    url: https://github.com/user/repo2
+";
+        let import_path = temp.path().join("import.yaml");
+        std::fs::write(&import_path, yaml).unwrap();
+
+        let count = import_vault(import_path.to_str().unwrap(), &config).unwrap();
+        assert_eq!(count, 1); // only repo2 added
+
+        let vault = Vault::load(&config).unwrap();
+        assert!(vault.contains("repo2"));
+        assert!(!vault.contains("repo1-https"));
+        assert!(!vault.contains("repo1-ssh"));
+    }
 }
@@ -7,6 +7,7 @@ use std::process::Command;
 
 use crate::config::Config;
 use crate::error::Result;
+use crate::journal::{self, Change};
 use crate::metadata::Metadata;
 use crate::vault::Vault;
 
@@ -24,13 +25,14 @@ pub struct GcReport {
     pub pristines_gc_run: usize,
 }
 
-/// Find clones whose HEAD commit is older than `days` days.
-pub fn find_stale_clones(days: u64, config: &Config) -> Result<Vec<StaleClone>> {
+/// Find clones whose HEAD commit is older than `days` days, in repos carrying
+/// every tag in `tags` (all repos when empty).
+pub fn find_stale_clones(days: u64, tags: &[String], config: &Config) -> Result<Vec<StaleClone>> {
     let vault = Vault::load(config)?;
     let cutoff = Utc::now() - chrono::Duration::days(days as i64);
     let mut stale = Vec::new();
 
-    for repo_name in vault.get_all_names() {
+    for repo_name in vault.names_with_tags(tags, config) {
         let metadata = match Metadata::load(repo_name, config) {
             Ok(m) => m,
             Err(_) => continue,
@@ -75,11 +77,13 @@ pub fn find_stale_clones(days: u64, config: &Config) -> Result<Vec<StaleClone>>
 }
 
 /// Run `git gc --auto` on each pristine bare repo, then repack alternates for clones.
-fn gc_pristines(config: &Config, dry_run: bool) -> Result<usize> {
+/// Clone pins are refreshed first; a pristine whose clones can't be pinned is
+/// skipped rather than risk pruning objects they borrow.
+fn gc_pristines(tags: &[String], config: &Config, dry_run: bool) -> Result<usize> {
     let vault = Vault::load(config)?;
     let mut count = 0;
 
-    for repo_name in vault.get_all_names() {
+    for repo_name in vault.names_with_tags(tags, config) {
         let pristine_path = config.pristines_dir.join(repo_name);
         if !pristine_path.exists() {
             continue;
@@ -91,6 +95,21 @@ fn gc_pristines(config: &Config, dry_run: bool) -> Result<usize> {
             continue;
         }
 
+        if let Err(e) = super::pins::pin_clones(repo_name, config) {
+            warn!(
+                "gc: skipping '{}', couldn't pin its clones' objects: {}",
+                repo_name, e
+            );
+            println!(
+                "  Skipping gc of {}: couldn't pin its clones ({})",
+                repo_name, e
+            );
+            continue;
+        }
+        if let Err(e) = super::pins::unpin_orphans(repo_name, config) {
+            warn!("gc: failed to drop stale pins of '{}': {}", repo_name, e);
+        }
+
         info!("gc: running git gc --auto in {}", pristine_path.display());
         match Command::new("git")
             .args(["gc", "--auto"])
@@ -146,11 +165,15 @@ fn gc_pristines(config: &Config, dry_run: bool) -> Result<usize> {
 }
 
 /// Run the full GC cycle: find stale clones + gc pristines.
-pub fn run_gc(days: u64, dry_run: bool, config: &Config) -> Result<GcReport> {
-    info!("run_gc: days={}, dry_run={}", days, dry_run);
+/// `tags` limits both to repos carrying every listed tag.
+pub fn run_gc(days: u64, dry_run: bool, tags: &[String], config: &Config) -> Result<GcReport> {
+    info!(
+        "run_gc: days={}, dry_run={}, tags={:?}",
+        days, dry_run, tags
+    );
 
-    let stale_clones = find_stale_clones(days, config)?;
-    let pristines_gc_run = gc_pristines(config, dry_run)?;
+    let stale_clones = find_stale_clones(days, tags, config)?;
+    let pristines_gc_run = gc_pristines(tags, config, dry_run)?;
 
     if !dry_run {
         // Actually remove stale clones
@@ -162,12 +185,33 @@ pub fn run_gc(days: u64, dry_run: bool, config: &Config) -> Result<GcReport> {
                     sc.path.display()
                 );
                 let _ = std::fs::remove_dir_all(&sc.path);
+                if let Err(e) = super::pins::unpin_clone(
+                    &config.pristines_dir.join(&sc.repo_name),
+                    &sc.clone_name,
+                ) {
+                    warn!("gc: failed to unpin clone '{}': {}", sc.clone_name, e);
+                }
 
                 // Update metadata
-                if let Ok(mut metadata) = Metadata::load(&sc.repo_name, config) {
-                    metadata.remove_clone(&sc.clone_name);
-                    let _ = metadata.save(&sc.repo_name, config);
-                }
+                let entry =
+                    Metadata::update(
+                        &sc.repo_name,
+                        config,
+                        |m| Ok(m.remove_clone(&sc.clone_name)),
+                    )
+                    .ok()
+                    .flatten();
+                journal::record(
+                    config,
+                    "gc",
+                    Change::CloneDestroyed {
+                        repo: Some(sc.repo_name.clone()),
+                        entry,
+                        path: sc.path.clone(),
+                        archive: None,
+                    },
+                    vec![sc.path.clone()],
+                );
             }
         }
     }
@@ -0,0 +1,353 @@
+use log::info;
+use serde::Serialize;
+use std::path::Path;
+
+use super::rename::move_repo;
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::journal::{self, Change, JournalEntry};
+use crate::metadata::{CloneEntry, Metadata};
+use crate::vault::{Vault, VaultEntry};
+
+/// A journal entry annotated with whether it can still be undone
+#[derive(Debug, Serialize)]
+pub struct HistoryItem {
+    #[serde(flatten)]
+    pub entry: JournalEntry,
+    pub undone: bool,
+    pub undoable: bool,
+}
+
+/// Return the most recent `limit` journal entries, newest first
+pub fn list_history(limit: Option<usize>, config: &Config) -> Result<Vec<HistoryItem>> {
+    let entries = journal::read_entries(config)?;
+    let undone = journal::undone_ids(&entries);
+
+    let items = entries
+        .into_iter()
+        .rev()
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|entry| HistoryItem {
+            undone: undone.contains(&entry.id),
+            undoable: entry.change.is_undoable() && !undone.contains(&entry.id),
+            entry,
+        })
+        .collect();
+    Ok(items)
+}
+
+/// Pick the entry `undo` should act on: the given id, or the most recent
+/// undoable entry that hasn't been undone yet.
+pub fn resolve_undo_target(id: Option<u64>, config: &Config) -> Result<JournalEntry> {
+    let entries = journal::read_entries(config)?;
+    let undone = journal::undone_ids(&entries);
+
+    let Some(id) = id else {
+        return entries
+            .into_iter()
+            .rev()
+            .find(|e| e.change.is_undoable() && !undone.contains(&e.id))
+            .ok_or(RepomanError::NothingToUndo);
+    };
+
+    let entry = entries
+        .into_iter()
+        .find(|e| e.id == id)
+        .ok_or(RepomanError::JournalEntryNotFound(id))?;
+    if undone.contains(&id) {
+        return Err(RepomanError::CannotUndo(id, "already undone".to_string()));
+    }
+    if !entry.change.is_undoable() {
+        return Err(RepomanError::CannotUndo(
+            id,
+            format!("'{}' is not reversible", entry.change.summary()),
+        ));
+    }
+    Ok(entry)
+}
+
+/// Reverse a journal entry and record an `Undo` entry pointing at it
+pub fn undo_entry(entry: &JournalEntry, config: &Config) -> Result<()> {
+    info!("undo_entry: #{} ({})", entry.id, entry.change.summary());
+    let conflict = |msg: String| RepomanError::CannotUndo(entry.id, msg);
+
+    let paths = match &entry.change {
+        Change::RepoAdded { name, .. } => {
+            undo_add(name, config).map_err(|e| conflict(e.to_string()))?;
+            Vec::new()
+        }
+        Change::RepoRemoved {
+            entry: removed,
+            aliases,
+            metadata,
+        } => {
+            restore_repo(removed, aliases, metadata.as_deref(), config)
+                .map_err(|e| conflict(e.to_string()))?;
+            Vec::new()
+        }
+        Change::RepoRenamed { from, to } => {
+            // Not journaled as a rename of its own, or the next undo would redo it
+            move_repo(to, from, config)
+                .map_err(|e| conflict(e.to_string()))?
+                .1
+        }
+        Change::RepoEdited { before } => {
+            Vault::update(config, |v| {
+                let entry = v
+                    .get_entry_mut(&before.name)
+                    .ok_or_else(|| RepomanError::RepoNotInVault(before.name.clone()))?;
+                entry.description.clone_from(&before.description);
+                entry.tags.clone_from(&before.tags);
+                entry.owner.clone_from(&before.owner);
+                entry.notes.clone_from(&before.notes);
+                entry.archived = before.archived;
+                Ok(())
+            })
+            .map_err(|e| conflict(e.to_string()))?;
+            Vec::new()
+        }
+        Change::AliasAdded { alias, .. } => {
+            Vault::update(config, |v| v.remove_alias(alias))
+                .map_err(|e| conflict(e.to_string()))?;
+            Vec::new()
+        }
+        Change::AliasRemoved { alias, target } => {
+            Vault::update(config, |v| {
+                if !v.contains(target) {
+                    return Err(RepomanError::RepoNotInVault(target.clone()));
+                }
+                v.add_alias(alias.clone(), target.clone())
+            })
+            .map_err(|e| conflict(e.to_string()))?;
+            Vec::new()
+        }
+        Change::CloneDestroyed {
+            repo,
+            entry: clone,
+            path,
+            archive: Some(archive),
+        } => {
+            restore_clone(repo.as_deref(), clone.as_ref(), path, archive, config)
+                .map_err(|e| conflict(e.to_string()))?;
+            vec![path.clone()]
+        }
+        other => {
+            return Err(conflict(format!("'{}' is not reversible", other.summary())));
+        }
+    };
+
+    journal::append(config, "undo", Change::Undo { target: entry.id }, paths)?;
+    Ok(())
+}
+
+/// Undo `repoman add`: only safe while nothing has been built on top of the entry
+fn undo_add(name: &str, config: &Config) -> Result<()> {
+    if config.pristines_dir.join(name).exists() {
+        return Err(RepomanError::Other(format!(
+            "'{}' has a pristine; use 'repoman remove' instead",
+            name
+        )));
+    }
+    if let Ok(metadata) = Metadata::load(name, config)
+        && !metadata.clones.is_empty()
+    {
+        return Err(RepomanError::Other(format!(
+            "'{}' has clones; use 'repoman remove' instead",
+            name
+        )));
+    }
+
+    Vault::update(config, |v| {
+        if v.remove_entry(name).is_none() {
+            return Err(RepomanError::RepoNotInVault(name.to_string()));
+        }
+        v.remove_aliases_for(name);
+        Ok(())
+    })?;
+    Metadata::remove(name, config)?;
+    Ok(())
+}
+
+/// Undo `repoman remove`: restore the vault entry, aliases and metadata.
+/// Pristine and clone directories are gone, so metadata is trimmed to match disk.
+fn restore_repo(
+    entry: &VaultEntry,
+    aliases: &[String],
+    metadata: Option<&Metadata>,
+    config: &Config,
+) -> Result<()> {
+    Vault::update(config, |v| {
+        if v.contains(&entry.name) {
+            return Err(RepomanError::RepoAlreadyInVault(entry.name.clone()));
+        }
+        v.entries.push(entry.clone());
+        for alias in aliases {
+            if !v.list_aliases().contains_key(alias) {
+                v.add_alias(alias.clone(), entry.name.clone())?;
+            }
+        }
+        Ok(())
+    })?;
+
+    let mut metadata = metadata
+        .cloned()
+        .unwrap_or_else(|| Metadata::new(vec![entry.url.clone()]));
+    metadata.clones.retain(|c| c.path.exists());
+    if !config.pristines_dir.join(&entry.name).exists() {
+        metadata.pristine_created = None;
+    }
+    metadata.save(&entry.name, config)?;
+    Ok(())
+}
+
+/// Undo `repoman destroy --archive`: move the clone back and re-register it
+fn restore_clone(
+    repo: Option<&str>,
+    clone: Option<&CloneEntry>,
+    path: &Path,
+    archive: &Path,
+    config: &Config,
+) -> Result<()> {
+    if path.exists() {
+        return Err(RepomanError::Other(format!(
+            "{} already exists",
+            path.display()
+        )));
+    }
+    if !archive.exists() {
+        return Err(RepomanError::Other(format!(
+            "archive {} no longer exists",
+            archive.display()
+        )));
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(archive, path)?;
+    println!("Restored {}", path.display());
+
+    if let (Some(repo), Some(clone)) = (repo, clone) {
+        let vault = Vault::load(config)?;
+        if vault.contains(repo) {
+            Metadata::update(repo, config, |m| {
+                if m.get_clone(&clone.name).is_none() {
+                    m.clones.push(clone.clone());
+                }
+                Ok(())
+            })?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::{add_alias, destroy_clone, remove_alias};
+    use crate::test_support::create_test_config;
+
+    fn add_repo(name: &str, config: &Config) {
+        Vault::update(config, |v| {
+            v.add_entry(name.to_string(), format!("url-{}", name))
+        })
+        .unwrap();
+        Metadata::new(vec![format!("url-{}", name)])
+            .save(name, config)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_undo_alias_add_and_remove() {
+        let (_temp, config) = create_test_config();
+        add_repo("repo", &config);
+
+        add_alias("r", "repo", &config).unwrap();
+        remove_alias("r", &config).unwrap();
+
+        // Most recent first: undoing the removal brings the alias back
+        let target = resolve_undo_target(None, &config).unwrap();
+        undo_entry(&target, &config).unwrap();
+        assert_eq!(
+            Vault::load(&config).unwrap().list_aliases().get("r"),
+            Some(&"repo".to_string())
+        );
+
+        // Next undo reverses the original add
+        let target = resolve_undo_target(None, &config).unwrap();
+        undo_entry(&target, &config).unwrap();
+        assert!(Vault::load(&config).unwrap().list_aliases().is_empty());
+
+        assert!(matches!(
+            resolve_undo_target(None, &config),
+            Err(RepomanError::NothingToUndo)
+        ));
+    }
+
+    #[test]
+    fn test_undo_rename() {
+        let (_temp, config) = create_test_config();
+        add_repo("old", &config);
+        crate::operations::rename_repo("old", "new", &config).unwrap();
+
+        let target = resolve_undo_target(None, &config).unwrap();
+        undo_entry(&target, &config).unwrap();
+
+        let vault = Vault::load(&config).unwrap();
+        assert!(vault.contains("old"));
+        assert!(!vault.contains("new"));
+
+        // The undo isn't itself a rename to undo; nothing else is left
+        assert!(matches!(
+            resolve_undo_target(None, &config),
+            Err(RepomanError::NothingToUndo)
+        ));
+    }
+
+    #[test]
+    fn test_undo_archived_clone_destroy() {
+        let (_temp, config) = create_test_config();
+        add_repo("repo", &config);
+        let clone_path = config.clones_dir.join("repo-feat");
+        std::fs::create_dir_all(&clone_path).unwrap();
+        std::fs::write(clone_path.join("file.txt"), "work").unwrap();
+        Metadata::update("repo", &config, |m| {
+            m.add_clone("feat".to_string(), clone_path.clone());
+            Ok(())
+        })
+        .unwrap();
+
+        destroy_clone("feat", true, &config).unwrap();
+        assert!(!clone_path.exists());
+
+        let target = resolve_undo_target(None, &config).unwrap();
+        undo_entry(&target, &config).unwrap();
+
+        assert!(clone_path.join("file.txt").exists());
+        let metadata = Metadata::load("repo", &config).unwrap();
+        assert!(metadata.get_clone("feat").is_some());
+    }
+
+    #[test]
+    fn test_undo_rejects_irreversible_entry() {
+        let (_temp, config) = create_test_config();
+        let id = journal::append(
+            &config,
+            "init",
+            Change::PristineCreated {
+                repo: "repo".to_string(),
+            },
+            Vec::new(),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            resolve_undo_target(Some(id), &config),
+            Err(RepomanError::CannotUndo(..))
+        ));
+        assert!(matches!(
+            resolve_undo_target(Some(99), &config),
+            Err(RepomanError::JournalEntryNotFound(99))
+        ));
+    }
+}
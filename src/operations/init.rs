@@ -1,18 +1,24 @@
-use git2::{FetchOptions, RemoteCallbacks, build::RepoBuilder};
-use indicatif::{ProgressBar, ProgressStyle};
+use git2::build::RepoBuilder;
 use log::{debug, error, info};
-use std::cell::RefCell;
 use std::path::PathBuf;
 
-use super::credentials;
 use crate::config::Config;
 use crate::error::{RepomanError, Result, git_error_with_context};
 use crate::hooks;
-use crate::metadata::Metadata;
+use crate::journal::{self, Change};
+use crate::metadata::{FetchFailure, Metadata};
+use crate::throttle;
 use crate::vault::Vault;
 
 /// Initialize a pristine (reference clone) for a single repository
-pub fn init_pristine(repo_name: &str, depth: Option<i32>, config: &Config) -> Result<PathBuf> {
+/// `filter` makes a partial clone (`blob:none`, `tree:0`, ...); it falls back to
+/// `clone_defaults.filter` like `depth` falls back to `clone_defaults.shallow`.
+pub fn init_pristine(
+    repo_name: &str,
+    depth: Option<i32>,
+    filter: Option<&str>,
+    config: &Config,
+) -> Result<PathBuf> {
     info!("init_pristine: starting for '{}'", repo_name);
 
     // Check if repo exists in vault
@@ -21,9 +27,11 @@ pub fn init_pristine(repo_name: &str, depth: Option<i32>, config: &Config) -> Re
         error!("init_pristine: '{}' not found in vault", repo_name);
         return Err(RepomanError::RepoNotInVault(repo_name.to_string()));
     }
+    let resolved = vault.resolve_name(repo_name).to_string();
+    let repo_name = resolved.as_str();
 
     // Load metadata to get git URL
-    let mut metadata = Metadata::load(repo_name, config)?;
+    let metadata = Metadata::load(repo_name, config)?;
     let git_url = metadata
         .default_url()
         .ok_or_else(|| RepomanError::InvalidRepoUrl(repo_name.to_string()))?
@@ -43,75 +51,7 @@ pub fn init_pristine(repo_name: &str, depth: Option<i32>, config: &Config) -> Re
         );
         return Err(RepomanError::PristineAlreadyExists(repo_name.to_string()));
     }
-
-    // Attempt counter MUST be declared before callbacks (drop order: callbacks
-    // dropped first, then counter — so the borrow in the closure stays valid).
-    let cred_attempts = std::cell::Cell::new(0u32);
-    let mut callbacks = RemoteCallbacks::new();
-
-    // Auth: config overrides metadata
-    let effective_auth = config.effective_auth(repo_name, &metadata);
-    credentials::setup_credentials(
-        &mut callbacks,
-        &cred_attempts,
-        effective_auth.as_ref(),
-        "init",
-    );
-
-    // Transfer progress with indicatif progress bar
-    let pb: RefCell<Option<ProgressBar>> = RefCell::new(None);
-    callbacks.transfer_progress(move |stats| {
-        let received = stats.received_objects();
-        let indexed = stats.indexed_objects();
-        let total = stats.total_objects();
-        let bytes = stats.received_bytes();
-        let mb = bytes as f64 / 1_048_576.0;
-
-        if total == 0 {
-            return true;
-        }
-
-        let mut pb_ref = pb.borrow_mut();
-        let bar = pb_ref.get_or_insert_with(|| {
-            let bar = ProgressBar::new(total as u64);
-            bar.set_style(
-                ProgressStyle::default_bar()
-                    .template("  {bar:40.cyan/blue} {pos}/{len} ({msg})")
-                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
-            );
-            bar
-        });
-
-        let (phase, done) = if received < total {
-            ("receiving", received)
-        } else {
-            ("indexing", indexed)
-        };
-
-        bar.set_position(done as u64);
-        bar.set_message(format!("{} {:.1} MiB", phase, mb));
-
-        if indexed == total && total > 0 {
-            bar.finish_and_clear();
-        }
-
-        true
-    });
-
-    // Server-side progress messages — only log to file, don't clutter console.
-    callbacks.sideband_progress(|msg| {
-        if let Ok(s) = std::str::from_utf8(msg) {
-            let s = s.trim();
-            if !s.is_empty() {
-                debug!("init remote: {}", s);
-            }
-        }
-        true
-    });
-
-    // Create fetch options with callbacks
-    let mut fetch_opts = FetchOptions::new();
-    fetch_opts.remote_callbacks(callbacks);
+    config.ensure_online("initialize a pristine")?;
 
     // Apply shallow clone depth if requested
     let effective_depth = depth.or_else(|| {
@@ -122,30 +62,152 @@ pub fn init_pristine(repo_name: &str, depth: Option<i32>, config: &Config) -> Re
             .and_then(|shallow| if shallow { Some(1) } else { None })
     });
     if let Some(d) = effective_depth {
-        fetch_opts.depth(d);
         debug!("init_pristine: using shallow depth={}", d);
     }
 
-    // Clone the repository (bare clone for pristine)
-    let mut builder = RepoBuilder::new();
-    builder.bare(true);
-    builder.fetch_options(fetch_opts);
+    // Partial clone filter: CLI flag, then clone_defaults.filter
+    let effective_filter = filter.map(String::from).or_else(|| {
+        config
+            .repo_config(repo_name)
+            .and_then(|r| r.clone_defaults.as_ref())
+            .and_then(|cd| cd.filter.clone())
+    });
+    if let Some(ref f) = effective_filter {
+        super::partial::validate_filter(f)?;
+        debug!("init_pristine: using partial clone filter={}", f);
+    }
 
     println!("Cloning {} into pristine...", repo_name);
-    info!(
-        "init_pristine: cloning '{}' -> {}",
-        git_url,
-        pristine_path.display()
-    );
 
-    builder.clone(&git_url, &pristine_path).map_err(|e| {
-        error!("init_pristine: clone failed for '{}': {}", repo_name, e);
-        git_error_with_context(e, repo_name)
-    })?;
+    // Namespaced names (`alice/utils`) live in nested directories
+    if let Some(parent) = pristine_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Try the default URL, then each configured mirror in order
+    throttle::configure(config)?;
+    let effective_auth = config.effective_auth(repo_name, &metadata);
+    let remote_name = metadata.default_remote().unwrap_or("origin").to_string();
+    let urls = config.fetch_urls(repo_name, &metadata);
+    let mut failures: Vec<FetchFailure> = Vec::new();
+    let mut cloned: Option<(git2::Repository, String)> = None;
+    let mut last_error: Option<RepomanError> = None;
+
+    for url in &urls {
+        if url != &git_url {
+            println!("  trying mirror {}...", url);
+        }
+        info!(
+            "init_pristine: cloning '{}' -> {}",
+            url,
+            pristine_path.display()
+        );
+        let _permit = throttle::host_permit(url, config);
+
+        let attempt = if let Some(ref f) = effective_filter {
+            // libgit2 can't make partial clones; the git CLI does it
+            throttle::warn_unmetered(repo_name, config);
+            super::partial::clone_partial_pristine(
+                url,
+                &pristine_path,
+                &remote_name,
+                f,
+                effective_depth,
+            )
+            .and_then(|()| Ok(git2::Repository::open_bare(&pristine_path)?))
+            .map_err(|e| (e.to_string(), e))
+        } else if !metadata.tracked_branches.is_empty() || !metadata.tracked_tags.is_empty() {
+            super::track::init_tracked_pristine(
+                url,
+                &pristine_path,
+                &remote_name,
+                &metadata,
+                effective_auth.as_ref(),
+                effective_depth,
+                repo_name,
+            )
+        } else {
+            // Attempt counter MUST be declared before the fetch options (drop order:
+            // callbacks dropped first, then counter — so the borrow stays valid).
+            let cred_attempts = std::cell::Cell::new(0u32);
+            let mut fetch_opts = super::sync::progress_fetch_options(
+                &cred_attempts,
+                effective_auth.as_ref(),
+                "init",
+            );
+            if let Some(d) = effective_depth {
+                fetch_opts.depth(d);
+            }
+
+            // Clone the repository (bare clone for pristine)
+            let mut builder = RepoBuilder::new();
+            builder.bare(true);
+            builder.fetch_options(fetch_opts);
+            let name = remote_name.clone();
+            builder.remote_create(move |repo, _name, url| repo.remote(&name, url));
+            builder.clone(url, &pristine_path).map_err(|e| {
+                (
+                    e.message().to_string(),
+                    git_error_with_context(e, repo_name),
+                )
+            })
+        };
 
-    // Update metadata
-    metadata.mark_pristine_created();
-    metadata.save(repo_name, config)?;
+        match attempt {
+            Ok(repo) => {
+                cloned = Some((repo, url.clone()));
+                break;
+            }
+            Err((reason, e)) => {
+                error!(
+                    "init_pristine: clone from '{}' failed for '{}': {}",
+                    url, repo_name, reason
+                );
+                failures.push(FetchFailure {
+                    url: url.clone(),
+                    reason,
+                });
+                last_error = Some(e);
+                // Don't leave a half-written pristine for the next URL to trip over
+                if pristine_path.exists() {
+                    std::fs::remove_dir_all(&pristine_path)?;
+                }
+            }
+        }
+    }
+
+    let Some((repo, cloned_from)) = cloned else {
+        Metadata::update(repo_name, config, |m| {
+            m.record_fetch(None, failures);
+            Ok(())
+        })?;
+        return Err(
+            last_error.unwrap_or_else(|| RepomanError::InvalidRepoUrl(repo_name.to_string()))
+        );
+    };
+    if cloned_from != git_url {
+        // Keep the remote on the canonical URL; later syncs try it first again
+        repo.remote_set_url(&remote_name, &git_url)?;
+        println!("  cloned from mirror {}", cloned_from);
+    }
+    // Partial clones fetch everything up front; drop what isn't tracked
+    super::track::prune_untracked_refs(&repo, &metadata)?;
+    super::sync::fetch_secondary_remotes(&repo, repo_name, &metadata, config);
+
+    // Update metadata as it is now, not the copy loaded before the clone
+    Metadata::update(repo_name, config, |m| {
+        m.record_fetch(Some(cloned_from), failures);
+        m.mark_pristine_created();
+        Ok(())
+    })?;
+    journal::record(
+        config,
+        "init",
+        Change::PristineCreated {
+            repo: repo_name.to_string(),
+        },
+        vec![pristine_path.clone()],
+    );
 
     hooks::run_post_init_pristine(config, repo_name, &pristine_path)?;
 
@@ -158,13 +220,15 @@ pub fn init_pristine(repo_name: &str, depth: Option<i32>, config: &Config) -> Re
     Ok(pristine_path)
 }
 
-/// Get list of repos that need initialization (not yet pristined)
-pub fn get_uninitialized_repos(config: &Config) -> Result<Vec<String>> {
+/// Get list of repos that need initialization (not yet pristined), optionally
+/// limited to entries carrying every tag in `tags`
+pub fn get_uninitialized_repos(tags: &[String], config: &Config) -> Result<Vec<String>> {
     let vault = Vault::load(config)?;
 
     let uninitialized: Vec<String> = vault
-        .get_all_names()
+        .names_with_tags(tags, config)
         .into_iter()
+        .filter(|name| vault.get_entry(name).is_some_and(|e| !e.archived))
         .filter(|name| {
             let pristine_path = config.pristines_dir.join(name);
             !pristine_path.exists()
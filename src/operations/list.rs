@@ -3,6 +3,7 @@ use colored::Colorize;
 use serde::Serialize;
 use std::path::PathBuf;
 
+use super::shallow::ShallowInfo;
 use crate::config::Config;
 use crate::error::Result;
 use crate::metadata::{CloneEntry, Metadata};
@@ -18,23 +19,45 @@ pub struct RepoStatus {
     pub has_pristine: bool,
     pub pristine_path: Option<PathBuf>,
     pub pristine_created: Option<DateTime<Utc>>,
+    /// Depth and cut-off of a shallow pristine (`None` = full history)
+    pub shallow: Option<ShallowInfo>,
     pub clones: Vec<CloneEntry>,
     pub last_sync: Option<DateTime<Utc>>,
     pub default_branch: Option<String>,
     pub latest_tag: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub owner: Option<String>,
+    pub notes: Option<String>,
+    pub archived: bool,
+    /// Consecutive sync failures since the last successful sync
+    pub sync_failures: u32,
+    /// Whether the agent stopped syncing the repo after repeated failures
+    pub quarantined: bool,
 }
 
 /// List all repositories with their status
 pub fn list_all_repos(config: &Config) -> Result<Vec<RepoStatus>> {
+    list_repos(&[], config)
+}
+
+/// List repositories carrying every tag in `tags` (all when empty)
+pub fn list_repos(tags: &[String], config: &Config) -> Result<Vec<RepoStatus>> {
     let vault = Vault::load(config)?;
+    let all_metadata = Metadata::load_all(config)?;
     let mut statuses = Vec::new();
 
-    for entry in &vault.entries {
+    for entry in vault
+        .entries
+        .iter()
+        .filter(|e| e.matches_tags(tags, config))
+    {
         let pristine_path = config.pristines_dir.join(&entry.name);
         let has_pristine = pristine_path.exists();
+        let shallow = super::shallow::shallow_info(&pristine_path);
 
         // Try to load metadata, use defaults if not available
-        let metadata = Metadata::load(&entry.name, config).ok();
+        let metadata = all_metadata.get(&entry.name);
 
         let status = RepoStatus {
             name: entry.name.clone(),
@@ -46,7 +69,8 @@ pub fn list_all_repos(config: &Config) -> Result<Vec<RepoStatus>> {
             } else {
                 None
             },
-            pristine_created: metadata.as_ref().and_then(|m| m.pristine_created),
+            pristine_created: metadata.and_then(|m| m.pristine_created),
+            shallow,
             clones: metadata
                 .as_ref()
                 .map(|m| m.clones.clone())
@@ -55,8 +79,17 @@ pub fn list_all_repos(config: &Config) -> Result<Vec<RepoStatus>> {
                 .as_ref()
                 .and_then(|m| m.last_sync.as_ref())
                 .map(|s| s.timestamp),
-            default_branch: metadata.as_ref().and_then(|m| m.default_branch.clone()),
-            latest_tag: metadata.as_ref().and_then(|m| m.latest_tag.clone()),
+            default_branch: metadata.and_then(|m| m.default_branch.clone()),
+            latest_tag: metadata.and_then(|m| m.latest_tag.clone()),
+            description: entry.description.clone(),
+            tags: entry.tags.clone(),
+            owner: entry.owner.clone(),
+            notes: entry.notes.clone(),
+            archived: entry.archived,
+            sync_failures: metadata
+                .and_then(|m| m.sync_failures.as_ref())
+                .map_or(0, |f| f.consecutive),
+            quarantined: metadata.is_some_and(Metadata::is_quarantined),
         };
 
         statuses.push(status);
@@ -70,8 +103,29 @@ pub fn format_repo_status(status: &RepoStatus) -> String {
     let mut output = String::new();
 
     // Header with name and URL
-    output.push_str(&format!("  {} \n", status.name.cyan()));
+    if status.archived {
+        output.push_str(&format!(
+            "  {} {}\n",
+            status.name.cyan(),
+            "(archived)".dimmed()
+        ));
+    } else {
+        output.push_str(&format!("  {} \n", status.name.cyan()));
+    }
+    if let Some(description) = &status.description {
+        output.push_str(&format!("    {}\n", description.italic()));
+    }
     output.push_str(&format!("    {} {}\n", "URL:".bold(), status.url));
+    if let Some(owner) = &status.owner {
+        output.push_str(&format!("    {} {}\n", "Owner:".bold(), owner));
+    }
+    if !status.tags.is_empty() {
+        output.push_str(&format!(
+            "    {} {}\n",
+            "Tags:".bold(),
+            status.tags.join(", ").yellow()
+        ));
+    }
     output.push_str(&format!(
         "    {} {}\n",
         "Added:".bold(),
@@ -95,6 +149,13 @@ pub fn format_repo_status(status: &RepoStatus) -> String {
                 created.format("%Y-%m-%d %H:%M")
             ));
         }
+        if let Some(shallow) = &status.shallow {
+            output.push_str(&format!(
+                "      {} {}\n",
+                "Shallow:".bold(),
+                shallow.summary().yellow()
+            ));
+        }
     } else {
         output.push_str(&format!(
             "    {} {} not initialized\n",
@@ -113,6 +174,21 @@ pub fn format_repo_status(status: &RepoStatus) -> String {
         ));
     }
 
+    if status.quarantined {
+        output.push_str(&format!(
+            "    {} {} after {} consecutive failures\n",
+            "Sync:".bold(),
+            "quarantined".red(),
+            status.sync_failures
+        ));
+    } else if status.sync_failures > 0 {
+        output.push_str(&format!(
+            "    {} {}\n",
+            "Sync failures:".bold(),
+            status.sync_failures.to_string().yellow()
+        ));
+    }
+
     // Default branch
     if let Some(branch) = &status.default_branch {
         output.push_str(&format!(
@@ -127,6 +203,13 @@ pub fn format_repo_status(status: &RepoStatus) -> String {
         output.push_str(&format!("    {} {}\n", "Latest tag:".bold(), tag));
     }
 
+    if let Some(notes) = &status.notes {
+        output.push_str(&format!("    {}\n", "Notes:".bold()));
+        for line in notes.lines() {
+            output.push_str(&format!("      {}\n", line));
+        }
+    }
+
     // Clones
     if status.clones.is_empty() {
         output.push_str(&format!("    {} none\n", "Clones:".bold()));
@@ -178,7 +261,7 @@ pub fn format_summary(statuses: &[RepoStatus]) -> String {
             "✗".red().to_string()
         };
         let clone_count = status.clones.len().to_string();
-        let last_sync = status.last_sync.map_or_else(
+        let mut last_sync = status.last_sync.map_or_else(
             || "never".to_string(),
             |t| {
                 format!(
@@ -188,6 +271,15 @@ pub fn format_summary(statuses: &[RepoStatus]) -> String {
                 )
             },
         );
+        if status.archived {
+            last_sync = format!("{} {}", last_sync, "[archived]".dimmed());
+        }
+        if status.quarantined {
+            last_sync = format!("{} {}", last_sync, "[quarantined]".red());
+        } else if status.sync_failures > 0 {
+            let failing = format!("[{} failures]", status.sync_failures);
+            last_sync = format!("{} {}", last_sync, failing.yellow());
+        }
 
         output.push_str(&format!(
             "{:<20} {:<12} {:<8} {:<20}\n",
@@ -218,26 +310,7 @@ fn truncate_string(s: &str, max_len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::TempDir;
-
-    fn create_test_config() -> (TempDir, Config) {
-        let temp_dir = TempDir::new().unwrap();
-        let base = temp_dir.path().to_path_buf();
-        let config = Config {
-            vault_dir: base.join("vault"),
-            pristines_dir: base.join("pristines"),
-            clones_dir: base.join("clones"),
-            plugins_dir: base.join("plugins"),
-            logs_dir: base.join("logs"),
-            agent_heartbeat_interval: None,
-            json_output: None,
-            max_parallel: None,
-            repos: None,
-        };
-        std::fs::create_dir_all(&config.vault_dir).unwrap();
-        std::fs::create_dir_all(&config.pristines_dir).unwrap();
-        (temp_dir, config)
-    }
+    use crate::test_support::create_test_config;
 
     #[test]
     fn test_truncate_string_short() {
@@ -307,10 +380,18 @@ mod tests {
             has_pristine: true,
             pristine_path: Some(PathBuf::from("/path")),
             pristine_created: None,
+            shallow: None,
             clones: vec![],
             last_sync: None,
             default_branch: None,
             latest_tag: None,
+            description: None,
+            tags: vec![],
+            owner: None,
+            notes: None,
+            archived: false,
+            sync_failures: 3,
+            quarantined: false,
         }];
 
         let summary = format_summary(&statuses);
@@ -318,6 +399,7 @@ mod tests {
         assert!(summary.contains("PRISTINE"));
         assert!(summary.contains("repo1"));
         assert!(summary.contains("✓"));
+        assert!(summary.contains("[3 failures]"));
     }
 
     #[test]
@@ -329,10 +411,18 @@ mod tests {
             has_pristine: false,
             pristine_path: None,
             pristine_created: None,
+            shallow: None,
             clones: vec![],
             last_sync: None,
             default_branch: None,
             latest_tag: None,
+            description: None,
+            tags: vec![],
+            owner: None,
+            notes: None,
+            archived: false,
+            sync_failures: 0,
+            quarantined: false,
         };
 
         let output = format_repo_status(&status);
@@ -351,10 +441,18 @@ mod tests {
             has_pristine: true,
             pristine_path: Some(PathBuf::from("/path/to/pristine")),
             pristine_created: Some(Utc::now()),
+            shallow: None,
             clones: vec![],
             last_sync: Some(Utc::now()),
             default_branch: Some("main".to_string()),
             latest_tag: Some("v1.0.0".to_string()),
+            description: None,
+            tags: vec![],
+            owner: None,
+            notes: None,
+            archived: false,
+            sync_failures: 10,
+            quarantined: true,
         };
 
         let output = format_repo_status(&status);
@@ -362,6 +460,7 @@ mod tests {
         assert!(output.contains("/path/to/pristine"));
         assert!(output.contains("Default branch: main"));
         assert!(output.contains("Latest tag: v1.0.0"));
+        assert!(output.contains("quarantined"));
     }
 
     #[test]
@@ -373,6 +472,7 @@ mod tests {
             has_pristine: true,
             pristine_path: None,
             pristine_created: None,
+            shallow: None,
             clones: vec![
                 CloneEntry {
                     name: "clone1".to_string(),
@@ -390,6 +490,13 @@ mod tests {
             last_sync: None,
             default_branch: None,
             latest_tag: None,
+            description: None,
+            tags: vec![],
+            owner: None,
+            notes: None,
+            archived: false,
+            sync_failures: 0,
+            quarantined: false,
         };
 
         let output = format_repo_status(&status);
@@ -397,4 +504,27 @@ mod tests {
         assert!(output.contains("clone1"));
         assert!(output.contains("clone2"));
     }
+
+    #[test]
+    fn test_list_repos_filters_by_tag() {
+        let (_temp, config) = create_test_config();
+        let mut vault = Vault::default();
+        vault
+            .add_entry("work-api".to_string(), "url1".to_string())
+            .unwrap();
+        vault
+            .add_entry("oss-lib".to_string(), "url2".to_string())
+            .unwrap();
+        vault.entries[0].tags = vec!["work".to_string()];
+        vault.entries[0].description = Some("Internal API".to_string());
+        vault.save(&config).unwrap();
+
+        let statuses = list_repos(&["work".to_string()], &config).unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].name, "work-api");
+
+        let output = format_repo_status(&statuses[0]);
+        assert!(output.contains("Internal API"));
+        assert!(output.contains("Tags: work"));
+    }
 }
@@ -0,0 +1,221 @@
+use log::info;
+use serde::Serialize;
+
+use crate::config::{Config, StateBackend};
+use crate::error::{RepomanError, Result};
+use crate::metadata::Metadata;
+use crate::schema::{self, StateFile};
+use crate::store;
+use crate::vault::Vault;
+
+/// Pending (or applied) schema migration for one state document.
+#[derive(Debug, Serialize)]
+pub struct FileMigration {
+    /// File path, or `state.db:<table>/<key>` on the SQLite backend.
+    pub location: String,
+    #[serde(skip)]
+    repo: Option<String>,
+    pub from_version: u32,
+    pub to_version: u32,
+    pub steps: Vec<String>,
+    /// Document was written by a newer repoman; it is left untouched.
+    pub too_new: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MigrationReport {
+    /// Number of state documents inspected.
+    pub scanned: usize,
+    /// Documents that are not at the current schema version.
+    pub files: Vec<FileMigration>,
+}
+
+impl MigrationReport {
+    /// True when at least one document needs (or needed) an upgrade.
+    pub fn has_pending(&self) -> bool {
+        self.files.iter().any(|f| !f.too_new)
+    }
+}
+
+/// Report what `repoman migrate` would change, without touching anything.
+/// Reads raw documents so that inspecting never triggers a load-time migration.
+pub fn check_migrations(config: &Config) -> Result<MigrationReport> {
+    let mut report = MigrationReport::default();
+    for doc in store::open(config).raw_documents()? {
+        report.scanned += 1;
+        let kind = if doc.repo.is_some() {
+            StateFile::Metadata
+        } else {
+            StateFile::Vault
+        };
+        let from_version = schema::version_of(&doc.value);
+        let to_version = kind.current_version();
+        if from_version == to_version {
+            continue;
+        }
+        report.files.push(FileMigration {
+            location: doc.location,
+            repo: doc.repo,
+            from_version,
+            to_version,
+            steps: schema::pending_migrations(kind, &doc.value),
+            too_new: from_version > to_version,
+        });
+    }
+    Ok(report)
+}
+
+/// Upgrade every state document to the current schema version, keeping a backup.
+pub fn run_migrations(config: &Config) -> Result<MigrationReport> {
+    let report = check_migrations(config)?;
+    if !report.has_pending() {
+        return Ok(report);
+    }
+
+    // JSON files are backed up one by one on load; the database is backed up whole
+    if config.state_backend() == StateBackend::Sqlite {
+        let db = store::sqlite_path(config);
+        let oldest = report
+            .files
+            .iter()
+            .map(|f| f.from_version)
+            .min()
+            .unwrap_or(0);
+        std::fs::copy(&db, schema::backup_path(&db, oldest))?;
+    }
+
+    // A no-op update loads (migrating in place) and re-saves with the current version
+    for plan in report.files.iter().filter(|f| !f.too_new) {
+        match plan.repo {
+            None => Vault::update(config, |_| Ok(()))?,
+            Some(ref repo) => Metadata::update(repo, config, |_| Ok(()))?,
+        }
+    }
+    info!(
+        "migrate: upgraded {} of {} state document(s)",
+        report.files.iter().filter(|f| !f.too_new).count(),
+        report.scanned
+    );
+    Ok(report)
+}
+
+/// Copy all state from the configured backend into `target`. The source is left
+/// untouched; switching over is done by setting `state_backend` in config.yaml.
+/// Returns the number of repos whose metadata was copied.
+pub fn convert_state(target: StateBackend, config: &Config) -> Result<usize> {
+    let current = config.state_backend();
+    if current == target {
+        return Err(RepomanError::ConfigError(format!(
+            "state_backend is already {:?}",
+            target
+        )));
+    }
+
+    let from = store::open(config);
+    let to = store::open_backend(target, config);
+    if !to.raw_documents()?.is_empty() {
+        return Err(RepomanError::ConfigError(format!(
+            "target store {} already contains state; move it aside first",
+            to.location()
+        )));
+    }
+
+    let copied = store::copy_state(from.as_ref(), to.as_ref())?;
+    info!(
+        "migrate: copied vault and {} metadata document(s) from {} to {}",
+        copied,
+        from.location(),
+        to.location()
+    );
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{METADATA_SCHEMA_VERSION, VAULT_SCHEMA_VERSION};
+    use crate::test_support::create_test_config;
+
+    fn write_legacy_state(config: &Config) {
+        std::fs::write(
+            config.vault_dir.join("vault.json"),
+            r#"{"entries": [{"name": "repo1", "url": "url1", "added_date": "2025-01-01T00:00:00Z"}]}"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(config.vault_dir.join("repo1")).unwrap();
+        std::fs::write(
+            config.vault_dir.join("repo1").join("metadata.json"),
+            r#"{"git_urls": ["url1"], "created_on": "2025-01-01T00:00:00Z", "last_updated": "2025-01-01T00:00:00Z", "default_branch": null, "tracked_branches": [], "clones": [], "sync_interval": 3600, "last_sync": null, "auth_config": null, "latest_tag": null, "pristine_created": null}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_check_does_not_modify() {
+        let (_temp, config) = create_test_config();
+        write_legacy_state(&config);
+        let before = std::fs::read_to_string(config.vault_dir.join("vault.json")).unwrap();
+
+        let report = check_migrations(&config).unwrap();
+        assert_eq!(report.scanned, 2);
+        assert_eq!(report.files.len(), 2);
+        assert!(report.has_pending());
+
+        let after = std::fs::read_to_string(config.vault_dir.join("vault.json")).unwrap();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_run_migrations_upgrades_all() {
+        let (_temp, config) = create_test_config();
+        write_legacy_state(&config);
+
+        run_migrations(&config).unwrap();
+        let report = check_migrations(&config).unwrap();
+        assert!(report.files.is_empty());
+
+        let vault = crate::vault::Vault::load(&config).unwrap();
+        assert_eq!(vault.schema_version, VAULT_SCHEMA_VERSION);
+        let metadata = crate::metadata::Metadata::load("repo1", &config).unwrap();
+        assert_eq!(metadata.schema_version, METADATA_SCHEMA_VERSION);
+        assert!(config.vault_dir.join("vault.json.v0.bak").exists());
+    }
+
+    #[test]
+    fn test_too_new_reported_not_pending() {
+        let (_temp, config) = create_test_config();
+        std::fs::write(
+            config.vault_dir.join("vault.json"),
+            format!(
+                r#"{{"schema_version": {}, "entries": []}}"#,
+                VAULT_SCHEMA_VERSION + 1
+            ),
+        )
+        .unwrap();
+
+        let report = check_migrations(&config).unwrap();
+        assert_eq!(report.files.len(), 1);
+        assert!(report.files[0].too_new);
+        assert!(!report.has_pending());
+    }
+
+    #[test]
+    fn test_convert_json_to_sqlite() {
+        let (_temp, mut config) = create_test_config();
+        write_legacy_state(&config);
+
+        let copied = convert_state(StateBackend::Sqlite, &config).unwrap();
+        assert_eq!(copied, 1);
+
+        config.state_backend = Some(StateBackend::Sqlite);
+        let vault = Vault::load(&config).unwrap();
+        assert!(vault.contains("repo1"));
+        let metadata = Metadata::load("repo1", &config).unwrap();
+        assert_eq!(metadata.default_url(), Some("url1"));
+
+        // Converting again into a populated store is refused
+        config.state_backend = None;
+        assert!(convert_state(StateBackend::Sqlite, &config).is_err());
+        assert!(convert_state(StateBackend::Json, &config).is_err());
+    }
+}
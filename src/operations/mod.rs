@@ -1,30 +1,64 @@
 pub mod add;
 pub mod alias;
+pub mod archive;
+pub mod bundle;
+pub mod changes;
 pub mod clone_op;
 pub mod credentials;
+pub mod dedupe;
+pub mod describe;
 pub mod destroy;
 pub mod export_import;
 pub mod gc;
+pub mod history;
 pub mod init;
 pub mod list;
+pub mod migrate;
 pub mod open;
+pub mod partial;
+pub mod pins;
 pub mod rebase;
+pub mod remote;
 pub mod remove;
+pub mod rename;
+pub mod shallow;
+pub mod sparse;
 pub mod status;
 pub mod sync;
+pub mod tag;
+pub mod track;
 pub mod update;
+pub mod vault_sync;
+pub mod verify;
 
 pub use add::*;
 pub use alias::*;
+pub use archive::*;
+pub use bundle::*;
+pub use changes::*;
 pub use clone_op::*;
+pub use dedupe::*;
+pub use describe::*;
 pub use destroy::*;
 pub use export_import::*;
 pub use gc::*;
+pub use history::*;
 pub use init::*;
 pub use list::*;
+pub use migrate::*;
 pub use open::*;
+pub use partial::*;
+pub use pins::*;
 pub use rebase::*;
+pub use remote::*;
 pub use remove::*;
+pub use rename::*;
+pub use shallow::*;
+pub use sparse::*;
 pub use status::*;
 pub use sync::*;
+pub use tag::*;
+pub use track::*;
 pub use update::*;
+pub use vault_sync::*;
+pub use verify::*;
@@ -23,7 +23,14 @@ pub fn find_path(target: &str, config: &Config) -> Result<PathBuf> {
         return Ok(pristine_path);
     }
 
-    // 2. Check clone suffixes in metadata
+    find_clone_path(target, config)
+}
+
+/// Find the path of a clone by suffix or full clone dir name (never a pristine).
+pub fn find_clone_path(target: &str, config: &Config) -> Result<PathBuf> {
+    let vault = Vault::load(config)?;
+
+    // 1. Check clone suffixes in metadata
     for repo_name in vault.get_all_names() {
         if let Ok(metadata) = Metadata::load(repo_name, config)
             && let Some(clone_entry) = metadata.get_clone(target)
@@ -38,7 +45,7 @@ pub fn find_path(target: &str, config: &Config) -> Result<PathBuf> {
         }
     }
 
-    // 3. Check full clone directory names
+    // 2. Check full clone directory names
     let clone_path = config.clones_dir.join(target);
     if clone_path.exists() {
         debug!(
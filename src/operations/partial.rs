@@ -0,0 +1,243 @@
+//! Partial-clone pristines (`blob:none`, `tree:0`, `blob:limit=<size>`).
+//! libgit2 can neither create nor fetch into partial clones, so these paths shell
+//! out to the git CLI; missing objects are fetched lazily from the promisor remote.
+
+use log::{info, warn};
+use std::path::Path;
+use std::sync::Once;
+
+use crate::error::{RepomanError, Result};
+use crate::util::run_git;
+
+static EXTENSIONS: Once = Once::new();
+
+/// Let libgit2 open repositories that carry `extensions.partialClone`. Without
+/// this, every `Repository::open` on a partial pristine (or its clones) fails.
+pub fn enable_partial_clone_support() {
+    EXTENSIONS.call_once(|| {
+        // SAFETY: called once, before any repository is opened by this code path
+        if let Err(e) = unsafe { git2::opts::set_extensions(&["partialclone"]) } {
+            warn!("enable_partial_clone_support: {}", e);
+        }
+    });
+}
+
+/// Check a filter spec: `blob:none`, `tree:<depth>` or `blob:limit=<n>[k|m|g]`
+pub fn validate_filter(spec: &str) -> Result<()> {
+    let valid = if spec == "blob:none" {
+        true
+    } else if let Some(depth) = spec.strip_prefix("tree:") {
+        !depth.is_empty() && depth.chars().all(|c| c.is_ascii_digit())
+    } else if let Some(limit) = spec.strip_prefix("blob:limit=") {
+        let digits = limit.trim_end_matches(['k', 'K', 'm', 'M', 'g', 'G']);
+        !digits.is_empty()
+            && digits.len() + 1 >= limit.len()
+            && digits.chars().all(|c| c.is_ascii_digit())
+    } else {
+        false
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(RepomanError::InvalidFilterSpec(spec.to_string()))
+    }
+}
+
+/// Filter a pristine was created with, read from its git config (`None` = full clone)
+pub fn pristine_filter(pristine_path: &Path) -> Option<String> {
+    enable_partial_clone_support();
+    let config = git2::Config::open(&pristine_path.join("config")).ok()?;
+    // Older git names the promisor in extensions.partialClone; newer git only
+    // marks the remote with `promisor = true` and `partialclonefilter`
+    if let Ok(remote) = config.get_string("extensions.partialclone") {
+        return config
+            .get_string(&format!("remote.{}.partialclonefilter", remote))
+            .ok();
+    }
+    let mut entries = config
+        .entries(Some(r"remote\..*\.partialclonefilter"))
+        .ok()?;
+    let entry = entries.next()?.ok()?;
+    entry.value().map(String::from)
+}
+
+/// `git clone --bare --filter=<spec>` a pristine, naming the remote `remote_name`
+pub fn clone_partial_pristine(
+    url: &str,
+    pristine_path: &Path,
+    remote_name: &str,
+    filter: &str,
+    depth: Option<i32>,
+) -> Result<()> {
+    enable_partial_clone_support();
+    info!(
+        "clone_partial_pristine: '{}' -> {} (filter={})",
+        url,
+        pristine_path.display(),
+        filter
+    );
+    let path = pristine_path.to_string_lossy();
+    let filter_arg = format!("--filter={}", filter);
+    let depth_arg = depth.map(|d| format!("--depth={}", d));
+    let mut args = vec![
+        "clone",
+        "--bare",
+        "--quiet",
+        filter_arg.as_str(),
+        "--origin",
+        remote_name,
+    ];
+    if let Some(ref d) = depth_arg {
+        args.push(d);
+    }
+    args.extend(["--", url, &path]);
+    run_git(&args).map(|_| ())
+}
+
+/// Fetch `refspecs` into a partial pristine from `source` (a remote name or a
+/// mirror URL), keeping the pristine's filter. Tags only arrive through `refspecs`;
+/// `prune` deletes refs the source no longer has.
+pub fn fetch_partial(
+    pristine_path: &Path,
+    source: &str,
+    filter: &str,
+    refspecs: &[String],
+    prune: bool,
+) -> Result<()> {
+    let path = pristine_path.to_string_lossy();
+    let filter_arg = format!("--filter={}", filter);
+    let mut args = super::pins::fetch_args(&path);
+    args.push(&filter_arg);
+    if prune {
+        args.push("--prune");
+    }
+    args.push(source);
+    args.extend(refspecs.iter().map(String::as_str));
+    run_git(&args).map(|_| ())
+}
+
+/// Fetch a secondary remote's branches into `refs/remotes/<name>/*` of a partial pristine
+pub fn fetch_partial_secondary(
+    pristine_path: &Path,
+    remote: &str,
+    filter: &str,
+    prune: bool,
+) -> Result<()> {
+    let path = pristine_path.to_string_lossy();
+    let filter_arg = format!("--filter={}", filter);
+    let refspec = format!("+refs/heads/*:refs/remotes/{}/*", remote);
+    let mut args = super::pins::fetch_args(&path);
+    args.push(&filter_arg);
+    if prune {
+        args.push("--prune");
+    }
+    args.extend([remote, refspec.as_str()]);
+    run_git(&args).map(|_| ())
+}
+
+/// Mark a clone of a partial pristine as a partial clone itself, so git fetches
+/// objects missing from the pristine on demand from `promisor` (the default remote)
+pub fn configure_partial_clone(
+    clone_repo: &git2::Repository,
+    promisor: &str,
+    filter: &str,
+) -> Result<()> {
+    let mut config = clone_repo.config()?;
+    config.set_i32("core.repositoryformatversion", 1)?;
+    config.set_bool(&format!("remote.{}.promisor", promisor), true)?;
+    config.set_str(&format!("remote.{}.partialclonefilter", promisor), filter)?;
+    Ok(())
+}
+
+/// Write the index and working tree of a partial clone for its current HEAD with
+/// the git CLI (libgit2 can't fetch the missing blobs)
+pub fn populate_partial_worktree(clone_path: &Path) -> Result<()> {
+    let path = clone_path.to_string_lossy();
+    run_git(&["-C", &path, "reset", "--quiet", "--hard"]).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::metadata::Metadata;
+    use crate::test_support::create_test_config;
+    use crate::vault::Vault;
+    use git2::{Repository, Signature};
+
+    #[test]
+    fn test_blobless_pristine_clone_fetches_blobs_lazily() {
+        let (temp, config) = create_test_config();
+
+        // Source repo that serves filtered fetches
+        let source = temp.path().join("source");
+        let repo = Repository::init(&source).unwrap();
+        let mut repo_config = repo.config().unwrap();
+        repo_config
+            .set_bool("uploadpack.allowFilter", true)
+            .unwrap();
+        repo_config
+            .set_bool("uploadpack.allowAnySHA1InWant", true)
+            .unwrap();
+        std::fs::write(source.join("big.txt"), "content").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("big.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+
+        // file:// so git uses the transport (plain paths ignore --filter)
+        let url = format!("file://{}", source.display());
+        Vault::update(&config, |v| v.add_entry("mono".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url]).save("mono", &config).unwrap();
+
+        crate::operations::init_pristine("mono", None, Some("blob:none"), &config).unwrap();
+        let pristine_path = config.pristines_dir.join("mono");
+        assert_eq!(
+            pristine_filter(&pristine_path).as_deref(),
+            Some("blob:none")
+        );
+        let status = crate::operations::get_detailed_status("mono", &config).unwrap();
+        assert_eq!(status.partial_filter.as_deref(), Some("blob:none"));
+
+        crate::operations::sync_pristine("mono", &config).unwrap();
+
+        let clone_path = crate::operations::clone_from_pristine(
+            "mono",
+            Some("work".to_string()),
+            None,
+            &[],
+            false,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(clone_path.join("big.txt")).unwrap(),
+            "content"
+        );
+    }
+
+    #[test]
+    fn test_validate_filter() {
+        for ok in [
+            "blob:none",
+            "tree:0",
+            "tree:2",
+            "blob:limit=1m",
+            "blob:limit=512",
+        ] {
+            assert!(validate_filter(ok).is_ok(), "{}", ok);
+        }
+        for bad in [
+            "",
+            "blob:all",
+            "tree:",
+            "tree:x",
+            "blob:limit=",
+            "blob:limit=1mm",
+        ] {
+            assert!(validate_filter(bad).is_err(), "{}", bad);
+        }
+    }
+}
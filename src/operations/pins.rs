@@ -0,0 +1,352 @@
+//! Pin refs that keep a pristine's objects alive for its clones. Clones read the
+//! pristine's object store through `objects/info/alternates`, but `git gc` in the
+//! pristine only keeps what the pristine's own refs reach. A branch deleted or
+//! force-pushed upstream, or the base of local work, could be pruned out from
+//! under a clone that still uses it, so those commits are pinned under
+//! `refs/repoman/clones/<clone>/<oid>`.
+
+use git2::{Oid, Repository};
+use log::{debug, info, warn};
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::journal::{self, Change};
+use crate::metadata::Metadata;
+use crate::util::run_git;
+
+pub(crate) const PIN_PREFIX: &str = "refs/repoman/clones/";
+
+/// Start of a git CLI fetch into the pristine at `path`. Auto gc is off: a
+/// fetch that moves refs would otherwise prune before the pins catch up.
+/// Pristines are compacted only by `gc` and `archive --compact`, which pin first.
+pub(crate) fn fetch_args(path: &str) -> Vec<&str> {
+    vec![
+        "-C",
+        path,
+        "-c",
+        "gc.auto=0",
+        "fetch",
+        "--quiet",
+        "--no-tags",
+    ]
+}
+
+fn pin_namespace(clone_name: &str) -> String {
+    format!("{}{}/", PIN_PREFIX, clone_name)
+}
+
+/// Commits the clone's HEAD, branches, remote-tracking branches and stash point
+/// at. Tags are left to the pristine, which mirrors them.
+fn clone_tips(clone: &Repository) -> Result<Vec<Oid>> {
+    let mut tips = Vec::new();
+    if let Ok(head) = clone.head()
+        && let Ok(commit) = head.peel_to_commit()
+    {
+        tips.push(commit.id());
+    }
+    for reference in clone.references()?.flatten() {
+        let Some(name) = reference.name() else {
+            continue;
+        };
+        let wanted = name.starts_with("refs/heads/")
+            || name.starts_with("refs/remotes/")
+            || name == "refs/stash";
+        if wanted && let Ok(commit) = reference.peel_to_commit() {
+            tips.push(commit.id());
+        }
+    }
+    Ok(tips)
+}
+
+/// Pristine commits `clone` relies on that no pristine branch or tag reaches.
+/// Commits only the clone has (local work) are walked back to the pristine
+/// commits they build on.
+fn needed_pins(pristine: &Repository, clone: &Repository) -> Result<BTreeSet<Oid>> {
+    let odb = pristine.odb()?;
+    let mut candidates = BTreeSet::new();
+    let mut seen = HashSet::new();
+    let mut stack = clone_tips(clone)?;
+    while let Some(oid) = stack.pop() {
+        if !seen.insert(oid) {
+            continue;
+        }
+        if odb.exists(oid) {
+            candidates.insert(oid);
+        } else if let Ok(commit) = clone.find_commit(oid) {
+            stack.extend(commit.parent_ids());
+        }
+    }
+    if candidates.is_empty() {
+        return Ok(candidates);
+    }
+
+    // git lists what only the candidates reach; a candidate in that list isn't
+    // kept by any ref but the pins
+    let path = pristine.path().to_string_lossy();
+    let exclude = format!("--exclude={}*", PIN_PREFIX);
+    let revs: Vec<String> = candidates.iter().map(Oid::to_string).collect();
+    let mut args = vec!["-C", &path, "rev-list"];
+    args.extend(revs.iter().map(String::as_str));
+    args.extend(["--not", &exclude, "--all"]);
+    let unreachable: HashSet<Oid> = run_git(&args)?
+        .lines()
+        .filter_map(|line| Oid::from_str(line.trim()).ok())
+        .collect();
+    candidates.retain(|oid| unreachable.contains(oid));
+    Ok(candidates)
+}
+
+/// The pins currently held for `clone_name`
+fn existing_pins(pristine: &Repository, clone_name: &str) -> Result<Vec<(String, Oid)>> {
+    let namespace = pin_namespace(clone_name);
+    Ok(pristine
+        .references()?
+        .flatten()
+        .filter_map(|r| {
+            let name = r.name()?;
+            name.starts_with(&namespace)
+                .then(|| r.target().map(|oid| (name.to_string(), oid)))
+                .flatten()
+        })
+        .collect())
+}
+
+/// Bring a clone's pins in the pristine up to date. Returns how many it holds.
+pub fn pin_clone(pristine_path: &Path, clone_name: &str, clone_path: &Path) -> Result<usize> {
+    super::partial::enable_partial_clone_support();
+    let pristine = Repository::open_bare(pristine_path)?;
+    let clone = Repository::open(clone_path)?;
+    let mut needed = needed_pins(&pristine, &clone)?;
+
+    for (name, oid) in existing_pins(&pristine, clone_name)? {
+        if !needed.remove(&oid) {
+            debug!("pin_clone: dropping {}", name);
+            pristine.find_reference(&name)?.delete()?;
+        }
+    }
+    let namespace = pin_namespace(clone_name);
+    for oid in &needed {
+        debug!("pin_clone: pinning {} for '{}'", oid, clone_name);
+        pristine.reference(
+            &format!("{}{}", namespace, oid),
+            *oid,
+            true,
+            "repoman: pin clone objects",
+        )?;
+    }
+    Ok(existing_pins(&pristine, clone_name)?.len())
+}
+
+/// Drop a clone's pins (the clone is gone). A missing pristine has none.
+pub fn unpin_clone(pristine_path: &Path, clone_name: &str) -> Result<()> {
+    if !pristine_path.exists() {
+        return Ok(());
+    }
+    super::partial::enable_partial_clone_support();
+    let pristine = Repository::open_bare(pristine_path)?;
+    for (name, _) in existing_pins(&pristine, clone_name)? {
+        pristine.find_reference(&name)?.delete()?;
+    }
+    Ok(())
+}
+
+/// Commits a clone relies on that nothing in the pristine protects from gc
+pub fn unpinned_commits(
+    pristine_path: &Path,
+    clone_name: &str,
+    clone_path: &Path,
+) -> Result<Vec<Oid>> {
+    super::partial::enable_partial_clone_support();
+    let pristine = Repository::open_bare(pristine_path)?;
+    let clone = Repository::open(clone_path)?;
+    let pinned: HashSet<Oid> = existing_pins(&pristine, clone_name)?
+        .into_iter()
+        .map(|(_, oid)| oid)
+        .collect();
+    Ok(needed_pins(&pristine, &clone)?
+        .into_iter()
+        .filter(|oid| !pinned.contains(oid))
+        .collect())
+}
+
+/// Refresh the pins of every clone of a repo that exists on disk
+pub fn pin_clones(repo_name: &str, config: &Config) -> Result<()> {
+    let pristine_path = config.pristines_dir.join(repo_name);
+    if !pristine_path.exists() {
+        return Ok(());
+    }
+    let metadata = Metadata::load(repo_name, config)?;
+    for clone in metadata.clones.iter().filter(|c| c.path.exists()) {
+        let pins = pin_clone(&pristine_path, &clone.name, &clone.path)?;
+        if pins > 0 {
+            info!(
+                "pin_clones: '{}' clone '{}' holds {} pin(s)",
+                repo_name, clone.name, pins
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Drop the pins of clones that are gone from the repo's metadata. A clone
+/// destroyed with `--archive` keeps its pins while the archived copy is on disk,
+/// so `undo` still brings back a working clone. Returns how many pins went.
+pub fn unpin_orphans(repo_name: &str, config: &Config) -> Result<usize> {
+    let pristine_path = config.pristines_dir.join(repo_name);
+    if !pristine_path.exists() {
+        return Ok(0);
+    }
+    let mut keep: HashSet<String> = Metadata::load(repo_name, config)?
+        .clones
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    for entry in journal::read_entries(config)? {
+        if let Change::CloneDestroyed {
+            repo: Some(repo),
+            entry: Some(clone),
+            archive: Some(archive),
+            ..
+        } = entry.change
+            && repo == repo_name
+            && archive.exists()
+        {
+            keep.insert(clone.name);
+        }
+    }
+
+    super::partial::enable_partial_clone_support();
+    let pristine = Repository::open_bare(&pristine_path)?;
+    let mut dropped = 0;
+    for mut reference in pristine
+        .references_glob(&format!("{}*", PIN_PREFIX))?
+        .flatten()
+    {
+        let orphan = reference
+            .name()
+            .and_then(|n| n.strip_prefix(PIN_PREFIX))
+            .and_then(|n| n.rsplit_once('/'))
+            .is_some_and(|(clone, _)| !keep.contains(clone));
+        if orphan {
+            reference.delete()?;
+            dropped += 1;
+        }
+    }
+    if dropped > 0 {
+        info!(
+            "unpin_orphans: dropped {} pin(s) of removed clones of '{}'",
+            dropped, repo_name
+        );
+    }
+    Ok(dropped)
+}
+
+/// `pin_clones` for callers that carry on regardless (clone, update, heartbeat)
+pub(crate) fn pin_clones_or_warn(repo_name: &str, config: &Config) {
+    if let Err(e) = pin_clones(repo_name, config) {
+        warn!("pin_clones: failed for '{}': {}", repo_name, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{commit, create_test_config};
+    use crate::vault::Vault;
+
+    #[test]
+    fn test_pins_survive_force_push_and_gc() {
+        let (temp, config) = create_test_config();
+
+        let source = temp.path().join("source");
+        let source_repo = Repository::init(&source).unwrap();
+        let base = commit(&source_repo, "a.txt", &[]);
+        let doomed = commit(&source_repo, "b.txt", &[base]);
+
+        let url = source.to_string_lossy().to_string();
+        Vault::update(&config, |v| v.add_entry("proj".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url]).save("proj", &config).unwrap();
+        crate::operations::init_pristine("proj", None, None, &config).unwrap();
+        let clone_path = crate::operations::clone_from_pristine(
+            "proj",
+            Some("work".to_string()),
+            None,
+            &[],
+            false,
+            &config,
+        )
+        .unwrap();
+        let pristine_path = config.pristines_dir.join("proj");
+
+        // Fresh clone: everything it uses is a pristine branch tip
+        assert_eq!(pin_clone(&pristine_path, "work", &clone_path).unwrap(), 0);
+
+        // Local work in the clone builds on `doomed`
+        let clone_repo = Repository::open(&clone_path).unwrap();
+        commit(&clone_repo, "local.txt", &[doomed]);
+
+        // Upstream moves on: `doomed` is behind the tip but still reachable
+        commit(&source_repo, "ahead.txt", &[doomed]);
+        crate::operations::sync_pristine("proj", &config).unwrap();
+        assert!(
+            unpinned_commits(&pristine_path, "work", &clone_path)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Upstream rewrites history: `doomed` is no longer on any pristine ref
+        let branch = source_repo.head().unwrap().shorthand().unwrap().to_string();
+        source_repo
+            .reference(&format!("refs/heads/{}", branch), base, true, "reset")
+            .unwrap();
+        commit(&source_repo, "c.txt", &[base]);
+        crate::operations::sync_pristine("proj", &config).unwrap();
+
+        assert_eq!(
+            unpinned_commits(&pristine_path, "work", &clone_path).unwrap(),
+            vec![doomed]
+        );
+        pin_clones("proj", &config).unwrap();
+        assert!(
+            unpinned_commits(&pristine_path, "work", &clone_path)
+                .unwrap()
+                .is_empty()
+        );
+        let pin = format!("{}work/{}", PIN_PREFIX, doomed);
+        let pristine = Repository::open_bare(&pristine_path).unwrap();
+        assert!(pristine.find_reference(&pin).is_ok());
+
+        // An aggressive gc keeps the pinned commit; the clone stays whole
+        let path = pristine_path.to_string_lossy();
+        crate::util::run_git(&["-C", &path, "gc", "--quiet", "--prune=now"]).unwrap();
+        let check = crate::operations::check_objects("work", &clone_path, true).unwrap();
+        assert!(check.is_ok(), "{:?}", check);
+
+        // Pins aren't branches: change reports and bundles don't see them
+        assert!(
+            !crate::operations::changes::ref_tips(&pristine)
+                .unwrap()
+                .contains_key(&pin)
+        );
+
+        // An archived clone keeps its pins while it can be restored
+        crate::operations::destroy_clone("work", true, &config).unwrap();
+        assert_eq!(unpin_orphans("proj", &config).unwrap(), 0);
+        assert!(pristine.find_reference(&pin).is_ok());
+        let archived = crate::operations::resolve_undo_target(None, &config).unwrap();
+        crate::operations::undo_entry(&archived, &config).unwrap();
+        assert!(clone_path.exists());
+
+        crate::operations::destroy_clone("work", false, &config).unwrap();
+        assert!(pristine.find_reference(&pin).is_err());
+
+        // Pins of a clone that is neither in metadata nor archived are dropped
+        pristine
+            .reference(&pin, doomed, false, "left behind")
+            .unwrap();
+        assert_eq!(unpin_orphans("proj", &config).unwrap(), 1);
+        assert!(pristine.find_reference(&pin).is_err());
+    }
+}
@@ -18,10 +18,12 @@ pub fn heartbeat_update_clones(repo_name: &str, config: &Config) -> Result<()> {
         return Ok(());
     }
 
-    let mut metadata = Metadata::load(repo_name, config)?;
-    let mut changed = false;
+    // Work from a snapshot: git operations below can take a while, and we must not hold
+    // the metadata lock across them. Flag changes are applied in one transaction at the end.
+    let metadata = Metadata::load(repo_name, config)?;
+    let mut flag_updates: Vec<(String, bool)> = Vec::new();
 
-    for clone_entry in &mut metadata.clones {
+    for clone_entry in &metadata.clones {
         if !clone_entry.path.exists() {
             debug!(
                 "heartbeat: clone '{}' path missing, skipping",
@@ -49,6 +51,14 @@ pub fn heartbeat_update_clones(repo_name: &str, config: &Config) -> Result<()> {
             }
         };
 
+        // A deepened pristine moves the boundary git needs to fetch from it
+        if let Err(e) = super::shallow::sync_clone_shallow(&pristine_path, &clone_entry.path) {
+            warn!(
+                "heartbeat: shallow boundary for clone '{}': {}",
+                clone_entry.name, e
+            );
+        }
+
         // Fetch from pristine remote (local, fast)
         let fetch_result = Command::new("git")
             .args(["-C", &clone_path_str, "fetch", remote_name])
@@ -135,8 +145,9 @@ pub fn heartbeat_update_clones(repo_name: &str, config: &Config) -> Result<()> {
             match ff {
                 Ok(output) if output.status.success() => {
                     info!("heartbeat: fast-forwarded clone '{}'", clone_entry.name);
-                    clone_entry.upstream_conflicts = false;
-                    changed = true;
+                    if clone_entry.upstream_conflicts {
+                        flag_updates.push((clone_entry.name.clone(), false));
+                    }
                 }
                 _ => {
                     warn!(
@@ -209,8 +220,7 @@ pub fn heartbeat_update_clones(repo_name: &str, config: &Config) -> Result<()> {
                         }
                     }
                     if clone_entry.upstream_conflicts {
-                        clone_entry.upstream_conflicts = false;
-                        changed = true;
+                        flag_updates.push((clone_entry.name.clone(), false));
                     }
                 }
                 _ => {
@@ -224,17 +234,27 @@ pub fn heartbeat_update_clones(repo_name: &str, config: &Config) -> Result<()> {
                         clone_entry.name
                     );
                     if !clone_entry.upstream_conflicts {
-                        clone_entry.upstream_conflicts = true;
-                        changed = true;
+                        flag_updates.push((clone_entry.name.clone(), true));
                     }
                 }
             }
         }
     }
 
-    if changed {
-        metadata.save(repo_name, config)?;
+    if !flag_updates.is_empty() {
+        // Apply by clone name so entries added or removed concurrently (e.g. by a CLI
+        // `clone` or `destroy`) are preserved.
+        Metadata::update(repo_name, config, |m| {
+            for (name, conflicts) in &flag_updates {
+                if let Some(entry) = m.clones.iter_mut().find(|c| &c.name == name) {
+                    entry.upstream_conflicts = *conflicts;
+                }
+            }
+            Ok(())
+        })?;
     }
+    // Clones moved (or gained local work); keep what they borrow pinned
+    super::pins::pin_clones_or_warn(repo_name, config);
 
     Ok(())
 }
@@ -244,38 +264,19 @@ mod tests {
     use super::*;
     use crate::config::Config;
     use crate::metadata::{CloneEntry, Metadata};
+    use crate::test_support::test_config_in;
     use chrono::Utc;
     use std::path::{Path, PathBuf};
     use std::process::Command;
     use tempfile::TempDir;
 
-    /// Create a Config rooted in `base`.
-    fn test_config(base: &Path) -> Config {
-        Config {
-            vault_dir: base.join("vault"),
-            pristines_dir: base.join("pristines"),
-            clones_dir: base.join("clones"),
-            plugins_dir: base.join("plugins"),
-            logs_dir: base.join("logs"),
-            agent_heartbeat_interval: None,
-            json_output: None,
-            max_parallel: None,
-            repos: None,
-        }
-    }
-
     /// Helper: initialise a bare "pristine" repo with one commit on `main`,
     /// clone it into the clones dir, add a "pristine" remote pointing back to
     /// the bare repo, and persist a Metadata file with a matching CloneEntry.
     ///
     /// Returns (config, clone_path).
     fn setup_pristine_and_clone(tmp: &Path, repo_name: &str) -> (Config, PathBuf) {
-        let config = test_config(tmp);
-
-        // Directories
-        std::fs::create_dir_all(&config.vault_dir).unwrap();
-        std::fs::create_dir_all(&config.pristines_dir).unwrap();
-        std::fs::create_dir_all(&config.clones_dir).unwrap();
+        let config = test_config_in(tmp);
 
         let pristine_path = config.pristines_dir.join(repo_name);
 
@@ -566,10 +567,8 @@ mod tests {
         let base = tmp.path();
         let repo_name = "no-pristine-repo";
 
-        let config = test_config(base);
-
-        // Create the vault dir but NOT the pristine dir
-        std::fs::create_dir_all(&config.vault_dir).unwrap();
+        // The repo has no pristine dir
+        let config = test_config_in(base);
 
         // Should return Ok(()) silently (no pristine path exists)
         let result = heartbeat_update_clones(repo_name, &config);
@@ -0,0 +1,253 @@
+use git2::Repository;
+use log::{debug, info, warn};
+
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::metadata::{Metadata, PRISTINE_REMOTE, RemoteEntry};
+use crate::vault::Vault;
+
+/// Resolve a repo name or alias to its vault name
+fn resolve_repo(name: &str, config: &Config) -> Result<String> {
+    let vault = Vault::load(config)?;
+    if !vault.contains(name) {
+        return Err(RepomanError::RepoNotInVault(name.to_string()));
+    }
+    Ok(vault.resolve_name(name).to_string())
+}
+
+/// Fetch spec a clone's `pristine` remote uses to mirror a secondary remote
+fn clone_fetch_spec(remote: &str) -> String {
+    format!("+refs/remotes/{0}/*:refs/remotes/{0}/*", remote)
+}
+
+/// List the named remotes of a repo (the default one first)
+pub fn list_repo_remotes(name: &str, config: &Config) -> Result<Vec<RemoteEntry>> {
+    let name = resolve_repo(name, config)?;
+    Ok(Metadata::load(&name, config)?.remotes)
+}
+
+/// Add a named remote to a repo. Existing pristines pick it up on the next sync;
+/// existing clones get the remote configured right away.
+pub fn add_repo_remote(name: &str, remote: &str, url: &str, config: &Config) -> Result<()> {
+    let name = resolve_repo(name, config)?;
+    info!("add_repo_remote: '{}' += {} ({})", name, remote, url);
+
+    let metadata = Metadata::update(&name, config, |m| {
+        m.add_remote(&name, remote, url)?;
+        Ok(m.clone())
+    })?;
+
+    let pristine_path = config.pristines_dir.join(&name);
+    if let Ok(repo) = Repository::open_bare(&pristine_path) {
+        super::sync::ensure_remote(&repo, remote, url)?;
+    }
+
+    for clone in &metadata.clones {
+        let Ok(repo) = Repository::open(&clone.path) else {
+            continue;
+        };
+        if repo.find_remote(remote).is_err() {
+            repo.remote(remote, url)?;
+        }
+        if repo.find_remote(PRISTINE_REMOTE).is_ok() {
+            repo.remote_add_fetch(PRISTINE_REMOTE, &clone_fetch_spec(remote))?;
+        }
+        debug!(
+            "add_repo_remote: configured '{}' in {}",
+            remote,
+            clone.path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Remove a named remote from a repo, along with its refs in the pristine.
+/// Clones only lose the remote if it still points at the same URL.
+pub fn remove_repo_remote(name: &str, remote: &str, config: &Config) -> Result<RemoteEntry> {
+    let name = resolve_repo(name, config)?;
+    info!("remove_repo_remote: '{}' -= {}", name, remote);
+
+    let (removed, metadata) = Metadata::update(&name, config, |m| {
+        let removed = m.remove_remote(&name, remote)?;
+        Ok((removed, m.clone()))
+    })?;
+
+    let pristine_path = config.pristines_dir.join(&name);
+    if let Ok(repo) = Repository::open_bare(&pristine_path)
+        && repo.find_remote(remote).is_ok()
+    {
+        // Also drops refs/remotes/<remote>/* via the remote's default refspec
+        repo.remote_delete(remote)?;
+    }
+
+    for clone in &metadata.clones {
+        let Ok(repo) = Repository::open(&clone.path) else {
+            continue;
+        };
+        let same_url = repo
+            .find_remote(remote)
+            .is_ok_and(|r| r.url() == Some(removed.url.as_str()));
+        if same_url {
+            repo.remote_delete(remote)?;
+        }
+        let mut git_config = repo.config()?;
+        let spec = regex_escape(&clone_fetch_spec(remote));
+        if let Err(e) = git_config.remove_multivar("remote.pristine.fetch", &format!("^{}$", spec))
+        {
+            debug!("remove_repo_remote: no fetch spec to drop in clone: {}", e);
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Make `remote` the default remote of a repo: the pristine mirrors its branches
+/// from the next sync on, and new clones track it.
+pub fn set_default_repo_remote(name: &str, remote: &str, config: &Config) -> Result<()> {
+    let name = resolve_repo(name, config)?;
+    info!("set_default_repo_remote: '{}' -> {}", name, remote);
+
+    let previous = Metadata::update(&name, config, |m| {
+        let previous = m.default_remote().map(String::from);
+        m.set_default_remote(&name, remote)?;
+        Ok(previous)
+    })?;
+
+    // Existing clones keep tracking the old default's branches
+    if previous.as_deref() != Some(remote) {
+        let metadata = Metadata::load(&name, config)?;
+        if !metadata.clones.is_empty() {
+            warn!(
+                "set_default_repo_remote: {} existing clone(s) of '{}' still track '{}'",
+                metadata.clones.len(),
+                name,
+                previous.unwrap_or_default()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Escape regex metacharacters for git's multivar matching
+fn regex_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_config;
+    use git2::Signature;
+    use std::path::Path;
+
+    /// Create a repo at `path` with one commit on `branch`
+    fn source_repo(path: &Path, branch: &str) {
+        let repo = Repository::init(path).unwrap();
+        std::fs::write(path.join("file.txt"), branch).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        let ref_name = format!("refs/heads/{}", branch);
+        repo.commit(Some(&ref_name), &sig, &sig, branch, &tree, &[])
+            .unwrap();
+        repo.set_head(&ref_name).unwrap();
+    }
+
+    #[test]
+    fn test_remote_metadata_operations() {
+        let (_temp, config) = create_test_config();
+        Vault::update(&config, |v| {
+            v.add_entry("proj".to_string(), "fork-url".to_string())
+        })
+        .unwrap();
+        Metadata::new(vec!["fork-url".to_string()])
+            .save("proj", &config)
+            .unwrap();
+
+        add_repo_remote("proj", "upstream", "canonical-url", &config).unwrap();
+        assert!(matches!(
+            add_repo_remote("proj", "upstream", "other", &config),
+            Err(RepomanError::RemoteAlreadyExists(_, _))
+        ));
+        assert!(add_repo_remote("proj", "pristine", "other", &config).is_err());
+
+        set_default_repo_remote("proj", "upstream", &config).unwrap();
+        let remotes = list_repo_remotes("proj", &config).unwrap();
+        assert_eq!(remotes[0].name, "upstream");
+        assert_eq!(remotes[1].name, "origin");
+
+        remove_repo_remote("proj", "origin", &config).unwrap();
+        assert!(matches!(
+            remove_repo_remote("proj", "origin", &config),
+            Err(RepomanError::RemoteNotFound(_, _))
+        ));
+        // The last remote can't go
+        assert!(remove_repo_remote("proj", "upstream", &config).is_err());
+    }
+
+    #[test]
+    fn test_secondary_remote_reaches_pristine_and_clone() {
+        let (temp, config) = create_test_config();
+        let fork = temp.path().join("fork");
+        let canonical = temp.path().join("canonical");
+        source_repo(&fork, "main");
+        source_repo(&canonical, "release");
+
+        let fork_url = fork.to_string_lossy().to_string();
+        Vault::update(&config, |v| {
+            v.add_entry("proj".to_string(), fork_url.clone())
+        })
+        .unwrap();
+        Metadata::new(vec![fork_url]).save("proj", &config).unwrap();
+        crate::operations::init_pristine("proj", None, None, &config).unwrap();
+
+        add_repo_remote("proj", "upstream", &canonical.to_string_lossy(), &config).unwrap();
+        crate::operations::sync_pristine("proj", &config).unwrap();
+
+        let pristine = Repository::open_bare(config.pristines_dir.join("proj")).unwrap();
+        assert!(pristine.find_reference("refs/heads/main").is_ok());
+        assert!(
+            pristine
+                .find_reference("refs/remotes/upstream/release")
+                .is_ok()
+        );
+
+        let clone_path = crate::operations::clone_from_pristine(
+            "proj",
+            Some("work".to_string()),
+            None,
+            &[],
+            false,
+            &config,
+        )
+        .unwrap();
+        let clone = Repository::open(&clone_path).unwrap();
+        assert!(clone.find_remote("origin").is_ok());
+        assert!(clone.find_remote("upstream").is_ok());
+        assert!(clone.find_reference("refs/remotes/origin/main").is_ok());
+        assert!(
+            clone
+                .find_reference("refs/remotes/upstream/release")
+                .is_ok()
+        );
+
+        remove_repo_remote("proj", "upstream", &config).unwrap();
+        let clone = Repository::open(&clone_path).unwrap();
+        assert!(clone.find_remote("upstream").is_err());
+        let pristine = Repository::open_bare(config.pristines_dir.join("proj")).unwrap();
+        assert!(
+            pristine
+                .find_reference("refs/remotes/upstream/release")
+                .is_err()
+        );
+    }
+}
@@ -3,13 +3,15 @@ use std::fs;
 
 use crate::config::Config;
 use crate::error::{RepomanError, Result};
+use crate::journal::{self, Change};
 use crate::metadata::Metadata;
+use crate::util;
 use crate::vault::Vault;
 
 /// Fully remove a repository: destroy all clones and pristine from disk,
 /// remove aliases, metadata, and vault entry.
 pub fn remove_repo(name: &str, config: &Config) -> Result<()> {
-    let mut vault = Vault::load(config)?;
+    let vault = Vault::load(config)?;
 
     // Resolve alias -> canonical name
     let canonical = vault.resolve_name(name).to_string();
@@ -23,11 +25,15 @@ pub fn remove_repo(name: &str, config: &Config) -> Result<()> {
         canonical, name
     );
 
+    let mut touched = Vec::new();
+
     // Load metadata (best-effort) and destroy all clones from disk
-    if let Ok(metadata) = Metadata::load(&canonical, config) {
+    let metadata = Metadata::load(&canonical, config).ok();
+    if let Some(metadata) = &metadata {
         for clone in &metadata.clones {
             if clone.path.exists() {
                 println!("  Removing clone: {}", clone.path.display());
+                touched.push(clone.path.clone());
                 if let Err(e) = fs::remove_dir_all(&clone.path) {
                     warn!(
                         "remove_repo: failed to remove clone '{}': {}",
@@ -43,7 +49,8 @@ pub fn remove_repo(name: &str, config: &Config) -> Result<()> {
     let pristine_path = config.pristines_dir.join(&canonical);
     if pristine_path.exists() {
         println!("  Removing pristine: {}", pristine_path.display());
-        if let Err(e) = fs::remove_dir_all(&pristine_path) {
+        touched.push(pristine_path.clone());
+        if let Err(e) = util::remove_dir_keeping(&pristine_path, &vault.nested_dirs(&canonical)) {
             warn!(
                 "remove_repo: failed to remove pristine '{}': {}",
                 pristine_path.display(),
@@ -52,28 +59,37 @@ pub fn remove_repo(name: &str, config: &Config) -> Result<()> {
         }
     }
 
-    // Remove metadata directory (vault/<repo>/)
-    let metadata_dir = config.vault_dir.join(&canonical);
-    if metadata_dir.exists() {
-        println!("  Removing metadata: {}", metadata_dir.display());
-        if let Err(e) = fs::remove_dir_all(&metadata_dir) {
-            warn!(
-                "remove_repo: failed to remove metadata dir '{}': {}",
-                metadata_dir.display(),
-                e
-            );
-        }
+    // Remove stored metadata (vault/<repo>/ or its state.db rows)
+    println!("  Removing metadata for {}", canonical);
+    if let Err(e) = Metadata::remove(&canonical, config) {
+        warn!(
+            "remove_repo: failed to remove metadata for '{}': {}",
+            canonical, e
+        );
     }
 
-    // Remove all aliases pointing at this repo
-    let removed_aliases = vault.remove_aliases_for(&canonical);
+    // Remove the vault entry and every alias pointing at it, against the vault
+    // as it is now (the directory removal above can take a while)
+    let (entry, removed_aliases) = Vault::update(config, |vault| {
+        let aliases = vault.remove_aliases_for(&canonical);
+        Ok((vault.remove_entry(&canonical), aliases))
+    })?;
     if !removed_aliases.is_empty() {
         println!("  Removed aliases: {}", removed_aliases.join(", "));
     }
 
-    // Remove vault entry and save
-    vault.remove_entry(&canonical);
-    vault.save(config)?;
+    if let Some(entry) = entry {
+        journal::record(
+            config,
+            "remove",
+            Change::RepoRemoved {
+                entry,
+                aliases: removed_aliases,
+                metadata: metadata.map(Box::new),
+            },
+            touched,
+        );
+    }
 
     println!("Repository '{}' removed", canonical);
     Ok(())
@@ -82,27 +98,7 @@ pub fn remove_repo(name: &str, config: &Config) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::TempDir;
-
-    fn create_test_config() -> (TempDir, Config) {
-        let temp_dir = TempDir::new().unwrap();
-        let base = temp_dir.path().to_path_buf();
-        let config = Config {
-            vault_dir: base.join("vault"),
-            pristines_dir: base.join("pristines"),
-            clones_dir: base.join("clones"),
-            plugins_dir: base.join("plugins"),
-            logs_dir: base.join("logs"),
-            agent_heartbeat_interval: None,
-            json_output: None,
-            max_parallel: None,
-            repos: None,
-        };
-        fs::create_dir_all(&config.vault_dir).unwrap();
-        fs::create_dir_all(&config.pristines_dir).unwrap();
-        fs::create_dir_all(&config.clones_dir).unwrap();
-        (temp_dir, config)
-    }
+    use crate::test_support::create_test_config;
 
     fn setup_repo(config: &Config, name: &str) {
         let mut vault = Vault::load(config).unwrap_or_default();
@@ -153,6 +149,34 @@ mod tests {
         assert!(!vault.aliases.contains_key("tr"));
     }
 
+    #[test]
+    fn test_remove_keeps_nested_namespace() {
+        let (_temp, config) = create_test_config();
+        setup_repo(&config, "alice/utils");
+        fs::write(config.pristines_dir.join("alice/utils/HEAD"), "ref").unwrap();
+
+        // `add` now refuses the overlap, but vaults from before it can hold both
+        let mut vault = Vault::load(&config).unwrap();
+        let mut alice = vault.get_entry("alice/utils").unwrap().clone();
+        alice.name = "alice".to_string();
+        vault.entries.push(alice);
+        vault.save(&config).unwrap();
+        Metadata::new(vec!["url".to_string()])
+            .save("alice", &config)
+            .unwrap();
+        fs::write(config.pristines_dir.join("alice/HEAD"), "ref").unwrap();
+
+        remove_repo("alice", &config).unwrap();
+
+        assert!(!config.pristines_dir.join("alice/HEAD").exists());
+        assert!(!config.vault_dir.join("alice/metadata.json").exists());
+        assert!(config.pristines_dir.join("alice/utils/HEAD").exists());
+        assert!(Metadata::load("alice/utils", &config).is_ok());
+        let vault = Vault::load(&config).unwrap();
+        assert!(!vault.contains("alice"));
+        assert!(vault.contains("alice/utils"));
+    }
+
     #[test]
     fn test_remove_not_in_vault() {
         let (_temp, config) = create_test_config();
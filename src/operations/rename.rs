@@ -0,0 +1,115 @@
+use log::info;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::journal::{self, Change};
+use crate::metadata::Metadata;
+use crate::vault::Vault;
+
+/// Rename a vault entry: moves metadata and the pristine directory, and repoints aliases.
+/// Returns the canonical name that was renamed (aliases are resolved).
+pub fn rename_repo(old_name: &str, new_name: &str, config: &Config) -> Result<String> {
+    let (canonical, paths) = move_repo(old_name, new_name, config)?;
+    journal::record(
+        config,
+        "rename",
+        Change::RepoRenamed {
+            from: canonical.clone(),
+            to: new_name.to_string(),
+        },
+        paths,
+    );
+    Ok(canonical)
+}
+
+/// `rename_repo` without the journal entry, for `undo`. Returns the canonical
+/// old name and the paths it moved.
+pub(crate) fn move_repo(
+    old_name: &str,
+    new_name: &str,
+    config: &Config,
+) -> Result<(String, Vec<PathBuf>)> {
+    let vault = Vault::load(config)?;
+
+    // Resolve alias
+    let canonical = vault.resolve_name(old_name).to_string();
+    if !vault.contains(&canonical) {
+        return Err(RepomanError::RepoNotInVault(canonical));
+    }
+
+    // Check new name doesn't conflict
+    if vault.contains(new_name) {
+        return Err(RepomanError::RepoAlreadyInVault(new_name.to_string()));
+    }
+    if let Some(other) = vault.nesting_conflict(new_name) {
+        return Err(RepomanError::RepoNameNested(
+            new_name.to_string(),
+            other.to_string(),
+        ));
+    }
+
+    // Move metadata to the new name
+    let metadata = Metadata::load(&canonical, config)?;
+    metadata.save(new_name, config)?;
+    Metadata::remove(&canonical, config)?;
+
+    // Rename pristine directory if it exists
+    let old_pristine = config.pristines_dir.join(&canonical);
+    let new_pristine = config.pristines_dir.join(new_name);
+    let mut paths = Vec::new();
+    if old_pristine.exists() {
+        if let Some(parent) = new_pristine.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::rename(&old_pristine, &new_pristine)?;
+        paths.push(new_pristine);
+    }
+
+    // Update vault entry and aliases pointing to old name
+    Vault::update(config, |vault| {
+        if let Some(entry) = vault.entries.iter_mut().find(|e| e.name == canonical) {
+            entry.name = new_name.to_string();
+        }
+        for target in vault.aliases.values_mut() {
+            if *target == canonical {
+                *target = new_name.to_string();
+            }
+        }
+        Ok(())
+    })?;
+
+    info!("rename_repo: '{}' -> '{}'", canonical, new_name);
+    Ok((canonical, paths))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_config;
+
+    #[test]
+    fn test_rename_moves_metadata_pristine_and_aliases() {
+        let (_temp, config) = create_test_config();
+        Vault::update(&config, |v| {
+            v.add_entry("old".to_string(), "url".to_string())?;
+            v.add_alias("o".to_string(), "old".to_string())
+        })
+        .unwrap();
+        Metadata::new(vec!["url".to_string()])
+            .save("old", &config)
+            .unwrap();
+        std::fs::create_dir_all(config.pristines_dir.join("old")).unwrap();
+
+        let canonical = rename_repo("o", "new", &config).unwrap();
+        assert_eq!(canonical, "old");
+
+        let vault = Vault::load(&config).unwrap();
+        assert!(vault.contains("new"));
+        assert!(!vault.contains("old"));
+        assert_eq!(vault.resolve_name("o"), "new");
+        assert!(Metadata::load("new", &config).is_ok());
+        assert!(Metadata::load("old", &config).is_err());
+        assert!(config.pristines_dir.join("new").exists());
+    }
+}
@@ -0,0 +1,403 @@
+//! Shallow pristines (`init --depth`, `clone_defaults.shallow`). libgit2 drops a
+//! pristine's `shallow` file when it fetches into it, so shallow pristines are
+//! fetched and deepened with the git CLI. Clones borrow the pristine's objects
+//! without its `shallow` file, so each clone gets a copy of the boundary it needs;
+//! otherwise `git log` and `git blame` fail on the first missing parent.
+
+use chrono::{DateTime, TimeZone, Utc};
+use git2::{Oid, Repository};
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::hooks;
+use crate::metadata::Metadata;
+use crate::throttle;
+use crate::util::run_git;
+use crate::vault::Vault;
+
+/// How far `repoman deepen` extends a shallow pristine's history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Deepen {
+    /// Keep this many commits from each branch tip
+    Depth(u32),
+    /// Keep every commit after this date (anything `git fetch --shallow-since` takes)
+    Since(String),
+    /// Fetch the complete history
+    Full,
+}
+
+impl fmt::Display for Deepen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Deepen::Depth(n) => write!(f, "depth {}", n),
+            Deepen::Since(date) => write!(f, "since {}", date),
+            Deepen::Full => write!(f, "full history"),
+        }
+    }
+}
+
+/// Where a shallow pristine's history stops
+#[derive(Debug, Clone, Serialize)]
+pub struct ShallowInfo {
+    /// Commits reachable from HEAD
+    pub depth: usize,
+    /// Commits whose parents were never fetched
+    pub boundary: usize,
+    /// Newest boundary commit date: history before it may be missing
+    pub cut_off: Option<DateTime<Utc>>,
+}
+
+impl ShallowInfo {
+    /// "depth 50, history before 2024-03-01 missing"
+    pub fn summary(&self) -> String {
+        match self.cut_off {
+            Some(date) => format!(
+                "depth {}, history before {} missing",
+                self.depth,
+                date.format("%Y-%m-%d")
+            ),
+            None => format!("depth {}", self.depth),
+        }
+    }
+}
+
+/// Result of `repoman deepen`
+#[derive(Debug, Serialize)]
+pub struct DeepenReport {
+    pub name: String,
+    /// `None` when the pristine already had its full history
+    pub before: Option<ShallowInfo>,
+    /// `None` once the pristine has its full history
+    pub after: Option<ShallowInfo>,
+}
+
+fn shallow_file(git_dir: &Path) -> PathBuf {
+    git_dir.join("shallow")
+}
+
+/// Boundary commits listed in `<git_dir>/shallow` (empty for a full repository)
+fn read_shallow(git_dir: &Path) -> Vec<Oid> {
+    std::fs::read_to_string(shallow_file(git_dir))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| Oid::from_str(line.trim()).ok())
+        .collect()
+}
+
+/// Whether a pristine was cloned or fetched with limited depth
+pub fn is_shallow(pristine_path: &Path) -> bool {
+    !read_shallow(pristine_path).is_empty()
+}
+
+/// Depth and cut-off of a shallow pristine (`None` = full history)
+pub fn shallow_info(pristine_path: &Path) -> Option<ShallowInfo> {
+    let roots = read_shallow(pristine_path);
+    if roots.is_empty() {
+        return None;
+    }
+    // libgit2's revwalk doesn't stop cleanly at the boundary; git does
+    let path = pristine_path.to_string_lossy();
+    let depth = run_git(&["-C", &path, "rev-list", "--count", "HEAD"])
+        .ok()
+        .and_then(|count| count.trim().parse().ok())
+        .unwrap_or(0);
+    super::partial::enable_partial_clone_support();
+    let repo = Repository::open_bare(pristine_path).ok()?;
+    let cut_off = roots
+        .iter()
+        .filter_map(|oid| repo.find_commit(*oid).ok())
+        .filter_map(|c| Utc.timestamp_opt(c.time().seconds(), 0).single())
+        .max();
+    Some(ShallowInfo {
+        depth,
+        boundary: roots.len(),
+        cut_off,
+    })
+}
+
+/// Parents named in a commit's raw object. Read raw because libgit2 hides the
+/// parents of boundary commits in a shallow repository.
+fn raw_parents(odb: &git2::Odb<'_>, oid: Oid) -> Vec<Oid> {
+    let Ok(object) = odb.read(oid) else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(object.data())
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.strip_prefix("parent "))
+        .filter_map(|hex| Oid::from_str(hex).ok())
+        .collect()
+}
+
+/// Write the clone's `.git/shallow` from its pristine's boundary (plus any of its
+/// own), keeping only commits whose parents the clone can't reach. Returns whether
+/// the clone's history is cut off.
+pub fn sync_clone_shallow(pristine_path: &Path, clone_path: &Path) -> Result<bool> {
+    let git_dir = clone_path.join(".git");
+    let mut candidates = read_shallow(pristine_path);
+    candidates.extend(read_shallow(&git_dir));
+    candidates.sort();
+    candidates.dedup();
+
+    let clone = Repository::open(clone_path)?;
+    let odb = clone.odb()?;
+    let boundary: Vec<Oid> = candidates
+        .into_iter()
+        .filter(|oid| odb.exists(*oid))
+        .filter(|oid| raw_parents(&odb, *oid).iter().any(|p| !odb.exists(*p)))
+        .collect();
+
+    let path = shallow_file(&git_dir);
+    if boundary.is_empty() {
+        if path.exists() {
+            debug!(
+                "sync_clone_shallow: {} has full history",
+                clone_path.display()
+            );
+            std::fs::remove_file(&path)?;
+        }
+        return Ok(false);
+    }
+    let mut content = boundary
+        .iter()
+        .map(Oid::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+    content.push('\n');
+    if std::fs::read_to_string(&path).ok().as_deref() != Some(content.as_str()) {
+        debug!(
+            "sync_clone_shallow: {} boundary commit(s) for {}",
+            boundary.len(),
+            clone_path.display()
+        );
+        std::fs::write(&path, content)?;
+    }
+    Ok(true)
+}
+
+/// `sync_clone_shallow` for every clone of a repo that exists on disk
+pub(crate) fn sync_clones_shallow_or_warn(repo_name: &str, config: &Config) {
+    let pristine_path = config.pristines_dir.join(repo_name);
+    let Ok(metadata) = Metadata::load(repo_name, config) else {
+        return;
+    };
+    for clone in metadata.clones.iter().filter(|c| c.path.exists()) {
+        if let Err(e) = sync_clone_shallow(&pristine_path, &clone.path) {
+            warn!(
+                "sync_clones_shallow: failed for '{}' clone '{}': {}",
+                repo_name, clone.name, e
+            );
+        }
+    }
+}
+
+/// Fetch `refspecs` into a shallow pristine from `source` (a remote name or a
+/// mirror URL) with the git CLI, which keeps the shallow boundary
+pub fn fetch_shallow(
+    pristine_path: &Path,
+    source: &str,
+    refspecs: &[String],
+    prune: bool,
+) -> Result<()> {
+    let path = pristine_path.to_string_lossy();
+    let mut args = super::pins::fetch_args(&path);
+    if prune {
+        args.push("--prune");
+    }
+    args.push(source);
+    args.extend(refspecs.iter().map(String::as_str));
+    run_git(&args).map(|_| ())
+}
+
+/// Fetch more history into a shallow pristine, then give its clones the new
+/// boundary. A pristine that already has its full history is left alone.
+pub fn deepen_pristine(name: &str, how: &Deepen, config: &Config) -> Result<DeepenReport> {
+    let vault = Vault::load(config)?;
+    if !vault.contains(name) {
+        return Err(RepomanError::RepoNotInVault(name.to_string()));
+    }
+    let name = vault.resolve_name(name).to_string();
+    super::archive::ensure_not_archived(&name, &vault)?;
+    let pristine_path = config.pristines_dir.join(&name);
+    if !pristine_path.exists() {
+        return Err(RepomanError::PristineNotFound(name));
+    }
+
+    let before = shallow_info(&pristine_path);
+    let already_deep = match (&before, how) {
+        (None, _) => true,
+        (Some(info), Deepen::Depth(n)) => info.depth >= *n as usize,
+        _ => false,
+    };
+    if already_deep {
+        debug!("deepen_pristine: '{}' needs no more history", name);
+        return Ok(DeepenReport {
+            name,
+            after: before.clone(),
+            before,
+        });
+    }
+    config.ensure_online("deepen a pristine")?;
+    throttle::warn_unmetered(&name, config);
+
+    let metadata = Metadata::load(&name, config)?;
+    let repo = Repository::open_bare(&pristine_path)?;
+    let head = super::track::head_branch(&repo);
+    let refspecs = super::track::pristine_refspecs(&metadata, head.as_deref());
+    let filter_arg =
+        super::partial::pristine_filter(&pristine_path).map(|f| format!("--filter={}", f));
+    let how_arg = match how {
+        Deepen::Depth(n) => format!("--depth={}", n),
+        Deepen::Since(date) => format!("--shallow-since={}", date),
+        Deepen::Full => "--unshallow".to_string(),
+    };
+    let remote_name = metadata.default_remote().unwrap_or("origin");
+    let refs_before = super::changes::ref_tips(&repo)?;
+    println!("Deepening {} ({})...", name, how);
+
+    let path = pristine_path.to_string_lossy();
+    let mut last_error = None;
+    let mut fetched = false;
+    for (i, url) in config.fetch_urls(&name, &metadata).iter().enumerate() {
+        let _permit = throttle::host_permit(url, config);
+        let source = if i == 0 { remote_name } else { url.as_str() };
+        let mut args = super::pins::fetch_args(&path);
+        args.push(&how_arg);
+        if let Some(ref f) = filter_arg {
+            args.push(f);
+        }
+        args.push(source);
+        args.extend(refspecs.iter().map(String::as_str));
+        match run_git(&args) {
+            Ok(_) => {
+                fetched = true;
+                break;
+            }
+            Err(e) => {
+                warn!("deepen_pristine: fetch from '{}' failed: {}", url, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    if !fetched {
+        return Err(last_error.unwrap_or_else(|| RepomanError::InvalidRepoUrl(name.clone())));
+    }
+
+    // The fetch also moves branches that changed upstream
+    super::track::prune_untracked_refs(&repo, &metadata)?;
+    let refs_after = super::changes::ref_tips(&repo)?;
+    let report = super::changes::diff_ref_tips(&repo, &refs_before, &refs_after);
+    Metadata::update(&name, config, |m| {
+        m.mark_synced("deepen");
+        m.record_changes(report);
+        Ok(())
+    })?;
+    sync_clones_shallow_or_warn(&name, config);
+    hooks::run_post_sync(config, &name, &pristine_path, &[])?;
+
+    let after = shallow_info(&pristine_path);
+    info!(
+        "deepen_pristine: '{}' now {}",
+        name,
+        after
+            .as_ref()
+            .map_or_else(|| "has full history".to_string(), ShallowInfo::summary)
+    );
+    Ok(DeepenReport {
+        name,
+        before,
+        after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{commit_on_head, create_test_config};
+
+    fn rev_count(path: &Path) -> usize {
+        let path = path.to_string_lossy();
+        run_git(&["-C", &path, "rev-list", "--count", "HEAD"])
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_shallow_pristine_sync_clone_and_deepen() {
+        let (temp, config) = create_test_config();
+
+        let source = temp.path().join("source");
+        let source_repo = Repository::init(&source).unwrap();
+        for file in ["1", "2", "3", "4", "5"] {
+            commit_on_head(&source_repo, file);
+        }
+
+        // libgit2's local transport can't fetch shallowly; git over file:// can
+        let url = format!("file://{}", source.display());
+        Vault::update(&config, |v| v.add_entry("proj".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url.clone()])
+            .save("proj", &config)
+            .unwrap();
+        let pristine_path = config.pristines_dir.join("proj");
+        let pristine_arg = pristine_path.to_string_lossy();
+        run_git(&[
+            "clone",
+            "--bare",
+            "--quiet",
+            "--depth=2",
+            &url,
+            &pristine_arg,
+        ])
+        .unwrap();
+
+        let info = shallow_info(&pristine_path).unwrap();
+        assert_eq!((info.depth, info.boundary), (2, 1));
+
+        // A sync keeps the boundary (libgit2 would drop it)
+        commit_on_head(&source_repo, "6");
+        crate::operations::sync_pristine("proj", &config).unwrap();
+        assert_eq!(shallow_info(&pristine_path).unwrap().depth, 3);
+        let metadata = Metadata::load("proj", &config).unwrap();
+        let change = &metadata.change_reports.last().unwrap().changes[0];
+        assert_eq!(change.kind, crate::metadata::RefChangeKind::Updated);
+        assert_eq!(change.commits, 1);
+        // The git CLI reports no stats; the object store's growth stands in
+        let attempt = metadata.sync_history.last().unwrap();
+        assert_eq!(attempt.received_objects, Some(3));
+        assert!(attempt.received_bytes.unwrap() > 0);
+
+        // Clones get the boundary so git can walk their history
+        let clone_path = crate::operations::clone_from_pristine(
+            "proj",
+            Some("work".to_string()),
+            None,
+            &[],
+            false,
+            &config,
+        )
+        .unwrap();
+        assert!(clone_path.join(".git/shallow").exists());
+        assert_eq!(rev_count(&clone_path), 3);
+
+        // Asking for less than it has is a no-op
+        let report = deepen_pristine("proj", &Deepen::Depth(2), &config).unwrap();
+        assert_eq!(report.after.unwrap().depth, 3);
+
+        let report = deepen_pristine("proj", &Deepen::Depth(5), &config).unwrap();
+        assert_eq!(report.before.unwrap().depth, 3);
+        assert_eq!(report.after.unwrap().depth, 5);
+        assert_eq!(rev_count(&clone_path), 5);
+
+        let report = deepen_pristine("proj", &Deepen::Full, &config).unwrap();
+        assert!(report.after.is_none());
+        assert!(!is_shallow(&pristine_path));
+        assert!(!clone_path.join(".git/shallow").exists());
+        assert_eq!(rev_count(&clone_path), 6);
+    }
+}
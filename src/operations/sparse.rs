@@ -0,0 +1,191 @@
+//! Cone-mode sparse checkout for clones. libgit2 has no sparse checkout support,
+//! so these paths use `git sparse-checkout`.
+
+use log::{debug, info};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::util::run_git;
+use crate::vault::Vault;
+
+/// Directories to check out for a new clone: explicit `paths` plus those of the
+/// named `profile` from `repos.<name>.sparse_profiles`
+pub fn sparse_paths_for(
+    repo_name: &str,
+    paths: &[String],
+    profile: Option<&str>,
+    config: &Config,
+) -> Result<Vec<String>> {
+    let mut result: Vec<String> = paths.iter().map(|p| normalize_path(p)).collect();
+    if let Some(profile) = profile {
+        let vault = Vault::load(config)?;
+        let repo_name = vault.resolve_name(repo_name);
+        let profile_paths = config
+            .repo_config(repo_name)
+            .and_then(|r| r.sparse_profiles.as_ref())
+            .and_then(|profiles| profiles.get(profile))
+            .ok_or_else(|| {
+                RepomanError::SparseProfileNotFound(profile.to_string(), repo_name.to_string())
+            })?;
+        result.extend(profile_paths.iter().map(|p| normalize_path(p)));
+    }
+    let mut seen = std::collections::HashSet::new();
+    result.retain(|p| seen.insert(p.clone()));
+    Ok(result)
+}
+
+/// Cone mode matches directories; `web/` and `web` mean the same thing
+fn normalize_path(path: &str) -> String {
+    path.trim_matches('/').to_string()
+}
+
+/// Whether a clone has sparse checkout enabled
+pub fn is_sparse(clone_path: &Path) -> bool {
+    git2::Repository::open(clone_path)
+        .and_then(|r| r.config())
+        .and_then(|c| c.get_bool("core.sparseCheckout"))
+        .unwrap_or(false)
+}
+
+/// Enable cone-mode sparse checkout on a freshly created clone and write its
+/// working tree. The index is still empty, so `sparse-checkout set` alone would
+/// leave nothing checked out; `reset --hard` populates the cone.
+pub(crate) fn init_sparse_checkout(clone_path: &Path, paths: &[String]) -> Result<()> {
+    info!(
+        "init_sparse_checkout: {} -> {:?}",
+        clone_path.display(),
+        paths
+    );
+    set_cone(clone_path, paths)?;
+    let path = clone_path.to_string_lossy();
+    run_git(&["-C", &path, "reset", "--quiet", "--hard"])?;
+    Ok(())
+}
+
+fn set_cone(clone_path: &Path, paths: &[String]) -> Result<()> {
+    let path = clone_path.to_string_lossy();
+    let mut args = vec!["-C", &path, "sparse-checkout", "set", "--cone"];
+    args.extend(paths.iter().map(String::as_str));
+    run_git(&args)?;
+    Ok(())
+}
+
+/// Directories checked out in a sparse clone (`target` as accepted by `open`)
+pub fn sparse_list(target: &str, config: &Config) -> Result<Vec<String>> {
+    let clone_path = super::open::find_clone_path(target, config)?;
+    if !is_sparse(&clone_path) {
+        return Err(RepomanError::CloneNotSparse(target.to_string()));
+    }
+    let path = clone_path.to_string_lossy();
+    let output = run_git(&["-C", &path, "sparse-checkout", "list"])?;
+    Ok(output.lines().map(String::from).collect())
+}
+
+/// Add directories to a clone's sparse checkout (turning it on for a full clone)
+pub fn sparse_add(target: &str, paths: &[String], config: &Config) -> Result<Vec<String>> {
+    let clone_path = super::open::find_clone_path(target, config)?;
+    let paths: Vec<String> = paths.iter().map(|p| normalize_path(p)).collect();
+    if is_sparse(&clone_path) {
+        let path = clone_path.to_string_lossy();
+        let mut args = vec!["-C", &path, "sparse-checkout", "add"];
+        args.extend(paths.iter().map(String::as_str));
+        run_git(&args)?;
+    } else {
+        debug!(
+            "sparse_add: enabling sparse checkout in {}",
+            clone_path.display()
+        );
+        set_cone(&clone_path, &paths)?;
+    }
+    sparse_list(target, config)
+}
+
+/// Remove directories from a clone's sparse checkout; their files leave the
+/// working tree (git refuses if they have uncommitted changes)
+pub fn sparse_remove(target: &str, paths: &[String], config: &Config) -> Result<Vec<String>> {
+    let current = sparse_list(target, config)?;
+    let remove: Vec<String> = paths.iter().map(|p| normalize_path(p)).collect();
+    let remaining: Vec<String> = current
+        .into_iter()
+        .filter(|p| !remove.contains(p))
+        .collect();
+    let clone_path = super::open::find_clone_path(target, config)?;
+    set_cone(&clone_path, &remaining)?;
+    Ok(remaining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RepoConfig;
+    use crate::metadata::Metadata;
+    use crate::test_support::create_test_config;
+    use git2::{Repository, Signature};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_sparse_clone_with_profile_and_adjust() {
+        let (temp, mut config) = create_test_config();
+
+        let source = temp.path().join("mono");
+        let repo = Repository::init(&source).unwrap();
+        let mut index = repo.index().unwrap();
+        for file in ["web/app.js", "api/main.rs", "docs/readme.md", "top.txt"] {
+            let path = source.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, file).unwrap();
+            index.add_path(Path::new(file)).unwrap();
+        }
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+
+        let mut repos = HashMap::new();
+        repos.insert(
+            "mono".to_string(),
+            RepoConfig {
+                sparse_profiles: Some(HashMap::from([(
+                    "frontend".to_string(),
+                    vec!["web/".to_string()],
+                )])),
+                ..Default::default()
+            },
+        );
+        config.repos = Some(repos);
+
+        let url = source.to_string_lossy().to_string();
+        Vault::update(&config, |v| v.add_entry("mono".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url]).save("mono", &config).unwrap();
+
+        assert!(matches!(
+            sparse_paths_for("mono", &[], Some("backend"), &config),
+            Err(RepomanError::SparseProfileNotFound(_, _))
+        ));
+        let paths =
+            sparse_paths_for("mono", &["docs".to_string()], Some("frontend"), &config).unwrap();
+        assert_eq!(paths, vec!["docs", "web"]);
+
+        let clone_path = crate::operations::clone_from_pristine(
+            "mono",
+            Some("ui".to_string()),
+            None,
+            &paths,
+            false,
+            &config,
+        )
+        .unwrap();
+        assert!(clone_path.join("web/app.js").exists());
+        assert!(clone_path.join("docs/readme.md").exists());
+        assert!(clone_path.join("top.txt").exists());
+        assert!(!clone_path.join("api/main.rs").exists());
+
+        assert_eq!(sparse_list("ui", &config).unwrap(), vec!["docs", "web"]);
+        sparse_add("ui", &["api/".to_string()], &config).unwrap();
+        assert!(clone_path.join("api/main.rs").exists());
+        let remaining = sparse_remove("ui", &["docs".to_string()], &config).unwrap();
+        assert_eq!(remaining, vec!["api", "web"]);
+        assert!(!clone_path.join("docs/readme.md").exists());
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use colored::Colorize;
 use git2::Repository;
 use log::{debug, warn};
@@ -5,9 +6,10 @@ use serde::Serialize;
 use std::fmt;
 use std::path::PathBuf;
 
+use super::shallow::ShallowInfo;
 use crate::config::Config;
 use crate::error::Result;
-use crate::metadata::Metadata;
+use crate::metadata::{FetchFailure, Metadata, SyncAttempt, SyncFailures};
 use crate::util;
 use crate::vault::Vault;
 
@@ -26,13 +28,29 @@ pub struct CloneStatus {
 pub struct DetailedStatus {
     pub name: String,
     pub url: String,
+    pub archived: bool,
     pub pristine_exists: bool,
+    /// Partial clone filter of the pristine (`None` = full clone)
+    pub partial_filter: Option<String>,
+    /// Depth and cut-off of a shallow pristine (`None` = full history)
+    pub shallow: Option<ShallowInfo>,
     pub pristine_branches: Vec<String>,
     pub clones: Vec<CloneStatus>,
     pub latest_tag: Option<String>,
     pub last_sync: Option<String>,
     pub last_sync_relative: Option<String>,
     pub sync_interval: Option<u64>,
+    /// Mirror the last fetch fell back to (`None` when the default URL worked)
+    pub fetched_from_mirror: Option<String>,
+    /// URLs that failed during the last fetch, in the order they were tried
+    pub fetch_failures: Vec<FetchFailure>,
+    /// Recent sync attempts, oldest first (`status --history`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sync_history: Vec<SyncAttempt>,
+    /// Consecutive failures since the last successful sync, and quarantine state
+    pub sync_failures: Option<SyncFailures>,
+    /// When the agent retries a failing repo (`None` when healthy or quarantined)
+    pub next_retry: Option<DateTime<Utc>>,
     pub alternates_ok: bool,
 }
 
@@ -40,6 +58,14 @@ impl fmt::Display for DetailedStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{}: {}", "Repository".bold(), self.name.cyan())?;
         writeln!(f, "  {}: {}", "URL".bold(), self.url)?;
+        if self.archived {
+            writeln!(
+                f,
+                "  {}: {}",
+                "Archived".bold(),
+                "yes (not synced)".yellow()
+            )?;
+        }
         writeln!(
             f,
             "  {}: {}",
@@ -51,6 +77,25 @@ impl fmt::Display for DetailedStatus {
             }
         )?;
 
+        if let Some(ref filter) = self.partial_filter {
+            writeln!(
+                f,
+                "  {}: {} (missing objects fetched on demand)",
+                "Partial clone".bold(),
+                filter.yellow()
+            )?;
+        }
+
+        if let Some(ref shallow) = self.shallow {
+            writeln!(
+                f,
+                "  {}: {} ('repoman deepen {}' fetches more)",
+                "Shallow".bold(),
+                shallow.summary().yellow(),
+                self.name
+            )?;
+        }
+
         if !self.pristine_branches.is_empty() {
             writeln!(
                 f,
@@ -74,6 +119,64 @@ impl fmt::Display for DetailedStatus {
         if let Some(interval) = self.sync_interval {
             writeln!(f, "  {}: {}s", "Sync interval".bold(), interval)?;
         }
+        if !self.sync_history.is_empty() {
+            let total = self.sync_history.len();
+            let ok = self.sync_history.iter().filter(|a| a.succeeded()).count();
+            let avg_ms =
+                self.sync_history.iter().map(|a| a.duration_ms).sum::<u64>() / total as u64;
+            let health = format!("{}/{} ok", ok, total);
+            writeln!(
+                f,
+                "  {}: {}, avg {:.1}s",
+                "Recent syncs".bold(),
+                if ok == total {
+                    health.green()
+                } else {
+                    health.yellow()
+                },
+                avg_ms as f64 / 1000.0
+            )?;
+        }
+        if let Some(ref failures) = self.sync_failures {
+            if failures.quarantined_at.is_some() {
+                writeln!(
+                    f,
+                    "  {}: {} after {} consecutive failures (retry with 'repoman sync {} --reset-failures')",
+                    "Sync".bold(),
+                    "QUARANTINED".red().bold(),
+                    failures.consecutive,
+                    self.name
+                )?;
+            } else {
+                let retry = self
+                    .next_retry
+                    .map(|t| format!(", next retry {}", t.format("%Y-%m-%d %H:%M:%S UTC")))
+                    .unwrap_or_default();
+                writeln!(
+                    f,
+                    "  {}: {} consecutive since {}{}",
+                    "Sync failures".bold(),
+                    failures.consecutive.to_string().yellow(),
+                    failures.first_failed.format("%Y-%m-%d %H:%M:%S UTC"),
+                    retry
+                )?;
+            }
+            writeln!(
+                f,
+                "  {}: {}",
+                "Last error".bold(),
+                failures.last_error.red()
+            )?;
+        }
+        if let Some(ref mirror) = self.fetched_from_mirror {
+            writeln!(f, "  {}: {}", "Fetched from mirror".bold(), mirror.yellow())?;
+        }
+        if !self.fetch_failures.is_empty() {
+            writeln!(f, "  {}:", "Last fetch failures".bold())?;
+            for failure in &self.fetch_failures {
+                writeln!(f, "    {}: {}", failure.url, failure.reason.red())?;
+            }
+        }
 
         if self.clones.is_empty() {
             writeln!(f, "  {}: none", "Clones".bold())?;
@@ -127,6 +230,12 @@ pub fn get_detailed_status(name: &str, config: &Config) -> Result<DetailedStatus
     let metadata = Metadata::load(resolved, config)?;
     let pristine_path = config.pristines_dir.join(resolved);
     let pristine_exists = pristine_path.exists();
+    let partial_filter = if pristine_exists {
+        super::partial::pristine_filter(&pristine_path)
+    } else {
+        None
+    };
+    let shallow = super::shallow::shallow_info(&pristine_path);
 
     // Get pristine branches
     let mut pristine_branches = Vec::new();
@@ -237,13 +346,38 @@ pub fn get_detailed_status(name: &str, config: &Config) -> Result<DetailedStatus
     Ok(DetailedStatus {
         name: resolved.to_string(),
         url: entry.url.clone(),
+        archived: entry.archived,
         pristine_exists,
+        partial_filter,
+        shallow,
         pristine_branches,
         clones,
         latest_tag: metadata.latest_tag.clone(),
         last_sync,
         last_sync_relative,
         sync_interval: metadata.sync_interval,
+        fetched_from_mirror: metadata
+            .last_fetch
+            .as_ref()
+            .filter(|_| metadata.used_mirror())
+            .and_then(|f| f.url.clone()),
+        fetch_failures: metadata
+            .last_fetch
+            .as_ref()
+            .map(|f| f.failures.clone())
+            .unwrap_or_default(),
+        sync_history: metadata.sync_history.clone(),
+        sync_failures: metadata.sync_failures.clone(),
+        next_retry: metadata
+            .sync_failures
+            .as_ref()
+            .filter(|f| f.quarantined_at.is_none())
+            .map(|f| {
+                f.next_retry(
+                    config.effective_sync_interval(resolved, &metadata),
+                    config.max_backoff(resolved),
+                )
+            }),
         alternates_ok,
     })
 }
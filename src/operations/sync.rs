@@ -1,18 +1,34 @@
-use git2::{FetchOptions, RemoteCallbacks, Repository};
+use git2::{FetchOptions, FetchPrune, RemoteCallbacks, Repository};
 use indicatif::{ProgressBar, ProgressStyle};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use std::cell::RefCell;
+use std::path::Path;
+use std::time::Instant;
 
 use super::credentials;
 use crate::config::Config;
 use crate::error::{RepomanError, Result, git_error_with_context};
 use crate::hooks;
-use crate::metadata::Metadata;
+use crate::metadata::{AuthConfig, FetchFailure, Metadata, SyncAttempt, SyncTrigger};
+use crate::throttle::{self, DownloadMeter};
 use crate::vault::Vault;
 
 /// Sync (fetch/update) a single pristine from its origin
 pub fn sync_pristine(pristine_name: &str, config: &Config) -> Result<()> {
-    info!("sync_pristine: starting for '{}'", pristine_name);
+    sync_pristine_triggered(pristine_name, SyncTrigger::Manual, config)
+}
+
+/// Sync a pristine on behalf of `trigger`, recording the attempt (timing,
+/// transfer stats, error) in the repo's sync history
+pub fn sync_pristine_triggered(
+    pristine_name: &str,
+    trigger: SyncTrigger,
+    config: &Config,
+) -> Result<()> {
+    info!(
+        "sync_pristine: starting for '{}' ({})",
+        pristine_name, trigger
+    );
 
     // Check if repo exists in vault
     let vault = Vault::load(config)?;
@@ -20,6 +36,11 @@ pub fn sync_pristine(pristine_name: &str, config: &Config) -> Result<()> {
         error!("sync_pristine: '{}' not found in vault", pristine_name);
         return Err(RepomanError::RepoNotInVault(pristine_name.to_string()));
     }
+    let resolved = vault.resolve_name(pristine_name).to_string();
+    let pristine_name = resolved.as_str();
+    super::archive::ensure_not_archived(pristine_name, &vault)?;
+    // Not an attempt: nothing is recorded and the failure streak is left alone
+    config.ensure_online("sync")?;
 
     // Check if pristine exists
     let pristine_path = config.pristines_dir.join(pristine_name);
@@ -31,11 +52,76 @@ pub fn sync_pristine(pristine_name: &str, config: &Config) -> Result<()> {
         return Err(RepomanError::PristineNotFound(pristine_name.to_string()));
     }
 
+    throttle::configure(config)?;
+
+    let started = chrono::Utc::now();
+    let clock = Instant::now();
+    let mut transfer: Option<(u64, u64)> = None;
+    let result = fetch_pristine(
+        pristine_name,
+        &pristine_path,
+        trigger,
+        &mut transfer,
+        config,
+    );
+
+    let attempt = SyncAttempt {
+        started,
+        finished: chrono::Utc::now(),
+        duration_ms: u64::try_from(clock.elapsed().as_millis()).unwrap_or(u64::MAX),
+        trigger,
+        received_bytes: transfer.map(|(bytes, _)| bytes),
+        received_objects: transfer.map(|(_, objects)| objects),
+        error: result.as_ref().err().map(ToString::to_string),
+    };
+    let quarantine_after = config.quarantine_after(pristine_name);
+    // An unreachable network says nothing about the repo: the attempt goes in
+    // the history, but the failure streak (and so quarantine) is left alone
+    let network_error = result.as_ref().is_err_and(RepomanError::is_network_error);
+    let recorded = Metadata::update(pristine_name, config, |m| {
+        let quarantined = if let Some(ref e) = attempt.error {
+            !network_error && m.record_sync_failure(e, quarantine_after)
+        } else {
+            m.clear_sync_failures();
+            false
+        };
+        m.record_sync_attempt(attempt);
+        Ok(quarantined)
+    });
+    match recorded {
+        Ok(true) => warn!(
+            "sync_pristine: '{}' quarantined after {} consecutive failures",
+            pristine_name, quarantine_after
+        ),
+        Ok(false) => {}
+        Err(e) => error!(
+            "sync_pristine: failed to record sync history for '{}': {}",
+            pristine_name, e
+        ),
+    }
+    result
+}
+
+/// Fetch the default remote (falling back to mirrors) and secondary remotes into
+/// a pristine. `transfer` receives the (bytes, objects) libgit2 downloaded.
+fn fetch_pristine(
+    pristine_name: &str,
+    pristine_path: &std::path::Path,
+    trigger: SyncTrigger,
+    transfer: &mut Option<(u64, u64)>,
+    config: &Config,
+) -> Result<()> {
     // Load metadata
-    let mut metadata = Metadata::load(pristine_name, config)?;
+    let metadata = Metadata::load(pristine_name, config)?;
+
+    // Partial pristines are fetched with the git CLI (checked before opening, so
+    // libgit2 accepts the partialClone extension)
+    let filter = super::partial::pristine_filter(pristine_path);
+    // So are shallow ones: libgit2 would drop their boundary
+    let shallow = super::shallow::is_shallow(pristine_path);
 
     // Open the pristine repository
-    let repo = Repository::open_bare(&pristine_path)?;
+    let repo = Repository::open_bare(pristine_path)?;
 
     // Get the origin URL from metadata
     let origin_url = metadata
@@ -48,22 +134,169 @@ pub fn sync_pristine(pristine_name: &str, config: &Config) -> Result<()> {
     );
     println!("Syncing {} from {}...", pristine_name, origin_url);
 
-    // Attempt counter declared before callbacks for correct drop order.
-    let cred_attempts = std::cell::Cell::new(0u32);
-    let mut callbacks = RemoteCallbacks::new();
-
-    // Auth: config overrides metadata
+    // Try the default URL, then each configured mirror in order
     let effective_auth = config.effective_auth(pristine_name, &metadata);
-    credentials::setup_credentials(
-        &mut callbacks,
-        &cred_attempts,
-        effective_auth.as_ref(),
-        "sync",
-    );
+    let remote_name = metadata.default_remote().unwrap_or("origin");
+    ensure_remote(&repo, remote_name, origin_url)?;
+    let urls = config.fetch_urls(pristine_name, &metadata);
+    let mut failures: Vec<FetchFailure> = Vec::new();
+    let mut fetched_from: Option<String> = None;
+    let mut last_error: Option<RepomanError> = None;
+
+    // Tracked branch/tag patterns narrow what gets fetched
+    let head = super::track::head_branch(&repo);
+    let refspecs = super::track::pristine_refspecs(&metadata, head.as_deref());
+    let autotag = super::track::autotag_option(&metadata);
+
+    // Refs deleted upstream are pruned unless `repos.<name>.prune` is false
+    let prune = config.prune_enabled(pristine_name);
+    let refs_before = super::changes::ref_tips(&repo)?;
+
+    for (i, url) in urls.iter().enumerate() {
+        if i > 0 {
+            println!("  trying mirror {}...", url);
+        }
+        let _permit = throttle::host_permit(url, config);
+
+        let attempt = if filter.is_some() || shallow {
+            throttle::warn_unmetered(pristine_name, config);
+            let source = if i == 0 { remote_name } else { url.as_str() };
+            let before = object_store_size(pristine_path);
+            let fetched = match filter {
+                Some(ref f) => {
+                    super::partial::fetch_partial(pristine_path, source, f, &refspecs, prune)
+                }
+                None => super::shallow::fetch_shallow(pristine_path, source, &refspecs, prune),
+            };
+            fetched
+                .map(|()| *transfer = object_store_growth(pristine_path, before))
+                .map_err(|e| (e.to_string(), e))
+        } else {
+            // The default URL goes through the named remote; mirrors are fetched
+            // anonymously so the remote keeps pointing at the canonical URL.
+            let mut remote = if i == 0 {
+                repo.find_remote(remote_name)?
+            } else {
+                repo.remote_anonymous(url)?
+            };
+
+            // Attempt counter declared before fetch options for correct drop order.
+            let cred_attempts = std::cell::Cell::new(0u32);
+            let mut fetch_opts =
+                progress_fetch_options(&cred_attempts, effective_auth.as_ref(), "sync");
+            fetch_opts.download_tags(autotag);
+            fetch_opts.prune(if prune {
+                FetchPrune::On
+            } else {
+                FetchPrune::Off
+            });
+
+            // Fetch the tracked branches and tags (all of them by default)
+            remote
+                .fetch(&refspecs, Some(&mut fetch_opts), None)
+                .map(|()| {
+                    let stats = remote.stats();
+                    *transfer = Some((
+                        stats.received_bytes() as u64,
+                        stats.received_objects() as u64,
+                    ));
+                })
+                .map_err(|e| {
+                    (
+                        e.message().to_string(),
+                        git_error_with_context(e, pristine_name),
+                    )
+                })
+        };
+
+        match attempt {
+            Ok(()) => {
+                fetched_from = Some(url.clone());
+                break;
+            }
+            Err((reason, e)) => {
+                error!(
+                    "sync_pristine: fetch from '{}' failed for '{}': {}",
+                    url, pristine_name, reason
+                );
+                failures.push(FetchFailure {
+                    url: url.clone(),
+                    reason,
+                });
+                last_error = Some(e);
+            }
+        }
+    }
 
-    // Transfer progress with indicatif progress bar
+    let Some(fetched_from) = fetched_from else {
+        Metadata::update(pristine_name, config, |m| {
+            m.record_fetch(None, failures);
+            Ok(())
+        })?;
+        return Err(
+            last_error.unwrap_or_else(|| RepomanError::InvalidRepoUrl(pristine_name.to_string()))
+        );
+    };
+    if fetched_from != origin_url {
+        println!("  fetched from mirror {}", fetched_from);
+    }
+
+    super::track::prune_untracked_refs(&repo, &metadata)?;
+    fetch_secondary_remotes(&repo, pristine_name, &metadata, config);
+
+    // Whatever disappeared was deleted upstream or fell out of the tracked patterns
+    let refs_after = super::changes::ref_tips(&repo)?;
+    let pruned: Vec<String> = refs_before
+        .keys()
+        .filter(|name| !refs_after.contains_key(*name))
+        .cloned()
+        .collect();
+    for name in &pruned {
+        println!("  pruned {}", name);
+    }
+    if !pruned.is_empty() {
+        info!(
+            "sync_pristine: pruned {} ref(s) from '{}'",
+            pruned.len(),
+            pristine_name
+        );
+    }
+
+    let report = super::changes::diff_ref_tips(&repo, &refs_before, &refs_after);
+    println!("  {}", report.summary());
+
+    // Update metadata
+    Metadata::update(pristine_name, config, |m| {
+        m.mark_synced(trigger.sync_type());
+        m.record_pruned(pruned.clone());
+        m.record_changes(report);
+        m.record_fetch(Some(fetched_from), failures);
+        Ok(())
+    })?;
+
+    hooks::run_post_sync(config, pristine_name, pristine_path, &pruned)?;
+
+    info!("sync_pristine: sync complete for '{}'", pristine_name);
+    println!("Sync complete for {}", pristine_name);
+
+    Ok(())
+}
+
+/// Fetch options with credential callbacks and an indicatif progress bar.
+/// `label` tags the credential and server-progress log lines ("init", "sync").
+pub(crate) fn progress_fetch_options<'a>(
+    cred_attempts: &'a std::cell::Cell<u32>,
+    auth: Option<&AuthConfig>,
+    label: &'static str,
+) -> FetchOptions<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+    credentials::setup_credentials(&mut callbacks, cred_attempts, auth, label);
+
+    // Transfer progress with indicatif progress bar, held to the download ceiling
     let pb: RefCell<Option<ProgressBar>> = RefCell::new(None);
+    let mut meter = DownloadMeter::default();
     callbacks.transfer_progress(move |stats| {
+        meter.record(stats.received_bytes());
         let received = stats.received_objects();
         let indexed = stats.indexed_objects();
         let total = stats.total_objects();
@@ -101,68 +334,172 @@ pub fn sync_pristine(pristine_name: &str, config: &Config) -> Result<()> {
         true
     });
 
-    callbacks.sideband_progress(|msg| {
+    // Server-side progress messages — only log to file, don't clutter console.
+    callbacks.sideband_progress(move |msg| {
         if let Ok(s) = std::str::from_utf8(msg) {
             let s = s.trim();
             if !s.is_empty() {
-                debug!("sync remote: {}", s);
+                debug!("{} remote: {}", label, s);
             }
         }
         true
     });
 
-    // Set up fetch options
     let mut fetch_opts = FetchOptions::new();
     fetch_opts.remote_callbacks(callbacks);
+    fetch_opts
+}
 
-    // Find or create origin remote
-    let mut remote = if let Ok(r) = repo.find_remote("origin") {
-        r
-    } else {
-        debug!("sync_pristine: origin remote missing, creating it");
-        repo.remote("origin", origin_url)?;
-        repo.find_remote("origin")?
-    };
-
-    // Fetch all branches and tags
-    remote
-        .fetch(
-            &["refs/heads/*:refs/heads/*", "refs/tags/*:refs/tags/*"],
-            Some(&mut fetch_opts),
-            None,
-        )
-        .map_err(|e| {
-            error!("sync_pristine: fetch failed for '{}': {}", pristine_name, e);
-            git_error_with_context(e, pristine_name)
-        })?;
-
-    // Update metadata
-    metadata.mark_synced("manual");
-    metadata.save(pristine_name, config)?;
+/// Make sure `repo` has a remote called `name` pointing at `url`
+pub(crate) fn ensure_remote(repo: &Repository, name: &str, url: &str) -> Result<()> {
+    match repo.find_remote(name) {
+        Ok(r) if r.url() == Some(url) => {}
+        Ok(_) => {
+            debug!("ensure_remote: updating url of '{}' to '{}'", name, url);
+            repo.remote_set_url(name, url)?;
+        }
+        Err(_) => {
+            debug!("ensure_remote: creating remote '{}' -> '{}'", name, url);
+            repo.remote(name, url)?;
+        }
+    }
+    Ok(())
+}
 
-    hooks::run_post_sync(config, pristine_name, &pristine_path)?;
+/// Objects and bytes (packs plus loose objects) in a repo's object store
+fn object_store_size(repo_path: &Path) -> Option<(u64, u64)> {
+    let path = repo_path.to_string_lossy();
+    let counts = crate::util::run_git(&["-C", &path, "count-objects", "-v"]).ok()?;
+    let field = |key: &str| {
+        counts
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.trim().parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    let objects = field("count:") + field("in-pack:");
+
+    let mut bytes = 0;
+    for dir in std::fs::read_dir(repo_path.join("objects")).ok()?.flatten() {
+        let name = dir.file_name().to_string_lossy().to_string();
+        let loose = name.len() == 2 && name.chars().all(|c| c.is_ascii_hexdigit());
+        if !loose && name != "pack" {
+            continue;
+        }
+        for file in std::fs::read_dir(dir.path()).ok()?.flatten() {
+            if loose || file.path().extension().is_some_and(|ext| ext == "pack") {
+                bytes += file.metadata().map_or(0, |m| m.len());
+            }
+        }
+    }
+    Some((bytes, objects))
+}
 
-    info!("sync_pristine: sync complete for '{}'", pristine_name);
-    println!("Sync complete for {}", pristine_name);
+/// Transfer stats of a git CLI fetch, which doesn't report them: how much the
+/// object store grew. Loose objects count at their compressed size, so bytes
+/// are close to, not exactly, what came over the wire.
+fn object_store_growth(repo_path: &Path, before: Option<(u64, u64)>) -> Option<(u64, u64)> {
+    let (bytes_before, objects_before) = before?;
+    let (bytes, objects) = object_store_size(repo_path)?;
+    Some((
+        bytes.saturating_sub(bytes_before),
+        objects.saturating_sub(objects_before),
+    ))
+}
 
-    Ok(())
+/// Fetch every non-default remote of a pristine into `refs/remotes/<name>/*`.
+/// The default remote owns `refs/heads/*`; the others are best effort, so a
+/// failing fork or upstream is reported but doesn't fail the sync.
+pub(crate) fn fetch_secondary_remotes(
+    repo: &Repository,
+    repo_name: &str,
+    metadata: &Metadata,
+    config: &Config,
+) {
+    let effective_auth = config.effective_auth(repo_name, metadata);
+    let filter = super::partial::pristine_filter(repo.path());
+    let shallow = super::shallow::is_shallow(repo.path());
+    let prune = config.prune_enabled(repo_name);
+    for entry in metadata.remotes.iter().skip(1) {
+        debug!(
+            "fetch_secondary_remotes: fetching '{}' for '{}'",
+            entry.name, repo_name
+        );
+        let _permit = throttle::host_permit(&entry.url, config);
+        if filter.is_some() || shallow {
+            throttle::warn_unmetered(repo_name, config);
+        }
+        let result = ensure_remote(repo, &entry.name, &entry.url).and_then(|()| {
+            if let Some(ref f) = filter {
+                return super::partial::fetch_partial_secondary(repo.path(), &entry.name, f, prune);
+            }
+            let refspec = format!("+refs/heads/*:refs/remotes/{}/*", entry.name);
+            if shallow {
+                return super::shallow::fetch_shallow(
+                    repo.path(),
+                    &entry.name,
+                    std::slice::from_ref(&refspec),
+                    prune,
+                );
+            }
+            let attempts = std::cell::Cell::new(0u32);
+            let mut callbacks = RemoteCallbacks::new();
+            credentials::setup_credentials(
+                &mut callbacks,
+                &attempts,
+                effective_auth.as_ref(),
+                "sync",
+            );
+            let mut meter = DownloadMeter::default();
+            callbacks.transfer_progress(move |stats| {
+                meter.record(stats.received_bytes());
+                true
+            });
+            let mut fetch_opts = FetchOptions::new();
+            fetch_opts.remote_callbacks(callbacks);
+            fetch_opts.download_tags(git2::AutotagOption::None);
+            if prune {
+                fetch_opts.prune(FetchPrune::On);
+            }
+            repo.find_remote(&entry.name)?
+                .fetch(&[refspec.as_str()], Some(&mut fetch_opts), None)
+                .map_err(|e| git_error_with_context(e, repo_name))
+        });
+        if let Err(e) = result {
+            error!(
+                "fetch_secondary_remotes: '{}' failed for '{}': {}",
+                entry.name, repo_name, e
+            );
+            eprintln!(
+                "Warning: failed to fetch remote '{}' for {}: {}",
+                entry.name, repo_name, e
+            );
+        }
+    }
 }
 
-/// Sync all pristines
+/// Sync all pristines (skipping quarantined repos)
 /// Returns a Vec of (repo_name, Result) tuples
 pub fn sync_all_pristines(config: &Config) -> Vec<(String, Result<()>)> {
     let vault = match Vault::load(config) {
         Ok(v) => v,
         Err(e) => return vec![("vault".to_string(), Err(e))],
     };
+    let all_metadata = Metadata::load_all(config).unwrap_or_default();
 
     vault
-        .get_all_names()
-        .into_iter()
+        .entries
+        .iter()
+        .filter(|entry| !entry.archived)
+        .map(|entry| entry.name.as_str())
         .filter(|name| {
             let pristine_path = config.pristines_dir.join(name);
             pristine_path.exists()
         })
+        .filter(|name| {
+            !all_metadata
+                .get(*name)
+                .is_some_and(Metadata::is_quarantined)
+        })
         .map(|name| {
             let result = sync_pristine(name, config);
             (name.to_string(), result)
@@ -170,13 +507,15 @@ pub fn sync_all_pristines(config: &Config) -> Vec<(String, Result<()>)> {
         .collect()
 }
 
-/// Get list of repos that can be synced (have pristines)
-pub fn get_syncable_repos(config: &Config) -> Result<Vec<String>> {
+/// Get list of repos that can be synced (have pristines), optionally limited to
+/// entries carrying every tag in `tags`
+pub fn get_syncable_repos(tags: &[String], config: &Config) -> Result<Vec<String>> {
     let vault = Vault::load(config)?;
 
     let syncable: Vec<String> = vault
-        .get_all_names()
+        .names_with_tags(tags, config)
         .into_iter()
+        .filter(|name| vault.get_entry(name).is_some_and(|e| !e.archived))
         .filter(|name| {
             let pristine_path = config.pristines_dir.join(name);
             pristine_path.exists()
@@ -191,9 +530,53 @@ pub fn get_syncable_repos(config: &Config) -> Result<Vec<String>> {
     Ok(syncable)
 }
 
+/// Clear the failure streak (and quarantine) of `name`, or of every repo when
+/// `None`. Returns the repos that had one.
+pub fn reset_sync_failures(name: Option<&str>, config: &Config) -> Result<Vec<String>> {
+    let vault = Vault::load(config)?;
+    let names: Vec<String> = match name {
+        Some(name) => {
+            if !vault.contains(name) {
+                return Err(RepomanError::RepoNotInVault(name.to_string()));
+            }
+            vec![vault.resolve_name(name).to_string()]
+        }
+        None => Metadata::load_all(config)?
+            .into_iter()
+            .filter(|(_, m)| m.sync_failures.is_some())
+            .map(|(name, _)| name)
+            .collect(),
+    };
+
+    let mut reset = Vec::new();
+    for name in names {
+        if Metadata::update(&name, config, |m| Ok(m.clear_sync_failures()))?.is_some() {
+            info!("reset_sync_failures: cleared '{}'", name);
+            reset.push(name);
+        }
+    }
+    reset.sort();
+    Ok(reset)
+}
+
+/// Repos among `names` the agent has quarantined
+pub fn quarantined_repos(names: &[String], config: &Config) -> Result<Vec<String>> {
+    let all_metadata = Metadata::load_all(config)?;
+    Ok(names
+        .iter()
+        .filter(|name| {
+            all_metadata
+                .get(*name)
+                .is_some_and(Metadata::is_quarantined)
+        })
+        .cloned()
+        .collect())
+}
+
 /// Check for new tags on a remote
 pub fn check_for_new_tag(pristine_name: &str, config: &Config) -> Result<Option<String>> {
     debug!("check_for_new_tag: checking '{}'", pristine_name);
+    config.ensure_online("check for new tags")?;
 
     // Load metadata
     let metadata = Metadata::load(pristine_name, config)?;
@@ -222,6 +605,7 @@ pub fn check_for_new_tag(pristine_name: &str, config: &Config) -> Result<Option<
     );
 
     // Connect to remote and list refs
+    let _permit = throttle::host_permit(origin_url, config);
     let mut remote = git2::Remote::create_detached(origin_url)
         .map_err(|e| git_error_with_context(e, pristine_name))?;
     remote
@@ -300,8 +684,277 @@ pub fn check_for_new_tag(pristine_name: &str, config: &Config) -> Result<Option<
 /// Update the latest tag in metadata
 pub fn update_latest_tag(pristine_name: &str, tag: &str, config: &Config) -> Result<()> {
     debug!("update_latest_tag: '{}' -> '{}'", pristine_name, tag);
-    let mut metadata = Metadata::load(pristine_name, config)?;
-    metadata.latest_tag = Some(tag.to_string());
-    metadata.save(pristine_name, config)?;
-    Ok(())
+    Metadata::update(pristine_name, config, |m| {
+        m.latest_tag = Some(tag.to_string());
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RepoConfig;
+    use crate::test_support::create_test_config;
+    use git2::Signature;
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    #[test]
+    fn test_mirror_fallback_records_source() {
+        let (temp, mut config) = create_test_config();
+
+        // A working mirror with one commit; the default URL doesn't exist
+        let mirror = temp.path().join("mirror");
+        let repo = Repository::init(&mirror).unwrap();
+        std::fs::write(mirror.join("file.txt"), "hello").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+
+        let origin = temp.path().join("gone").to_string_lossy().to_string();
+        let mirror_url = mirror.to_string_lossy().to_string();
+        let mut repos = HashMap::new();
+        repos.insert(
+            "proj".to_string(),
+            RepoConfig {
+                mirrors: Some(vec![mirror_url.clone()]),
+                ..Default::default()
+            },
+        );
+        config.repos = Some(repos);
+
+        Vault::update(&config, |v| v.add_entry("proj".to_string(), origin.clone())).unwrap();
+        Metadata::new(vec![origin.clone()])
+            .save("proj", &config)
+            .unwrap();
+
+        crate::operations::init_pristine("proj", None, None, &config).unwrap();
+        let metadata = Metadata::load("proj", &config).unwrap();
+        let fetch = metadata.last_fetch.as_ref().unwrap();
+        assert_eq!(fetch.url.as_deref(), Some(mirror_url.as_str()));
+        assert_eq!(fetch.failures.len(), 1);
+        assert_eq!(fetch.failures[0].url, origin);
+        assert!(metadata.used_mirror());
+
+        // The pristine's remote still points at the canonical URL
+        let pristine = Repository::open_bare(config.pristines_dir.join("proj")).unwrap();
+        assert_eq!(
+            pristine.find_remote("origin").unwrap().url(),
+            Some(origin.as_str())
+        );
+
+        sync_pristine("proj", &config).unwrap();
+        let metadata = Metadata::load("proj", &config).unwrap();
+        assert!(metadata.last_sync.is_some());
+        assert_eq!(
+            metadata.last_fetch.unwrap().url.as_deref(),
+            Some(mirror_url.as_str())
+        );
+    }
+
+    #[test]
+    fn test_sync_history_records_attempts() {
+        let (temp, config) = create_test_config();
+
+        let source = temp.path().join("source");
+        let repo = Repository::init(&source).unwrap();
+        std::fs::write(source.join("file.txt"), "hello").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+
+        let url = source.to_string_lossy().to_string();
+        Vault::update(&config, |v| v.add_entry("proj".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url]).save("proj", &config).unwrap();
+        crate::operations::init_pristine("proj", None, None, &config).unwrap();
+
+        sync_pristine_triggered("proj", SyncTrigger::Agent, &config).unwrap();
+        std::fs::remove_dir_all(&source).unwrap();
+        assert!(sync_pristine_triggered("proj", SyncTrigger::Webhook, &config).is_err());
+        // Not a sync attempt: the repo isn't in the vault
+        assert!(sync_pristine("missing", &config).is_err());
+
+        let metadata = Metadata::load("proj", &config).unwrap();
+        let history = &metadata.sync_history;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].trigger, SyncTrigger::Agent);
+        assert!(history[0].succeeded());
+        assert!(history[0].received_objects.is_some());
+        assert!(history[0].finished >= history[0].started);
+        assert_eq!(history[1].trigger, SyncTrigger::Webhook);
+        assert!(history[1].error.is_some());
+        // last_sync only moves on success
+        assert_eq!(metadata.last_sync.unwrap().sync_type, "auto");
+    }
+
+    #[test]
+    fn test_sync_failures_quarantine_and_reset() {
+        let (temp, mut config) = create_test_config();
+
+        let source = temp.path().join("source");
+        let repo = Repository::init(&source).unwrap();
+        std::fs::write(source.join("file.txt"), "hello").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+
+        let url = source.to_string_lossy().to_string();
+        Vault::update(&config, |v| v.add_entry("proj".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url]).save("proj", &config).unwrap();
+        crate::operations::init_pristine("proj", None, None, &config).unwrap();
+        config.repos = Some(HashMap::from([(
+            "proj".to_string(),
+            RepoConfig {
+                quarantine_after: Some(2),
+                ..Default::default()
+            },
+        )]));
+
+        let moved = temp.path().join("moved");
+        std::fs::rename(&source, &moved).unwrap();
+        assert!(sync_pristine("proj", &config).is_err());
+        assert!(!Metadata::load("proj", &config).unwrap().is_quarantined());
+        assert!(sync_pristine("proj", &config).is_err());
+        let metadata = Metadata::load("proj", &config).unwrap();
+        assert!(metadata.is_quarantined());
+        assert_eq!(metadata.sync_failures.unwrap().consecutive, 2);
+
+        // Bulk sync leaves quarantined repos alone
+        assert!(sync_all_pristines(&config).is_empty());
+        let names = vec!["proj".to_string()];
+        assert_eq!(quarantined_repos(&names, &config).unwrap(), names);
+
+        assert_eq!(reset_sync_failures(None, &config).unwrap(), names);
+        assert!(reset_sync_failures(None, &config).unwrap().is_empty());
+
+        // Offline syncs aren't attempts and don't count as failures
+        config.network = Some(crate::config::NetworkConfig {
+            offline: Some(true),
+            ..Default::default()
+        });
+        let history_len = Metadata::load("proj", &config).unwrap().sync_history.len();
+        assert!(matches!(
+            sync_pristine("proj", &config),
+            Err(RepomanError::Offline(_))
+        ));
+        let metadata = Metadata::load("proj", &config).unwrap();
+        assert_eq!(metadata.sync_history.len(), history_len);
+        assert!(metadata.sync_failures.is_none());
+        config.network = None;
+
+        // Network outages are recorded but don't count toward quarantine
+        Metadata::new(vec!["http://127.0.0.1:1/proj.git".to_string()])
+            .save("proj", &config)
+            .unwrap();
+        for _ in 0..3 {
+            let err = sync_pristine("proj", &config).unwrap_err();
+            assert!(err.is_network_error(), "{}", err);
+        }
+        let metadata = Metadata::load("proj", &config).unwrap();
+        assert!(metadata.sync_failures.is_none());
+        assert_eq!(metadata.sync_history.len(), 3);
+        assert!(metadata.sync_history.iter().all(|a| a.error.is_some()));
+        Metadata::new(vec![source.to_string_lossy().to_string()])
+            .save("proj", &config)
+            .unwrap();
+
+        // A success ends the streak
+        assert!(sync_pristine("proj", &config).is_err());
+        std::fs::rename(&moved, &source).unwrap();
+        sync_pristine("proj", &config).unwrap();
+        assert!(
+            Metadata::load("proj", &config)
+                .unwrap()
+                .sync_failures
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_sync_prunes_refs_deleted_upstream() {
+        let (temp, mut config) = create_test_config();
+
+        let source = temp.path().join("source");
+        let repo = Repository::init(&source).unwrap();
+        std::fs::write(source.join("file.txt"), "hello").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+        let commit = repo.find_commit(oid).unwrap();
+        repo.branch("feature", &commit, false).unwrap();
+        repo.branch("keep", &commit, false).unwrap();
+        repo.tag_lightweight("v0.1", commit.as_object(), false)
+            .unwrap();
+
+        // post_sync sees the pruned refs
+        let marker = temp.path().join("pruned.txt");
+        let mut repos = HashMap::new();
+        repos.insert(
+            "proj".to_string(),
+            RepoConfig {
+                hooks: Some(crate::config::HookConfig {
+                    post_sync: Some(format!(
+                        "echo \"$REPOMAN_PRUNED_REFS\" > {}",
+                        marker.display()
+                    )),
+                    ..Default::default()
+                }),
+                prune: Some(false),
+                ..Default::default()
+            },
+        );
+        config.repos = Some(repos);
+
+        let url = source.to_string_lossy().to_string();
+        Vault::update(&config, |v| v.add_entry("proj".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url]).save("proj", &config).unwrap();
+        crate::operations::init_pristine("proj", None, None, &config).unwrap();
+        sync_pristine("proj", &config).unwrap();
+
+        repo.find_branch("feature", git2::BranchType::Local)
+            .unwrap()
+            .delete()
+            .unwrap();
+        repo.tag_delete("v0.1").unwrap();
+
+        // With pruning off, the deleted refs stay
+        sync_pristine("proj", &config).unwrap();
+        let pristine = Repository::open_bare(config.pristines_dir.join("proj")).unwrap();
+        assert!(pristine.find_reference("refs/heads/feature").is_ok());
+
+        config
+            .repos
+            .as_mut()
+            .unwrap()
+            .get_mut("proj")
+            .unwrap()
+            .prune = None;
+        sync_pristine("proj", &config).unwrap();
+        assert!(pristine.find_reference("refs/heads/feature").is_err());
+        assert!(pristine.find_reference("refs/tags/v0.1").is_err());
+        assert!(pristine.find_reference("refs/heads/keep").is_ok());
+
+        let metadata = Metadata::load("proj", &config).unwrap();
+        assert_eq!(
+            metadata.last_sync.unwrap().pruned_refs,
+            vec!["refs/heads/feature", "refs/tags/v0.1"]
+        );
+        assert_eq!(
+            std::fs::read_to_string(&marker).unwrap().trim(),
+            "refs/heads/feature refs/tags/v0.1"
+        );
+    }
 }
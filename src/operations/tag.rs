@@ -0,0 +1,62 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::vault::VaultEntry;
+
+use super::describe::edit_entry;
+
+/// Add and remove tags on a vault entry. Tags are compared case-insensitively;
+/// adding an existing tag is a no-op. Returns the updated entry.
+pub fn tag_repo(
+    name: &str,
+    add: &[String],
+    remove: &[String],
+    config: &Config,
+) -> Result<VaultEntry> {
+    edit_entry(name, "tag", config, |entry| {
+        entry
+            .tags
+            .retain(|t| !remove.iter().any(|r| r.eq_ignore_ascii_case(t)));
+        for tag in add.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !entry.has_tag(tag) {
+                entry.tags.push(tag.to_string());
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_config;
+    use crate::vault::Vault;
+
+    #[test]
+    fn test_tag_add_remove_dedupes() {
+        let (_temp, config) = create_test_config();
+        Vault::update(&config, |v| {
+            v.add_entry("repo".to_string(), "url".to_string())
+        })
+        .unwrap();
+
+        let entry = tag_repo(
+            "repo",
+            &["work".to_string(), "Work".to_string(), "rust".to_string()],
+            &[],
+            &config,
+        )
+        .unwrap();
+        assert_eq!(entry.tags, vec!["work", "rust"]);
+
+        let entry = tag_repo("repo", &[], &["WORK".to_string()], &config).unwrap();
+        assert_eq!(entry.tags, vec!["rust"]);
+
+        let vault = Vault::load(&config).unwrap();
+        let tags = vec!["rust".to_string()];
+        assert_eq!(vault.names_with_tags(&tags, &config), vec!["repo"]);
+        assert!(
+            vault
+                .names_with_tags(&["work".to_string()], &config)
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Tracked branch and tag globs. A pristine with tracked patterns only fetches
+//! the matching refs (plus the remote's default branch) and prunes the rest.
+
+use git2::{AutotagOption, Repository};
+use log::{debug, info};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::error::{RepomanError, Result, git_error_with_context};
+use crate::metadata::{AuthConfig, Metadata};
+use crate::vault::Vault;
+
+/// Check a tracked pattern: a branch or tag name relative to `refs/heads/` or
+/// `refs/tags/`, with at most one `*` (what git refspecs accept)
+pub fn validate_ref_pattern(pattern: &str) -> Result<()> {
+    let valid = !pattern.is_empty()
+        && !pattern.starts_with("refs/")
+        && !pattern.starts_with('/')
+        && !pattern.ends_with('/')
+        && !pattern.contains("..")
+        && !pattern.contains("//")
+        && pattern.matches('*').count() <= 1
+        && !pattern
+            .chars()
+            .any(|c| c.is_whitespace() || "?[\\^~:".contains(c));
+    if valid {
+        Ok(())
+    } else {
+        Err(RepomanError::InvalidRefPattern(pattern.to_string()))
+    }
+}
+
+/// Whether a short ref name matches a tracked pattern. As in refspecs, `*`
+/// matches any run of characters, `/` included.
+pub fn ref_matches(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+        }
+        None => pattern == name,
+    }
+}
+
+fn refspecs_for(namespace: &str, patterns: &[String]) -> Vec<String> {
+    if patterns.is_empty() {
+        return vec![format!("+refs/{0}/*:refs/{0}/*", namespace)];
+    }
+    patterns
+        .iter()
+        .map(|p| format!("+refs/{0}/{1}:refs/{0}/{1}", namespace, p))
+        .collect()
+}
+
+/// Refspecs a pristine fetches from its default remote. `head` is the remote's
+/// default branch, fetched even when no pattern matches it so clones have a HEAD.
+pub(crate) fn pristine_refspecs(metadata: &Metadata, head: Option<&str>) -> Vec<String> {
+    let mut specs = refspecs_for("heads", &metadata.tracked_branches);
+    if let Some(head) = head
+        && !metadata.tracked_branches.is_empty()
+        && !branch_is_tracked(metadata, head)
+    {
+        specs.push(format!("+refs/heads/{0}:refs/heads/{0}", head));
+    }
+    specs.extend(refspecs_for("tags", &metadata.tracked_tags));
+    specs
+}
+
+/// Tag auto-following would drag in tags outside the tracked tag patterns
+pub(crate) fn autotag_option(metadata: &Metadata) -> AutotagOption {
+    if metadata.tracked_tags.is_empty() {
+        AutotagOption::Auto
+    } else {
+        AutotagOption::None
+    }
+}
+
+fn branch_is_tracked(metadata: &Metadata, branch: &str) -> bool {
+    metadata.tracked_branches.is_empty()
+        || metadata
+            .tracked_branches
+            .iter()
+            .any(|p| ref_matches(p, branch))
+}
+
+fn tag_is_tracked(metadata: &Metadata, tag: &str) -> bool {
+    metadata.tracked_tags.is_empty() || metadata.tracked_tags.iter().any(|p| ref_matches(p, tag))
+}
+
+/// Branch a bare pristine's HEAD points at
+pub(crate) fn head_branch(repo: &Repository) -> Option<String> {
+    let head = repo.find_reference("HEAD").ok()?;
+    head.symbolic_target()?
+        .strip_prefix("refs/heads/")
+        .map(String::from)
+}
+
+/// Delete the pristine's branches and tags that no tracked pattern matches
+/// (HEAD's branch always stays). Returns the deleted ref names.
+pub(crate) fn prune_untracked_refs(repo: &Repository, metadata: &Metadata) -> Result<Vec<String>> {
+    if metadata.tracked_branches.is_empty() && metadata.tracked_tags.is_empty() {
+        return Ok(Vec::new());
+    }
+    let head = head_branch(repo);
+    let mut pruned = Vec::new();
+    for reference in repo.references()? {
+        let mut reference = reference?;
+        let Some(name) = reference.name().map(String::from) else {
+            continue;
+        };
+        let keep = if let Some(branch) = name.strip_prefix("refs/heads/") {
+            head.as_deref() == Some(branch) || branch_is_tracked(metadata, branch)
+        } else if let Some(tag) = name.strip_prefix("refs/tags/") {
+            tag_is_tracked(metadata, tag)
+        } else {
+            true
+        };
+        if !keep {
+            debug!("prune_untracked_refs: deleting {}", name);
+            reference.delete()?;
+            pruned.push(name);
+        }
+    }
+    if !pruned.is_empty() {
+        info!(
+            "prune_untracked_refs: pruned {} untracked ref(s) from {}",
+            pruned.len(),
+            repo.path().display()
+        );
+    }
+    Ok(pruned)
+}
+
+/// Create a bare pristine that fetches only the tracked refs. A plain clone would
+/// download every branch first, which is what tracking is meant to avoid.
+pub(crate) fn init_tracked_pristine(
+    url: &str,
+    pristine_path: &Path,
+    remote_name: &str,
+    metadata: &Metadata,
+    auth: Option<&AuthConfig>,
+    depth: Option<i32>,
+    repo_name: &str,
+) -> std::result::Result<Repository, (String, RepomanError)> {
+    let git_err = |e: git2::Error| {
+        (
+            e.message().to_string(),
+            git_error_with_context(e, repo_name),
+        )
+    };
+
+    let repo = Repository::init_bare(pristine_path).map_err(git_err)?;
+    let mut remote = repo.remote(remote_name, url).map_err(git_err)?;
+
+    // Ask the remote for its default branch before fetching
+    let cred_attempts = std::cell::Cell::new(0u32);
+    let mut callbacks = git2::RemoteCallbacks::new();
+    super::credentials::setup_credentials(&mut callbacks, &cred_attempts, auth, "init");
+    let head = {
+        let connection = remote
+            .connect_auth(git2::Direction::Fetch, Some(callbacks), None)
+            .map_err(git_err)?;
+        connection
+            .default_branch()
+            .ok()
+            .and_then(|b| b.as_str().map(String::from))
+    };
+    let head = head.as_deref().and_then(|h| h.strip_prefix("refs/heads/"));
+    debug!("init_tracked_pristine: remote default branch is {:?}", head);
+
+    let fetch_attempts = std::cell::Cell::new(0u32);
+    let mut fetch_opts = super::sync::progress_fetch_options(&fetch_attempts, auth, "init");
+    fetch_opts.download_tags(autotag_option(metadata));
+    if let Some(d) = depth {
+        fetch_opts.depth(d);
+    }
+    let refspecs = pristine_refspecs(metadata, head);
+    remote
+        .fetch(&refspecs, Some(&mut fetch_opts), None)
+        .map_err(git_err)?;
+    drop(remote);
+
+    if let Some(head) = head {
+        repo.set_head(&format!("refs/heads/{}", head))
+            .map_err(git_err)?;
+    }
+    Ok(repo)
+}
+
+/// Resolve a repo name or alias to its vault name
+fn resolve_repo(name: &str, config: &Config) -> Result<String> {
+    let vault = Vault::load(config)?;
+    if !vault.contains(name) {
+        return Err(RepomanError::RepoNotInVault(name.to_string()));
+    }
+    Ok(vault.resolve_name(name).to_string())
+}
+
+/// Tracked branch and tag patterns of a repo (empty = everything)
+pub fn list_tracked(name: &str, config: &Config) -> Result<(Vec<String>, Vec<String>)> {
+    let name = resolve_repo(name, config)?;
+    let metadata = Metadata::load(&name, config)?;
+    Ok((metadata.tracked_branches, metadata.tracked_tags))
+}
+
+/// Add (or with `remove`, drop) tracked patterns; `tags` selects tag patterns
+/// instead of branch patterns. The pristine follows on its next sync.
+/// Returns the resulting pattern list.
+pub fn track_refs(
+    name: &str,
+    patterns: &[String],
+    tags: bool,
+    remove: bool,
+    config: &Config,
+) -> Result<Vec<String>> {
+    let name = resolve_repo(name, config)?;
+    let patterns: Vec<String> = patterns.iter().map(|p| p.trim().to_string()).collect();
+    if !remove {
+        for pattern in &patterns {
+            validate_ref_pattern(pattern)?;
+        }
+    }
+    info!(
+        "track_refs: '{}' {} {} {:?}",
+        name,
+        if remove { "-=" } else { "+=" },
+        if tags { "tags" } else { "branches" },
+        patterns
+    );
+
+    Metadata::update(&name, config, |m| {
+        let list = if tags {
+            &mut m.tracked_tags
+        } else {
+            &mut m.tracked_branches
+        };
+        if remove {
+            list.retain(|p| !patterns.contains(p));
+        } else {
+            for pattern in &patterns {
+                if !list.contains(pattern) {
+                    list.push(pattern.clone());
+                }
+            }
+        }
+        Ok(list.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_config;
+    use git2::Signature;
+
+    #[test]
+    fn test_ref_patterns() {
+        assert!(ref_matches("main", "main"));
+        assert!(!ref_matches("main", "main2"));
+        assert!(ref_matches("release/*", "release/1.0"));
+        assert!(ref_matches("release/*", "release/1.x/hotfix"));
+        assert!(!ref_matches("release/*", "releases/1.0"));
+        assert!(ref_matches("v*-lts", "v2-lts"));
+        assert!(!ref_matches("v*-lts", "v2-rc"));
+        assert!(ref_matches("*", "anything"));
+
+        for ok in ["main", "release/*", "v*", "team/*/ready"] {
+            assert!(validate_ref_pattern(ok).is_ok(), "{}", ok);
+        }
+        for bad in [
+            "",
+            "refs/heads/main",
+            "a*b*",
+            "bad name",
+            "x..y",
+            "rel/",
+            "f?o",
+        ] {
+            assert!(validate_ref_pattern(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_tracked_refs_limit_init_and_sync() {
+        let (temp, config) = create_test_config();
+
+        let source = temp.path().join("source");
+        let repo = Repository::init(&source).unwrap();
+        std::fs::write(source.join("file.txt"), "hello").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        let oid = repo
+            .commit(Some("refs/heads/main"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        let commit = repo.find_commit(oid).unwrap();
+        for branch in ["release/1.0", "bot/update-1", "bot/update-2"] {
+            repo.branch(branch, &commit, false).unwrap();
+        }
+        for tag in ["v1.0", "nightly-1"] {
+            repo.tag_lightweight(tag, commit.as_object(), false)
+                .unwrap();
+        }
+
+        let url = source.to_string_lossy().to_string();
+        Vault::update(&config, |v| v.add_entry("proj".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url]).save("proj", &config).unwrap();
+
+        track_refs("proj", &["release/*".to_string()], false, false, &config).unwrap();
+        track_refs("proj", &["v*".to_string()], true, false, &config).unwrap();
+        assert!(matches!(
+            track_refs("proj", &["a*b*".to_string()], false, false, &config),
+            Err(RepomanError::InvalidRefPattern(_))
+        ));
+
+        crate::operations::init_pristine("proj", None, None, &config).unwrap();
+        let pristine_path = config.pristines_dir.join("proj");
+        let pristine = Repository::open_bare(&pristine_path).unwrap();
+        assert_eq!(head_branch(&pristine).as_deref(), Some("main"));
+        assert!(pristine.find_reference("refs/heads/main").is_ok());
+        assert!(pristine.find_reference("refs/heads/release/1.0").is_ok());
+        assert!(pristine.find_reference("refs/heads/bot/update-1").is_err());
+        assert!(pristine.find_reference("refs/tags/v1.0").is_ok());
+        assert!(pristine.find_reference("refs/tags/nightly-1").is_err());
+
+        // Widening tracking fetches more; narrowing prunes on the next sync
+        track_refs("proj", &["bot/*".to_string()], false, false, &config).unwrap();
+        crate::operations::sync_pristine("proj", &config).unwrap();
+        assert!(pristine.find_reference("refs/heads/bot/update-2").is_ok());
+
+        let remaining =
+            track_refs("proj", &["release/*".to_string()], false, true, &config).unwrap();
+        assert_eq!(remaining, vec!["bot/*"]);
+        crate::operations::sync_pristine("proj", &config).unwrap();
+        assert!(pristine.find_reference("refs/heads/release/1.0").is_err());
+        assert!(pristine.find_reference("refs/heads/main").is_ok());
+
+        let (branches, tags) = list_tracked("proj", &config).unwrap();
+        assert_eq!(branches, vec!["bot/*"]);
+        assert_eq!(tags, vec!["v*"]);
+    }
+}
@@ -1,5 +1,6 @@
 use git2::Repository;
 use log::{debug, error, info, warn};
+use std::path::Path;
 
 use super::sync::sync_pristine;
 use crate::config::Config;
@@ -9,7 +10,7 @@ use crate::vault::Vault;
 
 /// Update a single clone: fetch from pristine and fast-forward if possible.
 /// Returns a status message to print.
-fn update_single_clone(clone_entry: &CloneEntry, _resolved: &str) -> String {
+fn update_single_clone(clone_entry: &CloneEntry, pristine_path: &Path) -> String {
     if !clone_entry.path.exists() {
         warn!(
             "update_repo: clone '{}' path missing, skipping",
@@ -62,6 +63,13 @@ fn update_single_clone(clone_entry: &CloneEntry, _resolved: &str) -> String {
         );
         return format!("  Failed to fetch clone {}: {}", clone_entry.name, e);
     }
+    // libgit2's fetch drops the shallow boundary the merge analysis below relies on
+    if let Err(e) = super::shallow::sync_clone_shallow(pristine_path, &clone_entry.path) {
+        warn!(
+            "update_repo: shallow boundary for clone '{}': {}",
+            clone_entry.name, e
+        );
+    }
 
     // Attempt fast-forward merge on current branch
     let head = match repo.head() {
@@ -189,9 +197,13 @@ pub fn update_repo(repo_name: &str, config: &Config) -> Result<()> {
         return Err(RepomanError::PristineNotFound(resolved));
     }
 
-    // 1. Sync pristine from remote
+    // 1. Sync pristine from remote (offline: clones catch up to the pristine as is)
     println!("Updating {}...", resolved);
-    sync_pristine(&resolved, config)?;
+    if config.offline() {
+        println!("  offline: skipping fetch, updating clones from the pristine");
+    } else {
+        sync_pristine(&resolved, config)?;
+    }
 
     // 2. Update each clone in parallel using thread::scope
     let metadata = Metadata::load(&resolved, config)?;
@@ -205,7 +217,7 @@ pub fn update_repo(repo_name: &str, config: &Config) -> Result<()> {
         std::thread::scope(|s| {
             let handles: Vec<_> = chunk
                 .iter()
-                .map(|entry| s.spawn(|| update_single_clone(entry, &resolved)))
+                .map(|entry| s.spawn(|| update_single_clone(entry, &pristine_path)))
                 .collect();
             for h in handles {
                 if let Ok(msg) = h.join()
@@ -217,11 +229,13 @@ pub fn update_repo(repo_name: &str, config: &Config) -> Result<()> {
         });
     }
 
+    super::pins::pin_clones_or_warn(&resolved, config);
+
     info!("update_repo: done for '{}'", resolved);
     Ok(())
 }
 
-/// Get list of repos that can be updated (have pristines)
-pub fn get_updatable_repos(config: &Config) -> Result<Vec<String>> {
-    super::sync::get_syncable_repos(config)
+/// Get list of repos that can be updated (have pristines), optionally filtered by tags
+pub fn get_updatable_repos(tags: &[String], config: &Config) -> Result<Vec<String>> {
+    super::sync::get_syncable_repos(tags, config)
 }
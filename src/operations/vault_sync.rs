@@ -0,0 +1,302 @@
+use git2::{FetchOptions, Oid, PushOptions, RemoteCallbacks, Repository};
+use log::{info, warn};
+use serde::Serialize;
+
+use super::credentials;
+use crate::config::{Config, StateBackend};
+use crate::error::{RepomanError, Result};
+use crate::metadata::Metadata;
+use crate::vault::Vault;
+use crate::vault_git;
+
+/// What `vault pull` changed locally
+#[derive(Debug, Default, Serialize)]
+pub struct VaultPullResult {
+    /// Remote had nothing new.
+    pub up_to_date: bool,
+    /// Entries that arrived from the remote.
+    pub added: Vec<String>,
+    pub renamed: Vec<(String, String)>,
+    pub removed: Vec<String>,
+    pub conflicts: Vec<String>,
+    /// New entries whose pristine was created after the merge.
+    pub initialized: Vec<String>,
+}
+
+/// Make `vault_dir` a git repository and optionally set its `origin` remote.
+pub fn vault_init_git(remote_url: Option<&str>, config: &Config) -> Result<()> {
+    if config.state_backend() == StateBackend::Sqlite {
+        return Err(RepomanError::ConfigError(
+            "a git-backed vault requires the json state backend".to_string(),
+        ));
+    }
+
+    let repo = vault_git::init_repo(config)?;
+    if !config.vault_dir.join("vault.json").exists() {
+        Vault::default().save(config)?;
+    }
+
+    if let Some(url) = remote_url {
+        if repo.find_remote("origin").is_ok() {
+            repo.remote_set_url("origin", url)?;
+        } else {
+            repo.remote("origin", url)?;
+        }
+        info!("vault_init_git: origin set to {}", url);
+    }
+
+    vault_git::commit_if_changed(config, "Initialize repoman vault")?;
+    Ok(())
+}
+
+fn open_vault_repo(config: &Config) -> Result<Repository> {
+    if !vault_git::is_enabled(config) {
+        return Err(RepomanError::VaultNotGitBacked);
+    }
+    Ok(Repository::open(&config.vault_dir)?)
+}
+
+fn current_branch(repo: &Repository) -> Result<String> {
+    let head = repo.head()?;
+    head.shorthand()
+        .map(str::to_string)
+        .ok_or_else(|| RepomanError::Other("vault HEAD is detached".to_string()))
+}
+
+/// Fetch `branch` from origin and return the remote tip, if the branch exists there.
+fn fetch_remote(repo: &Repository, branch: &str) -> Result<Option<Oid>> {
+    let mut remote = repo.find_remote("origin").map_err(|_| {
+        RepomanError::ConfigError(
+            "vault has no 'origin' remote (run 'repoman vault init <url>')".to_string(),
+        )
+    })?;
+
+    let cred_attempts = std::cell::Cell::new(0u32);
+    let mut callbacks = RemoteCallbacks::new();
+    credentials::setup_credentials(&mut callbacks, &cred_attempts, None, "vault-fetch");
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+
+    let tracking = format!("refs/remotes/origin/{}", branch);
+    remote.fetch(
+        &[format!("+refs/heads/{}:{}", branch, tracking)],
+        Some(&mut fetch_options),
+        None,
+    )?;
+    Ok(repo.refname_to_id(&tracking).ok())
+}
+
+/// Commit pending vault changes and push them to origin.
+/// Fails with `VaultPushRejected` if the remote has commits we haven't pulled.
+pub fn vault_push(config: &Config) -> Result<()> {
+    config.ensure_online("push the vault")?;
+    let repo = open_vault_repo(config)?;
+    vault_git::commit_if_changed(config, "Update vault")?;
+    let branch = current_branch(&repo)?;
+    let local = repo.refname_to_id("HEAD")?;
+
+    if let Some(remote_tip) = fetch_remote(&repo, &branch)?
+        && remote_tip != local
+        && !repo.graph_descendant_of(local, remote_tip)?
+    {
+        return Err(RepomanError::VaultPushRejected);
+    }
+
+    // Declared before the callbacks that borrow them
+    let rejection = std::cell::RefCell::new(None);
+    let cred_attempts = std::cell::Cell::new(0u32);
+    let mut remote = repo.find_remote("origin")?;
+    let mut callbacks = RemoteCallbacks::new();
+    credentials::setup_credentials(&mut callbacks, &cred_attempts, None, "vault-push");
+    callbacks.push_update_reference(|_, status| {
+        if let Some(msg) = status {
+            *rejection.borrow_mut() = Some(msg.to_string());
+        }
+        Ok(())
+    });
+    let mut push_options = PushOptions::new();
+    push_options.remote_callbacks(callbacks);
+
+    let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+    remote.push(&[refspec], Some(&mut push_options))?;
+    if let Some(msg) = rejection.borrow().as_ref() {
+        return Err(RepomanError::Other(format!("vault push failed: {}", msg)));
+    }
+
+    info!("vault_push: pushed {} to origin/{}", local, branch);
+    Ok(())
+}
+
+/// Fetch the remote vault and merge it into the local one entry by entry.
+/// With `init_new`, pristines are created for entries that arrived.
+pub fn vault_pull(init_new: bool, config: &Config) -> Result<VaultPullResult> {
+    config.ensure_online("pull the vault")?;
+    let repo = open_vault_repo(config)?;
+    vault_git::commit_if_changed(config, "Update vault")?;
+    let branch = current_branch(&repo)?;
+    let local = repo.refname_to_id("HEAD")?;
+
+    let Some(remote_tip) = fetch_remote(&repo, &branch)? else {
+        return Ok(VaultPullResult {
+            up_to_date: true,
+            ..VaultPullResult::default()
+        });
+    };
+    if remote_tip == local || repo.graph_descendant_of(local, remote_tip)? {
+        return Ok(VaultPullResult {
+            up_to_date: true,
+            ..VaultPullResult::default()
+        });
+    }
+
+    let local_commit = repo.find_commit(local)?;
+    let remote_commit = repo.find_commit(remote_tip)?;
+    let base = match repo.merge_base(local, remote_tip) {
+        Ok(oid) => vault_git::vault_at(&repo, &repo.find_commit(oid)?)?,
+        Err(_) => Vault::default(),
+    };
+    let theirs = vault_git::vault_at(&repo, &remote_commit)?;
+
+    let mut result = Vault::update(config, |vault| {
+        let merge = vault_git::merge_vaults(&base, vault, &theirs);
+        let added = merge
+            .vault
+            .entries
+            .iter()
+            .filter(|e| !vault.entries.iter().any(|l| l.url == e.url))
+            .map(|e| e.name.clone())
+            .collect();
+        *vault = merge.vault;
+        Ok(VaultPullResult {
+            up_to_date: false,
+            added,
+            renamed: merge.renamed,
+            removed: merge.removed,
+            conflicts: merge.conflicts,
+            initialized: Vec::new(),
+        })
+    })?;
+
+    // Record the merge: fast-forward when nothing local had to be kept
+    let tree_id = vault_git::stage_vault(&repo)?;
+    if repo.graph_descendant_of(remote_tip, local)? && remote_commit.tree_id() == tree_id {
+        repo.reference(
+            &format!("refs/heads/{}", branch),
+            remote_tip,
+            true,
+            "vault pull: fast-forward",
+        )?;
+    } else {
+        vault_git::commit_tree(
+            &repo,
+            tree_id,
+            "Merge remote vault changes",
+            &[&local_commit, &remote_commit],
+        )?;
+    }
+
+    apply_local_state(&result, config)?;
+
+    if init_new {
+        for name in &result.added {
+            let vault = Vault::load(config)?;
+            if vault.get_entry(name).is_some_and(|e| e.archived)
+                || config.pristines_dir.join(name).exists()
+            {
+                continue;
+            }
+            match super::init::init_pristine(name, None, None, config) {
+                Ok(_) => result.initialized.push(name.clone()),
+                Err(e) => warn!("vault_pull: failed to initialize '{}': {}", name, e),
+            }
+        }
+    }
+
+    info!(
+        "vault_pull: {} added, {} renamed, {} removed, {} conflict(s)",
+        result.added.len(),
+        result.renamed.len(),
+        result.removed.len(),
+        result.conflicts.len()
+    );
+    Ok(result)
+}
+
+/// Bring this machine's per-repo state in line with merged names: create
+/// metadata for arrivals and move metadata/pristines of renamed entries.
+fn apply_local_state(result: &VaultPullResult, config: &Config) -> Result<()> {
+    for (old, new) in &result.renamed {
+        if let Ok(metadata) = Metadata::load(old, config) {
+            metadata.save(new, config)?;
+            Metadata::remove(old, config)?;
+        }
+        let old_pristine = config.pristines_dir.join(old);
+        let new_pristine = config.pristines_dir.join(new);
+        if old_pristine.exists() && !new_pristine.exists() {
+            if let Some(parent) = new_pristine.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::rename(&old_pristine, &new_pristine)?;
+        }
+    }
+
+    let vault = Vault::load(config)?;
+    for name in &result.added {
+        if Metadata::load(name, config).is_err()
+            && let Some(entry) = vault.get_entry(name)
+        {
+            Metadata::new(vec![entry.url.clone()]).save(name, config)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_config_in;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_push_pull_between_machines() {
+        let temp = TempDir::new().unwrap();
+        let remote = temp.path().join("remote.git");
+        Repository::init_bare(&remote).unwrap();
+        let remote_url = remote.to_str().unwrap();
+
+        let laptop = test_config_in(&temp.path().join("laptop"));
+        let server = test_config_in(&temp.path().join("server"));
+
+        vault_init_git(Some(remote_url), &laptop).unwrap();
+        crate::operations::add_repo(Some("https://example.com/a.git".to_string()), &laptop)
+            .unwrap();
+        vault_push(&laptop).unwrap();
+
+        // Second machine starts empty and picks up the laptop's vault
+        vault_init_git(Some(remote_url), &server).unwrap();
+        let pulled = vault_pull(false, &server).unwrap();
+        assert_eq!(pulled.added, vec!["a"]);
+        assert!(Metadata::load("a", &server).is_ok());
+
+        // Concurrent changes on both machines
+        crate::operations::add_alias("aa", "a", &server).unwrap();
+        vault_push(&server).unwrap();
+        crate::operations::add_repo(Some("https://example.com/b.git".to_string()), &laptop)
+            .unwrap();
+        assert!(matches!(
+            vault_push(&laptop),
+            Err(RepomanError::VaultPushRejected)
+        ));
+
+        let pulled = vault_pull(false, &laptop).unwrap();
+        assert!(pulled.conflicts.is_empty());
+        vault_push(&laptop).unwrap();
+        vault_pull(false, &server).unwrap();
+
+        for config in [&laptop, &server] {
+            let vault = Vault::load(config).unwrap();
+            assert!(vault.contains("a") && vault.contains("b"));
+            assert_eq!(vault.resolve_name("aa"), "a");
+        }
+    }
+}
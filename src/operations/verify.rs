@@ -0,0 +1,506 @@
+//! Object integrity checks for pristines and their clones (`git fsck`, which
+//! follows a clone's alternates into the pristine), and repairs for pristines
+//! that lost objects. libgit2 has no fsck, so these paths use the git CLI.
+
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::error::{RepomanError, Result};
+use crate::metadata::Metadata;
+use crate::throttle;
+use crate::util::run_git;
+use crate::vault::Vault;
+
+/// How `verify` repairs a pristine that fails its check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Repair {
+    /// Fetch every object again from the remote (`git fetch --refetch`)
+    Refetch,
+    /// Clone the pristine again at the same path, so clone alternates stay valid
+    Reclone,
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Refetch => "refetch",
+            Self::Reclone => "reclone",
+        })
+    }
+}
+
+impl FromStr for Repair {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "refetch" => Ok(Self::Refetch),
+            "reclone" => Ok(Self::Reclone),
+            _ => Err(format!(
+                "unknown repair '{}' (expected refetch or reclone)",
+                s
+            )),
+        }
+    }
+}
+
+/// Result of checking one repository's objects
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectCheck {
+    /// `pristine` or the clone's name
+    pub target: String,
+    pub path: PathBuf,
+    /// Objects a ref or another object points at that aren't there (`blob <oid>`)
+    pub missing: Vec<String>,
+    /// Everything else fsck complained about (corrupt objects, broken links,
+    /// unreadable alternates)
+    pub errors: Vec<String>,
+}
+
+impl ObjectCheck {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// A pristine and its clones, checked
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub name: String,
+    pub pristine: ObjectCheck,
+    pub clones: Vec<ObjectCheck>,
+    /// Repair applied to the pristine; the checks above are from after it
+    pub repaired: Option<Repair>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.pristine.is_ok() && self.clones.iter().all(ObjectCheck::is_ok)
+    }
+}
+
+/// Run `git fsck` on a repository. `quick` only checks that every object
+/// reachable from a ref exists, without reading their contents.
+pub fn check_objects(target: &str, path: &Path, quick: bool) -> Result<ObjectCheck> {
+    debug!("check_objects: {} at {}", target, path.display());
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(path)
+        .args(["fsck", "--no-dangling", "--no-progress"]);
+    if quick {
+        command.arg("--connectivity-only");
+    }
+    let output = command.output()?;
+
+    let mut check = ObjectCheck {
+        target: target.to_string(),
+        path: path.to_path_buf(),
+        missing: Vec::new(),
+        errors: Vec::new(),
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for line in stdout.lines().chain(stderr.lines()) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("notice:") {
+            continue;
+        }
+        // "missing blob <oid>", or a ref whose target is gone:
+        // "error: refs/heads/main: invalid sha1 pointer <oid>"
+        let missing = trimmed
+            .strip_prefix("missing ")
+            .map(String::from)
+            .or_else(|| {
+                trimmed
+                    .split_once(": invalid sha1 pointer ")
+                    .map(|(_, oid)| format!("object {}", oid))
+            });
+        if let Some(object) = missing {
+            if !check.missing.contains(&object) {
+                check.missing.push(object);
+            }
+        } else if line.starts_with(char::is_whitespace)
+            && let Some(last) = check.errors.last_mut()
+        {
+            // Second line of "broken link from <a>\n    to <b>"
+            last.push(' ');
+            last.push_str(trimmed);
+        } else {
+            check.errors.push(trimmed.to_string());
+        }
+    }
+    if !output.status.success() && check.is_ok() {
+        check
+            .errors
+            .push(format!("git fsck failed ({})", output.status));
+    }
+    Ok(check)
+}
+
+/// Check a pristine and its clones. With `repair`, a pristine that fails is
+/// repaired and everything is checked again.
+pub fn verify_repo(
+    name: &str,
+    quick: bool,
+    repair: Option<Repair>,
+    config: &Config,
+) -> Result<VerifyReport> {
+    let vault = Vault::load(config)?;
+    if !vault.contains(name) {
+        return Err(RepomanError::RepoNotInVault(name.to_string()));
+    }
+    let name = vault.resolve_name(name).to_string();
+    let pristine_path = config.pristines_dir.join(&name);
+    if !pristine_path.exists() {
+        return Err(RepomanError::PristineNotFound(name));
+    }
+    info!("verify_repo: checking '{}' (quick={})", name, quick);
+
+    let mut report = check_repo(&name, &pristine_path, quick, config)?;
+    if let Some(repair) = repair
+        && !report.pristine.is_ok()
+    {
+        warn!(
+            "verify_repo: pristine '{}' failed its check, repairing ({})",
+            name, repair
+        );
+        match repair {
+            Repair::Refetch => refetch_pristine(&name, &pristine_path, config)?,
+            Repair::Reclone => reclone_pristine(&name, &pristine_path, config)?,
+        }
+        report = check_repo(&name, &pristine_path, quick, config)?;
+        report.repaired = Some(repair);
+    }
+    Ok(report)
+}
+
+fn check_repo(
+    name: &str,
+    pristine_path: &Path,
+    quick: bool,
+    config: &Config,
+) -> Result<VerifyReport> {
+    let pristine = check_objects("pristine", pristine_path, quick)?;
+    let metadata = Metadata::load(name, config)?;
+    let clones = metadata
+        .clones
+        .iter()
+        .filter(|c| c.path.exists())
+        .map(|c| check_objects(&c.name, &c.path, quick))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(VerifyReport {
+        name: name.to_string(),
+        pristine,
+        clones,
+        repaired: None,
+    })
+}
+
+/// Verify every repo that has a pristine (archived ones included)
+pub fn verify_all(
+    quick: bool,
+    repair: Option<Repair>,
+    config: &Config,
+) -> Result<Vec<(String, Result<VerifyReport>)>> {
+    let vault = Vault::load(config)?;
+    Ok(vault
+        .entries
+        .iter()
+        .filter(|e| config.pristines_dir.join(&e.name).exists())
+        .map(|e| (e.name.clone(), verify_repo(&e.name, quick, repair, config)))
+        .collect())
+}
+
+/// Fetch every object of the pristine's refs again, ignoring what it claims to
+/// have. Mirrors are tried after the default URL, as in `sync`.
+fn refetch_pristine(name: &str, pristine_path: &Path, config: &Config) -> Result<()> {
+    config.ensure_online("refetch a pristine")?;
+    let metadata = Metadata::load(name, config)?;
+    let repo = git2::Repository::open_bare(pristine_path)?;
+    let head = super::track::head_branch(&repo);
+    let refspecs = super::track::pristine_refspecs(&metadata, head.as_deref());
+    let filter = super::partial::pristine_filter(pristine_path);
+    let remote_name = metadata.default_remote().unwrap_or("origin");
+    println!("Refetching {}...", name);
+    throttle::warn_unmetered(name, config);
+
+    let path = pristine_path.to_string_lossy();
+    let filter_arg = filter.map(|f| format!("--filter={}", f));
+    let mut last_error = None;
+    for (i, url) in config.fetch_urls(name, &metadata).iter().enumerate() {
+        let _permit = throttle::host_permit(url, config);
+        let source = if i == 0 { remote_name } else { url.as_str() };
+        let mut args = super::pins::fetch_args(&path);
+        args.push("--refetch");
+        if let Some(ref f) = filter_arg {
+            args.push(f);
+        }
+        args.push(source);
+        args.extend(refspecs.iter().map(String::as_str));
+        match run_git(&args) {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                warn!("refetch_pristine: fetch from '{}' failed: {}", url, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| RepomanError::InvalidRepoUrl(name.to_string())))
+}
+
+/// Clone the pristine again at its own path, keeping its partial-clone filter and
+/// shallow depth.
+/// Clones' alternates name that path, so they pick up the new objects as is.
+/// The old pristine is put back if the clone fails. Its clone pins are fetched
+/// into the new one, and it is kept (not deleted) if a clone still fails its
+/// check afterwards.
+fn reclone_pristine(name: &str, pristine_path: &Path, config: &Config) -> Result<()> {
+    config.ensure_online("re-clone a pristine")?;
+    let filter = super::partial::pristine_filter(pristine_path);
+    let depth = super::shallow::shallow_info(pristine_path)
+        .map(|s| i32::try_from(s.depth).unwrap_or(i32::MAX).max(1));
+    let mut backup_name = pristine_path.file_name().unwrap_or_default().to_os_string();
+    backup_name.push(".verify-backup");
+    let backup = pristine_path.with_file_name(backup_name);
+    if backup.exists() {
+        std::fs::remove_dir_all(&backup)?;
+    }
+    std::fs::rename(pristine_path, &backup)?;
+    println!("Re-cloning {}...", name);
+
+    match super::init::init_pristine(name, depth, filter.as_deref(), config) {
+        Ok(_) => {
+            copy_pins(pristine_path, &backup, filter.as_deref());
+            super::pins::pin_clones_or_warn(name, config);
+            super::shallow::sync_clones_shallow_or_warn(name, config);
+
+            let failing: Vec<String> = Metadata::load(name, config)?
+                .clones
+                .iter()
+                .filter(|c| c.path.exists())
+                .map(|c| check_objects(&c.name, &c.path, true))
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .filter(|check| !check.is_ok())
+                .map(|check| check.target)
+                .collect();
+            if failing.is_empty() {
+                std::fs::remove_dir_all(&backup)?;
+            } else {
+                warn!(
+                    "reclone_pristine: clone(s) {} of '{}' still fail their check, keeping {}",
+                    failing.join(", "),
+                    name,
+                    backup.display()
+                );
+                println!(
+                    "  Kept the old pristine at {}: clone(s) {} still fail their check",
+                    backup.display(),
+                    failing.join(", ")
+                );
+            }
+            Ok(())
+        }
+        Err(e) => {
+            warn!(
+                "reclone_pristine: clone failed for '{}', restoring: {}",
+                name, e
+            );
+            if pristine_path.exists() {
+                std::fs::remove_dir_all(pristine_path)?;
+            }
+            std::fs::rename(&backup, pristine_path)?;
+            Err(e)
+        }
+    }
+}
+
+/// Fetch the clone pins, and the commits they keep, from the old pristine into
+/// its re-clone; upstream may no longer have them. Pins whose objects the old
+/// pristine lost are skipped.
+fn copy_pins(pristine_path: &Path, backup: &Path, filter: Option<&str>) {
+    let path = pristine_path.to_string_lossy();
+    let source = backup.to_string_lossy();
+    let filter_arg = filter.map(|f| format!("--filter={}", f));
+    let fetch = |refspec: &str| {
+        let mut args = super::pins::fetch_args(&path);
+        if let Some(ref f) = filter_arg {
+            args.push(f);
+        }
+        args.extend([source.as_ref(), refspec]);
+        run_git(&args)
+    };
+
+    let all = format!("+{0}*:{0}*", super::pins::PIN_PREFIX);
+    if fetch(&all).is_ok() {
+        return;
+    }
+    // Something is missing: take the pins one at a time
+    let pins = run_git(&[
+        "-C",
+        &source,
+        "for-each-ref",
+        "--format=%(refname)",
+        super::pins::PIN_PREFIX,
+    ])
+    .unwrap_or_default();
+    for pin in pins.lines() {
+        if let Err(e) = fetch(&format!("+{0}:{0}", pin)) {
+            warn!("copy_pins: couldn't carry over {}: {}", pin, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{commit, create_test_config};
+    use git2::{Repository, Signature};
+    use tempfile::TempDir;
+
+    /// A pristine (with one clone) whose packs have been deleted
+    fn setup_broken_pristine(temp: &TempDir, config: &Config) -> PathBuf {
+        let source = temp.path().join("source");
+        let repo = Repository::init(&source).unwrap();
+        std::fs::write(source.join("file.txt"), "content").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+
+        let url = source.to_string_lossy().to_string();
+        Vault::update(config, |v| v.add_entry("proj".to_string(), url.clone())).unwrap();
+        Metadata::new(vec![url]).save("proj", config).unwrap();
+        crate::operations::init_pristine("proj", None, None, config).unwrap();
+        crate::operations::clone_from_pristine(
+            "proj",
+            Some("work".to_string()),
+            None,
+            &[],
+            false,
+            config,
+        )
+        .unwrap();
+
+        let pristine_path = config.pristines_dir.join("proj");
+        let report = verify_repo("proj", false, None, config).unwrap();
+        assert!(report.is_ok(), "{:?}", report);
+
+        for dir in ["objects/pack", "objects"] {
+            for entry in std::fs::read_dir(pristine_path.join(dir)).unwrap() {
+                let path = entry.unwrap().path();
+                let keep = ["pack", "info"].iter().any(|n| path.ends_with(n));
+                if path.is_file() {
+                    std::fs::remove_file(&path).unwrap();
+                } else if !keep {
+                    std::fs::remove_dir_all(&path).unwrap();
+                }
+            }
+        }
+        pristine_path
+    }
+
+    #[test]
+    fn test_verify_detects_missing_objects_and_refetches() {
+        let (temp, config) = create_test_config();
+        setup_broken_pristine(&temp, &config);
+
+        let report = verify_repo("proj", true, None, &config).unwrap();
+        assert!(!report.is_ok());
+        assert!(!report.pristine.missing.is_empty());
+        // The clone reads the pristine's objects through alternates
+        assert_eq!(report.clones.len(), 1);
+        assert!(!report.clones[0].is_ok());
+        assert_eq!(report.repaired, None);
+
+        let report = verify_repo("proj", false, Some(Repair::Refetch), &config).unwrap();
+        assert_eq!(report.repaired, Some(Repair::Refetch));
+        assert!(report.is_ok(), "{:?}", report);
+    }
+
+    fn packs(pristine_path: &Path) -> Vec<PathBuf> {
+        std::fs::read_dir(pristine_path.join("objects/pack"))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_reclone_keeps_clones_working() {
+        let (temp, config) = create_test_config();
+        let pristine_path = setup_broken_pristine(&temp, &config);
+
+        let report = verify_repo("proj", false, Some(Repair::Reclone), &config).unwrap();
+        assert_eq!(report.repaired, Some(Repair::Reclone));
+        assert!(report.is_ok(), "{:?}", report);
+        assert!(!pristine_path.with_file_name("proj.verify-backup").exists());
+        // Clone metadata survives the re-clone
+        assert_eq!(Metadata::load("proj", &config).unwrap().clones.len(), 1);
+
+        // A clone on a commit upstream has since force-pushed away
+        let source = temp.path().join("source");
+        let source_repo = Repository::open(&source).unwrap();
+        let base = source_repo.head().unwrap().target().unwrap();
+        commit(&source_repo, "doomed.txt", &[base]);
+        crate::operations::sync_pristine("proj", &config).unwrap();
+        crate::operations::clone_from_pristine(
+            "proj",
+            Some("stale".to_string()),
+            None,
+            &[],
+            false,
+            &config,
+        )
+        .unwrap();
+        let branch = source_repo.head().unwrap().shorthand().unwrap().to_string();
+        source_repo
+            .reference(&format!("refs/heads/{}", branch), base, true, "reset")
+            .unwrap();
+        commit(&source_repo, "c.txt", &[base]);
+        // Upstream drops the old commit, so a re-clone can't get it from there
+        let source_arg = source.to_string_lossy();
+        run_git(&[
+            "-C",
+            &source_arg,
+            "reflog",
+            "expire",
+            "--expire=now",
+            "--all",
+        ])
+        .unwrap();
+        run_git(&["-C", &source_arg, "gc", "--quiet", "--prune=now"]).unwrap();
+        let before = packs(&pristine_path);
+        crate::operations::sync_pristine("proj", &config).unwrap();
+        crate::operations::pin_clones("proj", &config).unwrap();
+
+        // Lose only what the last sync brought; the pinned commit survives
+        for pack in packs(&pristine_path) {
+            if !before.contains(&pack) {
+                std::fs::remove_file(pack).unwrap();
+            }
+        }
+        let report = verify_repo("proj", true, None, &config).unwrap();
+        assert!(!report.pristine.is_ok());
+
+        let report = verify_repo("proj", false, Some(Repair::Reclone), &config).unwrap();
+        assert!(report.is_ok(), "{:?}", report);
+        assert_eq!(report.clones.len(), 2);
+        assert!(!pristine_path.with_file_name("proj.verify-backup").exists());
+    }
+
+    #[test]
+    fn test_repair_parse() {
+        assert_eq!("refetch".parse::<Repair>().unwrap(), Repair::Refetch);
+        assert_eq!("reclone".parse::<Repair>().unwrap(), Repair::Reclone);
+        assert!("rebuild".parse::<Repair>().is_err());
+    }
+}
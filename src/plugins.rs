@@ -17,6 +17,7 @@ pub struct HookContext {
     pub clone_path: Option<String>,
     pub clone_name: Option<String>,
     pub new_tag: Option<String>,
+    pub pruned_refs: Option<Vec<String>>,
 }
 
 /// Manages Lua plugin lifecycle.
@@ -207,6 +208,9 @@ impl PluginManager {
         if let Some(ref t) = context.new_tag {
             ctx.set("new_tag", t.as_str()).map_err(lua_err)?;
         }
+        if let Some(ref refs) = context.pruned_refs {
+            ctx.set("pruned_refs", refs.clone()).map_err(lua_err)?;
+        }
 
         for pair in callbacks.pairs::<LuaValue, LuaFunction>() {
             if let Ok((_, func)) = pair
@@ -263,6 +267,7 @@ mod tests {
             clone_path: None,
             clone_name: None,
             new_tag: None,
+            pruned_refs: None,
         };
         pm.run_hook("post_clone", &ctx).unwrap();
     }
@@ -0,0 +1,344 @@
+//! On-disk schema versioning for `vault.json` and `metadata.json`.
+//!
+//! Both files carry a `schema_version` field. Files written before versioning
+//! existed have no field and are treated as version 0. On load, older files are
+//! upgraded step by step through the migration table below; the original bytes
+//! are kept next to the file as `<file>.v<N>.bak` before anything is rewritten.
+//!
+//! Files with a *newer* version than this build understands are still readable
+//! (unknown fields are ignored), but saving them is refused so an older repoman
+//! never silently drops data written by a newer one.
+
+use log::{debug, info, warn};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::error::{RepomanError, Result};
+use crate::metadata::default_remote_name;
+use crate::storage;
+
+/// Current schema version for `vault.json`.
+pub const VAULT_SCHEMA_VERSION: u32 = 2;
+
+/// Current schema version for `metadata.json`.
+pub const METADATA_SCHEMA_VERSION: u32 = 2;
+
+/// Which kind of state file a JSON document belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateFile {
+    Vault,
+    Metadata,
+}
+
+impl StateFile {
+    /// Schema version this build reads and writes.
+    pub fn current_version(self) -> u32 {
+        match self {
+            StateFile::Vault => VAULT_SCHEMA_VERSION,
+            StateFile::Metadata => METADATA_SCHEMA_VERSION,
+        }
+    }
+
+    fn migrations(self) -> &'static [Migration] {
+        match self {
+            StateFile::Vault => VAULT_MIGRATIONS,
+            StateFile::Metadata => METADATA_MIGRATIONS,
+        }
+    }
+}
+
+/// A single upgrade step from `from` to `from + 1`.
+struct Migration {
+    from: u32,
+    description: &'static str,
+    apply: fn(&mut Value),
+}
+
+const VAULT_MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "add schema_version and ensure 'aliases' map exists",
+        apply: vault_v0_to_v1,
+    },
+    Migration {
+        from: 1,
+        description: "add description, tags, owner, notes and archived to entries",
+        apply: vault_v1_to_v2,
+    },
+];
+
+const METADATA_MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "add schema_version and ensure list fields exist",
+        apply: metadata_v0_to_v1,
+    },
+    Migration {
+        from: 1,
+        description: "replace git_urls with named remotes",
+        apply: metadata_v1_to_v2,
+    },
+];
+
+fn vault_v0_to_v1(value: &mut Value) {
+    if let Some(obj) = value.as_object_mut() {
+        obj.entry("entries")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        obj.entry("aliases")
+            .or_insert_with(|| Value::Object(serde_json::Map::new()));
+    }
+}
+
+fn vault_v1_to_v2(value: &mut Value) {
+    let Some(entries) = value.get_mut("entries").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for entry in entries.iter_mut().filter_map(Value::as_object_mut) {
+        for key in ["description", "owner", "notes"] {
+            entry.entry(key).or_insert(Value::Null);
+        }
+        entry
+            .entry("tags")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        entry.entry("archived").or_insert(Value::Bool(false));
+    }
+}
+
+fn metadata_v0_to_v1(value: &mut Value) {
+    if let Some(obj) = value.as_object_mut() {
+        for key in ["git_urls", "tracked_branches", "clones"] {
+            obj.entry(key).or_insert_with(|| Value::Array(Vec::new()));
+        }
+    }
+}
+
+fn metadata_v1_to_v2(value: &mut Value) {
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+    let urls = match obj.remove("git_urls") {
+        Some(Value::Array(urls)) => urls,
+        _ => Vec::new(),
+    };
+    let remotes = urls
+        .into_iter()
+        .enumerate()
+        .map(|(i, url)| serde_json::json!({ "name": default_remote_name(i), "url": url }))
+        .collect();
+    obj.entry("remotes").or_insert(Value::Array(remotes));
+}
+
+/// Read the `schema_version` of a raw document (missing = 0).
+pub fn version_of(value: &Value) -> u32 {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map_or(0, |v| u32::try_from(v).unwrap_or(u32::MAX))
+}
+
+/// Whether `value` is older than the current schema and must be migrated before use.
+pub fn is_outdated(kind: StateFile, value: &Value) -> bool {
+    version_of(value) < kind.current_version()
+}
+
+/// Descriptions of the migrations that would run on `value`, in order.
+pub fn pending_migrations(kind: StateFile, value: &Value) -> Vec<String> {
+    let from = version_of(value);
+    kind.migrations()
+        .iter()
+        .filter(|m| m.from >= from && m.from < kind.current_version())
+        .map(|m| format!("v{} -> v{}: {}", m.from, m.from + 1, m.description))
+        .collect()
+}
+
+/// Apply every pending migration to `value` in place and stamp the new version.
+/// Returns the descriptions of the steps that ran.
+pub fn migrate(kind: StateFile, value: &mut Value) -> Vec<String> {
+    let mut version = version_of(value);
+    let mut applied = Vec::new();
+
+    for m in kind.migrations() {
+        if m.from == version && version < kind.current_version() {
+            (m.apply)(value);
+            version = m.from + 1;
+            applied.push(format!("v{} -> v{}: {}", m.from, version, m.description));
+        }
+    }
+
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("schema_version".to_string(), Value::from(version));
+    }
+    applied
+}
+
+/// Path of the backup kept before migrating a file away from `version`.
+pub fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    path.with_file_name(name)
+}
+
+/// Parse a state file, upgrading it on disk if it uses an older schema.
+///
+/// The original file is copied to [`backup_path`] before being rewritten. A
+/// file with a newer schema is returned untouched (with a warning). Callers must
+/// hold the file's exclusive lock, since this may rewrite it.
+pub fn load_and_migrate(path: &Path, kind: StateFile) -> Result<Value> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut value: Value = serde_json::from_str(&contents)?;
+    let version = version_of(&value);
+    let current = kind.current_version();
+
+    if version > current {
+        warn!(
+            "schema: {} has schema v{} (this build supports v{}); loading read-only",
+            path.display(),
+            version,
+            current
+        );
+        return Ok(value);
+    }
+
+    if version == current {
+        return Ok(value);
+    }
+
+    let backup = backup_path(path, version);
+    if !backup.exists() {
+        storage::atomic_write(&backup, contents.as_bytes())?;
+        debug!(
+            "schema: backed up {} to {}",
+            path.display(),
+            backup.display()
+        );
+    }
+
+    let steps = migrate(kind, &mut value);
+    storage::atomic_write(path, serde_json::to_string_pretty(&value)?.as_bytes())?;
+    info!(
+        "schema: migrated {} from v{} to v{} ({} step(s))",
+        path.display(),
+        version,
+        current,
+        steps.len()
+    );
+    Ok(value)
+}
+
+/// Resolve the version to stamp on save. Refuses to downgrade a file that was
+/// loaded with a newer schema than this build understands.
+pub fn version_for_save(kind: StateFile, loaded: u32, label: &str) -> Result<u32> {
+    let current = kind.current_version();
+    if loaded > current {
+        return Err(RepomanError::SchemaTooNew(
+            label.to_string(),
+            loaded,
+            current,
+        ));
+    }
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_version_of_missing_is_zero() {
+        assert_eq!(version_of(&json!({"entries": []})), 0);
+        assert_eq!(version_of(&json!({"schema_version": 3})), 3);
+    }
+
+    #[test]
+    fn test_migrate_vault_v0() {
+        let mut value = json!({"entries": []});
+        let steps = migrate(StateFile::Vault, &mut value);
+        assert_eq!(steps.len(), VAULT_MIGRATIONS.len());
+        assert_eq!(version_of(&value), VAULT_SCHEMA_VERSION);
+        assert!(value["aliases"].is_object());
+    }
+
+    #[test]
+    fn test_migrate_vault_v1_adds_entry_fields() {
+        let mut value = json!({
+            "schema_version": 1,
+            "entries": [{"name": "repo", "url": "url", "added_date": "2026-01-01T00:00:00Z"}],
+            "aliases": {}
+        });
+        let steps = migrate(StateFile::Vault, &mut value);
+        assert_eq!(steps.len(), 1);
+        let entry = &value["entries"][0];
+        assert!(entry["tags"].is_array());
+        assert_eq!(entry["archived"], json!(false));
+        assert!(entry["description"].is_null());
+    }
+
+    #[test]
+    fn test_migrate_metadata_fills_lists() {
+        let mut value = json!({
+            "git_urls": ["url"],
+            "created_on": "2026-01-01T00:00:00Z",
+            "last_updated": "2026-01-01T00:00:00Z"
+        });
+        migrate(StateFile::Metadata, &mut value);
+        assert!(value["clones"].is_array());
+        assert!(value["tracked_branches"].is_array());
+        assert_eq!(version_of(&value), METADATA_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_migrate_metadata_v1_names_remotes() {
+        let mut value = json!({
+            "schema_version": 1,
+            "git_urls": ["fork-url", "canonical-url"],
+            "clones": []
+        });
+        migrate(StateFile::Metadata, &mut value);
+        assert!(value.get("git_urls").is_none());
+        assert_eq!(
+            value["remotes"],
+            json!([
+                {"name": "origin", "url": "fork-url"},
+                {"name": "remote2", "url": "canonical-url"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_pending_empty_when_current() {
+        let value = json!({"schema_version": VAULT_SCHEMA_VERSION});
+        assert!(pending_migrations(StateFile::Vault, &value).is_empty());
+    }
+
+    #[test]
+    fn test_load_and_migrate_keeps_backup() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("vault.json");
+        std::fs::write(&path, r#"{"entries": []}"#).unwrap();
+
+        let value = load_and_migrate(&path, StateFile::Vault).unwrap();
+        assert_eq!(version_of(&value), VAULT_SCHEMA_VERSION);
+
+        let backup = backup_path(&path, 0);
+        assert!(backup.exists());
+        assert_eq!(
+            std::fs::read_to_string(&backup).unwrap(),
+            r#"{"entries": []}"#
+        );
+
+        let on_disk: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(version_of(&on_disk), VAULT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_newer_schema_refuses_save() {
+        let result = version_for_save(StateFile::Vault, VAULT_SCHEMA_VERSION + 1, "vault.json");
+        assert!(matches!(result, Err(RepomanError::SchemaTooNew(..))));
+        assert_eq!(
+            version_for_save(StateFile::Vault, 0, "vault.json").unwrap(),
+            VAULT_SCHEMA_VERSION
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Crash-safe persistence for state files (vault.json, metadata.json).
+//!
+//! Writes go to a temp file in the same directory, are fsynced, then renamed over
+//! the target so readers only ever see the old or the new contents, never a
+//! truncated file. Readers take a shared advisory lock, writers an exclusive one.
+
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Atomically replace `path` with `contents`.
+///
+/// The temp file lives next to the target (same filesystem, so the rename is atomic)
+/// and is removed if any step before the rename fails.
+pub fn atomic_write(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp_path = dir.join(format!(".{}.tmp.{}", file_name, std::process::id()));
+
+    let result = (|| {
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(contents)?;
+        tmp.sync_all()?;
+        std::fs::rename(&tmp_path, path)?;
+        sync_dir(dir)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Flush the directory entry so the rename itself survives a crash.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+fn open_lock_file(lock_path: &Path) -> std::io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_path)
+}
+
+/// Take a shared (read) lock. Released when the returned handle is dropped.
+pub fn lock_shared(lock_path: &Path) -> std::io::Result<File> {
+    let file = open_lock_file(lock_path)?;
+    file.lock_shared()?;
+    Ok(file)
+}
+
+/// Take an exclusive (write) lock. Released when the returned handle is dropped.
+///
+/// Locks are per open file handle, so the holder must not try to take another lock on
+/// the same path (e.g. by calling `load`/`save`) until this one is dropped.
+pub fn lock_exclusive(lock_path: &Path) -> std::io::Result<File> {
+    let file = open_lock_file(lock_path)?;
+    file.lock_exclusive()?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atomic_write_replaces_contents() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("state.json");
+        std::fs::write(&path, "old contents that are longer").unwrap();
+
+        atomic_write(&path, b"new").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+
+        // No temp files left behind
+        let leftovers: Vec<_> = std::fs::read_dir(temp.path())
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().contains(".tmp."))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_atomic_write_missing_dir_fails_cleanly() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("missing").join("state.json");
+        assert!(atomic_write(&path, b"x").is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_shared_locks_coexist() {
+        let temp = tempfile::tempdir().unwrap();
+        let lock_path = temp.path().join(".state.lock");
+        let a = lock_shared(&lock_path).unwrap();
+        let b = lock_shared(&lock_path).unwrap();
+        drop(a);
+        drop(b);
+        let _c = lock_exclusive(&lock_path).unwrap();
+    }
+}
@@ -0,0 +1,310 @@
+//! JSON file backend: `vault.json` + `<repo>/metadata.json`, with advisory locks.
+
+use log::warn;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use super::{RawDocument, StateStore, encode_metadata, encode_vault};
+use crate::error::{RepomanError, Result};
+use crate::metadata::Metadata;
+use crate::schema::{self, StateFile};
+use crate::storage;
+use crate::util;
+use crate::vault::Vault;
+
+pub struct JsonStore {
+    vault_dir: PathBuf,
+}
+
+impl JsonStore {
+    pub fn new(vault_dir: PathBuf) -> Self {
+        Self { vault_dir }
+    }
+
+    fn vault_path(&self) -> PathBuf {
+        self.vault_dir.join("vault.json")
+    }
+
+    fn vault_lock_path(&self) -> PathBuf {
+        self.vault_dir.join(".vault.lock")
+    }
+
+    fn metadata_dir(&self, repo_name: &str) -> PathBuf {
+        self.vault_dir.join(repo_name)
+    }
+
+    /// Names of every repo with a `metadata.json`, including namespaced ones
+    /// (`alice/utils` lives in `vault_dir/alice/utils/`). Hidden dirs are skipped.
+    fn metadata_names(&self) -> Result<Vec<String>> {
+        const MAX_DEPTH: usize = 4;
+        let mut names = Vec::new();
+        let mut pending = vec![(self.vault_dir.clone(), String::new(), 0)];
+        while let Some((dir, prefix, depth)) = pending.pop() {
+            if !dir.is_dir() || depth >= MAX_DEPTH {
+                continue;
+            }
+            for entry in std::fs::read_dir(&dir)?.flatten() {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                let path = entry.path();
+                if file_name.starts_with('.') || !path.is_dir() {
+                    continue;
+                }
+                let name = if prefix.is_empty() {
+                    file_name
+                } else {
+                    format!("{}/{}", prefix, file_name)
+                };
+                if path.join("metadata.json").exists() {
+                    names.push(name.clone());
+                }
+                pending.push((path, name, depth + 1));
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn lock_metadata(&self, repo_name: &str) -> Result<File> {
+        storage::lock_exclusive(&self.metadata_dir(repo_name).join(".metadata.lock"))
+            .map_err(|e| RepomanError::MetadataSaveError(repo_name.to_string(), e.to_string()))
+    }
+
+    /// Read (and migrate if needed) without locking. Caller holds the exclusive lock.
+    fn load_vault_unlocked(&self) -> Result<Vault> {
+        let value = schema::load_and_migrate(&self.vault_path(), StateFile::Vault)
+            .map_err(|e| RepomanError::VaultLoadError(e.to_string()))?;
+        serde_json::from_value(value).map_err(|e| RepomanError::VaultLoadError(e.to_string()))
+    }
+
+    /// Write atomically without locking. Caller holds the exclusive lock.
+    fn save_vault_unlocked(&self, vault: &Vault) -> Result<()> {
+        let contents = encode_vault(vault)?;
+        storage::atomic_write(&self.vault_path(), contents.as_bytes())
+            .map_err(|e| RepomanError::VaultSaveError(e.to_string()))
+    }
+
+    /// Read (and migrate if needed) without locking. Caller holds the exclusive lock.
+    fn load_metadata_unlocked(&self, repo_name: &str) -> Result<Metadata> {
+        let metadata_path = self.metadata_dir(repo_name).join("metadata.json");
+        let value = schema::load_and_migrate(&metadata_path, StateFile::Metadata)
+            .map_err(|e| RepomanError::MetadataLoadError(repo_name.to_string(), e.to_string()))?;
+        serde_json::from_value(value)
+            .map_err(|e| RepomanError::MetadataLoadError(repo_name.to_string(), e.to_string()))
+    }
+
+    /// Write atomically without locking. Caller holds the exclusive lock.
+    fn save_metadata_unlocked(&self, repo_name: &str, metadata: &Metadata) -> Result<()> {
+        let contents = encode_metadata(repo_name, metadata)?;
+        let metadata_path = self.metadata_dir(repo_name).join("metadata.json");
+        storage::atomic_write(&metadata_path, contents.as_bytes())
+            .map_err(|e| RepomanError::MetadataSaveError(repo_name.to_string(), e.to_string()))
+    }
+
+    /// Read a state file under a shared lock, upgrading to an exclusive lock only
+    /// if the file needs a schema migration (which rewrites it).
+    fn read_locked(
+        path: &Path,
+        lock_path: &Path,
+        kind: StateFile,
+    ) -> std::result::Result<serde_json::Value, String> {
+        let lock_file =
+            storage::lock_shared(lock_path).map_err(|e| format!("lock failed: {}", e))?;
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let value: serde_json::Value =
+            serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+
+        if schema::is_outdated(kind, &value) {
+            drop(lock_file);
+            let _lock =
+                storage::lock_exclusive(lock_path).map_err(|e| format!("lock failed: {}", e))?;
+            return schema::load_and_migrate(path, kind).map_err(|e| e.to_string());
+        }
+        Ok(value)
+    }
+}
+
+impl StateStore for JsonStore {
+    fn load_vault(&self) -> Result<Vault> {
+        let vault_path = self.vault_path();
+        if !vault_path.exists() {
+            return Ok(Vault::default());
+        }
+
+        let value = Self::read_locked(&vault_path, &self.vault_lock_path(), StateFile::Vault)
+            .map_err(RepomanError::VaultLoadError)?;
+        serde_json::from_value(value).map_err(|e| RepomanError::VaultLoadError(e.to_string()))
+    }
+
+    fn save_vault(&self, vault: &Vault) -> Result<()> {
+        let _lock = storage::lock_exclusive(&self.vault_lock_path())
+            .map_err(|e| RepomanError::VaultSaveError(format!("lock failed: {}", e)))?;
+        self.save_vault_unlocked(vault)
+    }
+
+    fn update_vault(&self, f: &mut dyn FnMut(&mut Vault) -> Result<()>) -> Result<()> {
+        std::fs::create_dir_all(&self.vault_dir)
+            .map_err(|e| RepomanError::VaultSaveError(e.to_string()))?;
+        let _lock = storage::lock_exclusive(&self.vault_lock_path())
+            .map_err(|e| RepomanError::VaultSaveError(format!("lock failed: {}", e)))?;
+
+        let mut vault = if self.vault_path().exists() {
+            self.load_vault_unlocked()?
+        } else {
+            Vault::default()
+        };
+        f(&mut vault)?;
+        self.save_vault_unlocked(&vault)
+    }
+
+    fn load_metadata(&self, repo_name: &str) -> Result<Metadata> {
+        let metadata_dir = self.metadata_dir(repo_name);
+        let metadata_path = metadata_dir.join("metadata.json");
+
+        if !metadata_path.exists() {
+            return Err(RepomanError::MetadataLoadError(
+                repo_name.to_string(),
+                "File not found".to_string(),
+            ));
+        }
+
+        let value = Self::read_locked(
+            &metadata_path,
+            &metadata_dir.join(".metadata.lock"),
+            StateFile::Metadata,
+        )
+        .map_err(|e| RepomanError::MetadataLoadError(repo_name.to_string(), e))?;
+        serde_json::from_value(value)
+            .map_err(|e| RepomanError::MetadataLoadError(repo_name.to_string(), e.to_string()))
+    }
+
+    fn save_metadata(&self, repo_name: &str, metadata: &Metadata) -> Result<()> {
+        std::fs::create_dir_all(self.metadata_dir(repo_name))?;
+        let _lock = self.lock_metadata(repo_name)?;
+        self.save_metadata_unlocked(repo_name, metadata)
+    }
+
+    fn update_metadata(
+        &self,
+        repo_name: &str,
+        f: &mut dyn FnMut(&mut Metadata) -> Result<()>,
+    ) -> Result<()> {
+        if !self.metadata_dir(repo_name).join("metadata.json").exists() {
+            return Err(RepomanError::MetadataLoadError(
+                repo_name.to_string(),
+                "File not found".to_string(),
+            ));
+        }
+
+        let _lock = self.lock_metadata(repo_name)?;
+        let mut metadata = self.load_metadata_unlocked(repo_name)?;
+        f(&mut metadata)?;
+        self.save_metadata_unlocked(repo_name, &metadata)
+    }
+
+    fn update_metadata_many(
+        &self,
+        repo_names: &[String],
+        f: &mut dyn FnMut(&str, &mut Metadata) -> Result<()>,
+    ) -> Result<()> {
+        // Lock in sorted order so two multi-repo updates can't deadlock each other
+        let mut names: Vec<&String> = repo_names
+            .iter()
+            .filter(|n| self.metadata_dir(n).join("metadata.json").exists())
+            .collect();
+        names.sort();
+        names.dedup();
+
+        let mut locks = Vec::with_capacity(names.len());
+        for name in &names {
+            locks.push(self.lock_metadata(name)?);
+        }
+
+        // Apply every change in memory first; nothing is written if any callback fails
+        let mut updated = Vec::with_capacity(names.len());
+        for name in &names {
+            let mut metadata = self.load_metadata_unlocked(name)?;
+            f(name, &mut metadata)?;
+            updated.push((name, metadata));
+        }
+        for (name, metadata) in &updated {
+            self.save_metadata_unlocked(name, metadata)?;
+        }
+        Ok(())
+    }
+
+    fn remove_metadata(&self, repo_name: &str) -> Result<()> {
+        let metadata_dir = self.metadata_dir(repo_name);
+        if metadata_dir.exists() {
+            let prefix = format!("{}/", repo_name);
+            let names = self.metadata_names()?;
+            let nested: Vec<&str> = names
+                .iter()
+                .filter_map(|n| n.strip_prefix(&prefix))
+                .map(|rest| rest.split('/').next().unwrap_or(rest))
+                .collect();
+            util::remove_dir_keeping(&metadata_dir, &nested)?;
+        }
+        Ok(())
+    }
+
+    fn load_all_metadata(&self) -> Result<HashMap<String, Metadata>> {
+        let mut all = HashMap::new();
+        if !self.vault_dir.is_dir() {
+            return Ok(all);
+        }
+        for name in self.metadata_names()? {
+            match self.load_metadata(&name) {
+                Ok(m) => {
+                    all.insert(name, m);
+                }
+                Err(e) => warn!("store: skipping metadata for '{}': {}", name, e),
+            }
+        }
+        Ok(all)
+    }
+
+    fn raw_documents(&self) -> Result<Vec<RawDocument>> {
+        let mut docs = Vec::new();
+        let mut read = |path: PathBuf, lock_path: PathBuf, repo: Option<String>| -> Result<()> {
+            let _lock = storage::lock_shared(&lock_path)?;
+            let value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            docs.push(RawDocument {
+                location: path.display().to_string(),
+                repo,
+                value,
+            });
+            Ok(())
+        };
+
+        if self.vault_path().exists() {
+            read(self.vault_path(), self.vault_lock_path(), None)?;
+        }
+
+        for name in self.metadata_names()? {
+            let dir = self.metadata_dir(&name);
+            read(
+                dir.join("metadata.json"),
+                dir.join(".metadata.lock"),
+                Some(name),
+            )?;
+        }
+        Ok(docs)
+    }
+
+    fn location(&self) -> String {
+        self.vault_dir.display().to_string()
+    }
+
+    fn find_clone_owner(&self, clone_name: &str) -> Result<Option<String>> {
+        let vault = self.load_vault()?;
+        for name in vault.get_all_names() {
+            if let Ok(metadata) = self.load_metadata(name)
+                && metadata.get_clone(clone_name).is_some()
+            {
+                return Ok(Some(name.to_string()));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,133 @@
+//! Pluggable persistence for vault and metadata state.
+//!
+//! `Vault` and `Metadata` never touch disk directly; they go through a [`StateStore`]
+//! chosen by `state_backend` in config.yaml:
+//!
+//! - `json` (default): `vault_dir/vault.json` plus one `vault_dir/<repo>/metadata.json`
+//!   per repo, guarded by advisory file locks.
+//! - `sqlite`: a single `vault_dir/state.db`, with indexed clone lookups and real
+//!   multi-repo transactions.
+//!
+//! Both backends store the same versioned JSON documents, so schema migrations
+//! (see `schema.rs`) apply identically and `repoman migrate --to-sqlite/--to-json`
+//! can copy state between them losslessly.
+
+mod json;
+mod sqlite;
+
+pub use json::JsonStore;
+pub use sqlite::SqliteStore;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::config::{Config, StateBackend};
+use crate::error::{RepomanError, Result};
+use crate::metadata::Metadata;
+use crate::schema::{self, StateFile};
+use crate::vault::Vault;
+
+/// Backend-independent access to persisted state.
+///
+/// `update_*` methods run load-modify-save as one transaction: concurrent writers are
+/// serialized and nothing is written if the callback fails. Callbacks must not
+/// re-enter the store for the same state.
+pub trait StateStore {
+    /// Load the vault, or an empty one if none has been saved yet.
+    fn load_vault(&self) -> Result<Vault>;
+    fn save_vault(&self, vault: &Vault) -> Result<()>;
+    fn update_vault(&self, f: &mut dyn FnMut(&mut Vault) -> Result<()>) -> Result<()>;
+
+    fn load_metadata(&self, repo_name: &str) -> Result<Metadata>;
+    fn save_metadata(&self, repo_name: &str, metadata: &Metadata) -> Result<()>;
+    fn update_metadata(
+        &self,
+        repo_name: &str,
+        f: &mut dyn FnMut(&mut Metadata) -> Result<()>,
+    ) -> Result<()>;
+    /// Update several repos as one unit. Repos without metadata are skipped.
+    fn update_metadata_many(
+        &self,
+        repo_names: &[String],
+        f: &mut dyn FnMut(&str, &mut Metadata) -> Result<()>,
+    ) -> Result<()>;
+    /// Delete a repo's metadata (no-op if absent).
+    fn remove_metadata(&self, repo_name: &str) -> Result<()>;
+
+    /// Every repo's metadata in one pass, keyed by repo name. Unreadable entries are skipped.
+    fn load_all_metadata(&self) -> Result<HashMap<String, Metadata>>;
+    /// Which repo owns a clone entry with this name, if any.
+    fn find_clone_owner(&self, clone_name: &str) -> Result<Option<String>>;
+
+    /// Every stored document as raw JSON, without migrating it (vault first).
+    fn raw_documents(&self) -> Result<Vec<RawDocument>>;
+    /// Human-readable location of the store (for messages).
+    fn location(&self) -> String;
+}
+
+/// An unparsed state document, used to inspect schema versions.
+pub struct RawDocument {
+    /// Where the document lives (file path, or `state.db:<table>/<key>`).
+    pub location: String,
+    /// Owning repo for metadata documents; `None` for the vault.
+    pub repo: Option<String>,
+    pub value: serde_json::Value,
+}
+
+/// Path of the SQLite database for a config.
+pub fn sqlite_path(config: &Config) -> PathBuf {
+    config.vault_dir.join("state.db")
+}
+
+/// Open the store configured for `config`.
+pub fn open(config: &Config) -> Box<dyn StateStore> {
+    open_backend(config.state_backend(), config)
+}
+
+/// Open a specific backend regardless of config (used when converting between them).
+pub fn open_backend(backend: StateBackend, config: &Config) -> Box<dyn StateStore> {
+    match backend {
+        StateBackend::Json => Box::new(JsonStore::new(config.vault_dir.clone())),
+        StateBackend::Sqlite => Box::new(SqliteStore::new(sqlite_path(config))),
+    }
+}
+
+/// Copy every vault entry and metadata document from one backend to another.
+/// Returns the number of repos whose metadata was copied.
+pub fn copy_state(from: &dyn StateStore, to: &dyn StateStore) -> Result<usize> {
+    let vault = from.load_vault()?;
+    let all = from.load_all_metadata()?;
+    to.save_vault(&vault)?;
+
+    let mut copied = 0;
+    for entry in &vault.entries {
+        if let Some(metadata) = all.get(&entry.name) {
+            to.save_metadata(&entry.name, metadata)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Serialize a vault with the current schema version stamped in.
+fn encode_vault(vault: &Vault) -> Result<String> {
+    let version = schema::version_for_save(StateFile::Vault, vault.schema_version, "vault.json")?;
+    let mut value =
+        serde_json::to_value(vault).map_err(|e| RepomanError::VaultSaveError(e.to_string()))?;
+    value["schema_version"] = version.into();
+    serde_json::to_string_pretty(&value).map_err(|e| RepomanError::VaultSaveError(e.to_string()))
+}
+
+/// Serialize metadata with the current schema version stamped in.
+fn encode_metadata(repo_name: &str, metadata: &Metadata) -> Result<String> {
+    let version = schema::version_for_save(
+        StateFile::Metadata,
+        metadata.schema_version,
+        &format!("{}/metadata.json", repo_name),
+    )?;
+    let mut value = serde_json::to_value(metadata)
+        .map_err(|e| RepomanError::MetadataSaveError(repo_name.to_string(), e.to_string()))?;
+    value["schema_version"] = version.into();
+    serde_json::to_string_pretty(&value)
+        .map_err(|e| RepomanError::MetadataSaveError(repo_name.to_string(), e.to_string()))
+}
@@ -0,0 +1,428 @@
+//! SQLite backend: all state in a single `vault_dir/state.db`.
+//!
+//! Documents are stored as the same versioned JSON used by the file backend. A
+//! `clones` side table mirrors every metadata's clone list so clone-to-repo
+//! lookups are a single indexed query. Writers use `BEGIN IMMEDIATE`, so
+//! concurrent processes are serialized by SQLite itself.
+
+use rusqlite::{Connection, OptionalExtension, Transaction, TransactionBehavior, params};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use super::{RawDocument, StateStore, encode_metadata, encode_vault};
+use crate::error::{RepomanError, Result};
+use crate::metadata::Metadata;
+use crate::schema::{self, StateFile};
+use crate::vault::Vault;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS vault (
+    id   INTEGER PRIMARY KEY CHECK (id = 0),
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS metadata (
+    repo TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS clones (
+    repo TEXT NOT NULL REFERENCES metadata(repo) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    path TEXT NOT NULL,
+    PRIMARY KEY (repo, name)
+);
+CREATE INDEX IF NOT EXISTS clones_by_name ON clones(name);
+";
+
+/// Idle connections per database file. `Metadata::load` and friends open a new
+/// store for every call, so connections are pooled here rather than on the store;
+/// a connection checked out by an outer call never blocks a nested one.
+static POOL: LazyLock<Mutex<HashMap<PathBuf, Vec<Connection>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Idle connections kept per database
+const MAX_IDLE: usize = 4;
+
+fn pool() -> MutexGuard<'static, HashMap<PathBuf, Vec<Connection>>> {
+    POOL.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A pooled connection, returned to the pool on drop
+struct PooledConnection {
+    path: PathBuf,
+    conn: Option<Connection>,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection present until drop")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("connection present until drop")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        let mut pool = pool();
+        let idle = pool.entry(self.path.clone()).or_default();
+        if idle.len() < MAX_IDLE {
+            idle.push(conn);
+        }
+    }
+}
+
+pub struct SqliteStore {
+    path: PathBuf,
+}
+
+impl SqliteStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Check out a pooled connection, opening (and setting up the schema on) a
+    /// new one only when none is idle
+    fn connect(&self) -> Result<PooledConnection> {
+        let idle = pool().get_mut(&self.path).and_then(Vec::pop);
+        let conn = match idle {
+            Some(conn) => conn,
+            None => self.open()?,
+        };
+        Ok(PooledConnection {
+            path: self.path.clone(),
+            conn: Some(conn),
+        })
+    }
+
+    fn open(&self) -> Result<Connection> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(&self.path)?;
+        conn.busy_timeout(Duration::from_secs(10))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(conn)
+    }
+
+    fn read_vault(conn: &Connection) -> Result<Vault> {
+        let data: Option<String> = conn
+            .query_row("SELECT data FROM vault WHERE id = 0", [], |row| row.get(0))
+            .optional()?;
+        match data {
+            Some(data) => decode(&data, StateFile::Vault)
+                .map_err(|e| RepomanError::VaultLoadError(e.to_string())),
+            None => Ok(Vault::default()),
+        }
+    }
+
+    fn write_vault(conn: &Connection, vault: &Vault) -> Result<()> {
+        let data = encode_vault(vault)?;
+        conn.execute(
+            "INSERT INTO vault (id, data) VALUES (0, ?1)
+             ON CONFLICT(id) DO UPDATE SET data = excluded.data",
+            params![data],
+        )?;
+        Ok(())
+    }
+
+    fn read_metadata(conn: &Connection, repo_name: &str) -> Result<Option<Metadata>> {
+        let data: Option<String> = conn
+            .query_row(
+                "SELECT data FROM metadata WHERE repo = ?1",
+                params![repo_name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        data.map(|d| {
+            decode(&d, StateFile::Metadata)
+                .map_err(|e| RepomanError::MetadataLoadError(repo_name.to_string(), e.to_string()))
+        })
+        .transpose()
+    }
+
+    fn write_metadata(tx: &Transaction<'_>, repo_name: &str, metadata: &Metadata) -> Result<()> {
+        let data = encode_metadata(repo_name, metadata)?;
+        tx.execute(
+            "INSERT INTO metadata (repo, data) VALUES (?1, ?2)
+             ON CONFLICT(repo) DO UPDATE SET data = excluded.data",
+            params![repo_name, data],
+        )?;
+        tx.execute("DELETE FROM clones WHERE repo = ?1", params![repo_name])?;
+        for clone in &metadata.clones {
+            tx.execute(
+                "INSERT INTO clones (repo, name, path) VALUES (?1, ?2, ?3)",
+                params![repo_name, clone.name, clone.path.to_string_lossy()],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn not_found(repo_name: &str) -> RepomanError {
+        RepomanError::MetadataLoadError(repo_name.to_string(), "File not found".to_string())
+    }
+}
+
+/// Parse a stored document, applying any pending schema migrations in memory.
+/// The upgraded form is persisted the next time the document is saved.
+fn decode<T: serde::de::DeserializeOwned>(data: &str, kind: StateFile) -> serde_json::Result<T> {
+    let mut value: serde_json::Value = serde_json::from_str(data)?;
+    if schema::is_outdated(kind, &value) {
+        schema::migrate(kind, &mut value);
+    }
+    serde_json::from_value(value)
+}
+
+impl StateStore for SqliteStore {
+    fn load_vault(&self) -> Result<Vault> {
+        let conn = self.connect()?;
+        Self::read_vault(&conn)
+    }
+
+    fn save_vault(&self, vault: &Vault) -> Result<()> {
+        let conn = self.connect()?;
+        Self::write_vault(&conn, vault)
+    }
+
+    fn update_vault(&self, f: &mut dyn FnMut(&mut Vault) -> Result<()>) -> Result<()> {
+        let mut conn = self.connect()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let mut vault = Self::read_vault(&tx)?;
+        f(&mut vault)?;
+        Self::write_vault(&tx, &vault)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_metadata(&self, repo_name: &str) -> Result<Metadata> {
+        let conn = self.connect()?;
+        Self::read_metadata(&conn, repo_name)?.ok_or_else(|| Self::not_found(repo_name))
+    }
+
+    fn save_metadata(&self, repo_name: &str, metadata: &Metadata) -> Result<()> {
+        let mut conn = self.connect()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        Self::write_metadata(&tx, repo_name, metadata)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn update_metadata(
+        &self,
+        repo_name: &str,
+        f: &mut dyn FnMut(&mut Metadata) -> Result<()>,
+    ) -> Result<()> {
+        let mut conn = self.connect()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let mut metadata =
+            Self::read_metadata(&tx, repo_name)?.ok_or_else(|| Self::not_found(repo_name))?;
+        f(&mut metadata)?;
+        Self::write_metadata(&tx, repo_name, &metadata)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn update_metadata_many(
+        &self,
+        repo_names: &[String],
+        f: &mut dyn FnMut(&str, &mut Metadata) -> Result<()>,
+    ) -> Result<()> {
+        let mut conn = self.connect()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        for name in repo_names {
+            if let Some(mut metadata) = Self::read_metadata(&tx, name)? {
+                f(name, &mut metadata)?;
+                Self::write_metadata(&tx, name, &metadata)?;
+            }
+        }
+        // Dropping the transaction on error rolls everything back
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn remove_metadata(&self, repo_name: &str) -> Result<()> {
+        let conn = self.connect()?;
+        conn.execute("DELETE FROM metadata WHERE repo = ?1", params![repo_name])?;
+        Ok(())
+    }
+
+    fn load_all_metadata(&self) -> Result<HashMap<String, Metadata>> {
+        let conn = self.connect()?;
+        let mut stmt = conn.prepare("SELECT repo, data FROM metadata")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut all = HashMap::new();
+        for row in rows {
+            let (repo, data) = row?;
+            match decode(&data, StateFile::Metadata) {
+                Ok(m) => {
+                    all.insert(repo, m);
+                }
+                Err(e) => log::warn!("store: skipping metadata for '{}': {}", repo, e),
+            }
+        }
+        Ok(all)
+    }
+
+    fn raw_documents(&self) -> Result<Vec<RawDocument>> {
+        let conn = self.connect()?;
+        let mut docs = Vec::new();
+
+        let vault: Option<String> = conn
+            .query_row("SELECT data FROM vault WHERE id = 0", [], |row| row.get(0))
+            .optional()?;
+        if let Some(data) = vault {
+            docs.push(RawDocument {
+                location: format!("{}:vault", self.path.display()),
+                repo: None,
+                value: serde_json::from_str(&data)?,
+            });
+        }
+
+        let mut stmt = conn.prepare("SELECT repo, data FROM metadata ORDER BY repo")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+            let (repo, data) = row?;
+            docs.push(RawDocument {
+                location: format!("{}:metadata/{}", self.path.display(), repo),
+                repo: Some(repo),
+                value: serde_json::from_str(&data)?,
+            });
+        }
+        Ok(docs)
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn find_clone_owner(&self, clone_name: &str) -> Result<Option<String>> {
+        let conn = self.connect()?;
+        let owner = conn
+            .query_row(
+                "SELECT repo FROM clones WHERE name = ?1 ORDER BY repo LIMIT 1",
+                params![clone_name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(owner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn store() -> (tempfile::TempDir, SqliteStore) {
+        let temp = tempfile::tempdir().unwrap();
+        let store = SqliteStore::new(temp.path().join("state.db"));
+        (temp, store)
+    }
+
+    #[test]
+    fn test_sqlite_vault_roundtrip() {
+        let (_temp, store) = store();
+        assert!(store.load_vault().unwrap().entries.is_empty());
+
+        store
+            .update_vault(&mut |v| v.add_entry("repo1".to_string(), "url1".to_string()))
+            .unwrap();
+        let vault = store.load_vault().unwrap();
+        assert!(vault.contains("repo1"));
+        assert_eq!(vault.schema_version, schema::VAULT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_sqlite_metadata_and_clone_index() {
+        let (_temp, store) = store();
+        let mut metadata = Metadata::new(vec!["url1".to_string()]);
+        metadata.add_clone("fix".to_string(), PathBuf::from("/tmp/repo1-fix"));
+        store.save_metadata("repo1", &metadata).unwrap();
+
+        assert_eq!(
+            store.find_clone_owner("fix").unwrap().as_deref(),
+            Some("repo1")
+        );
+
+        store
+            .update_metadata("repo1", &mut |m| {
+                m.remove_clone("fix");
+                Ok(())
+            })
+            .unwrap();
+        assert!(store.find_clone_owner("fix").unwrap().is_none());
+        assert!(store.load_metadata("missing").is_err());
+    }
+
+    #[test]
+    fn test_sqlite_update_many_rolls_back_on_error() {
+        let (_temp, store) = store();
+        for name in ["a", "b"] {
+            store
+                .save_metadata(name, &Metadata::new(vec!["url".to_string()]))
+                .unwrap();
+        }
+
+        let names = vec!["a".to_string(), "b".to_string()];
+        let result = store.update_metadata_many(&names, &mut |name, m| {
+            if name == "b" {
+                return Err(RepomanError::Other("boom".to_string()));
+            }
+            m.latest_tag = Some("v1".to_string());
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert!(store.load_metadata("a").unwrap().latest_tag.is_none());
+    }
+
+    #[test]
+    fn test_sqlite_reuses_connections() {
+        let (_temp, store) = store();
+        let idle = || pool().get(&store.path).map_or(0, Vec::len);
+        store.load_vault().unwrap();
+        assert_eq!(idle(), 1);
+        for _ in 0..3 {
+            store.load_all_metadata().unwrap();
+        }
+        assert_eq!(idle(), 1);
+
+        // A nested call while a transaction is open gets its own connection
+        store
+            .update_vault(&mut |v| {
+                assert!(store.load_vault().unwrap().entries.is_empty());
+                v.add_entry("repo1".to_string(), "url1".to_string())
+            })
+            .unwrap();
+        assert_eq!(idle(), 2);
+        assert!(store.load_vault().unwrap().contains("repo1"));
+    }
+
+    #[test]
+    fn test_sqlite_remove_metadata_cascades_clones() {
+        let (_temp, store) = store();
+        let mut metadata = Metadata::new(vec!["url1".to_string()]);
+        metadata.add_clone("c1".to_string(), PathBuf::from("/tmp/c1"));
+        store.save_metadata("repo1", &metadata).unwrap();
+
+        store.remove_metadata("repo1").unwrap();
+        assert!(store.load_all_metadata().unwrap().is_empty());
+        assert!(store.find_clone_owner("c1").unwrap().is_none());
+    }
+}
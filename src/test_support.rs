@@ -0,0 +1,55 @@
+//! Fixtures shared by the unit tests
+
+use git2::{Oid, Repository, Signature};
+use std::path::Path;
+use tempfile::TempDir;
+
+use crate::config::Config;
+
+/// Config rooted in a fresh temp dir, with the vault, pristines, clones and
+/// logs dirs already created
+pub fn create_test_config() -> (TempDir, Config) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = test_config_in(temp_dir.path());
+    (temp_dir, config)
+}
+
+/// Same as `create_test_config`, rooted in a dir the caller owns
+pub fn test_config_in(base: &Path) -> Config {
+    let config = Config {
+        vault_dir: base.join("vault"),
+        pristines_dir: base.join("pristines"),
+        clones_dir: base.join("clones"),
+        plugins_dir: base.join("plugins"),
+        logs_dir: base.join("logs"),
+        ..Config::default()
+    };
+    std::fs::create_dir_all(&config.vault_dir).unwrap();
+    std::fs::create_dir_all(&config.pristines_dir).unwrap();
+    std::fs::create_dir_all(&config.clones_dir).unwrap();
+    std::fs::create_dir_all(&config.logs_dir).unwrap();
+    config
+}
+
+/// Commit `file` (containing its own name) onto HEAD with the given parents
+pub fn commit(repo: &Repository, file: &str, parents: &[Oid]) -> Oid {
+    std::fs::write(repo.workdir().unwrap().join(file), file).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(file)).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Test", "test@test.com").unwrap();
+    let parents: Vec<git2::Commit> = parents
+        .iter()
+        .map(|p| repo.find_commit(*p).unwrap())
+        .collect();
+    let parents: Vec<&git2::Commit> = parents.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, file, &tree, &parents)
+        .unwrap()
+}
+
+/// `commit` on top of the current HEAD, or as the root commit
+pub fn commit_on_head(repo: &Repository, file: &str) -> Oid {
+    let head = repo.head().ok().and_then(|h| h.target());
+    commit(repo, file, head.as_slice())
+}
@@ -0,0 +1,298 @@
+//! Process-wide limits on pristine fetches: how many may run against one host at
+//! a time, and a token bucket shared by every libgit2 download. Both only
+//! coordinate fetches within one repoman process (the agent, or one bulk command).
+//! Fetches run by the git CLI can't be metered and only take a host permit.
+
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Condvar, LazyLock, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::metadata::Metadata;
+use crate::vault::normalize_url;
+
+static HOSTS: LazyLock<(Mutex<HashMap<String, usize>>, Condvar)> =
+    LazyLock::new(|| (Mutex::new(HashMap::new()), Condvar::new()));
+
+static BUCKET: Mutex<Option<TokenBucket>> = Mutex::new(None);
+
+static UNMETERED_WARNED: LazyLock<Mutex<HashSet<String>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Host a fetch URL talks to (`None` for local paths and `file://` URLs)
+pub fn host_of(url: &str) -> Option<String> {
+    let normalized = normalize_url(url);
+    let (host, _) = normalized.split_once('/')?;
+    let is_local = url.starts_with("file://")
+        || host.is_empty()
+        || host == "."
+        || host == ".."
+        || host.contains('\\');
+    (!is_local).then(|| host.to_string())
+}
+
+/// A fetch slot on one host, released on drop
+#[derive(Debug)]
+pub struct HostPermit {
+    host: Option<String>,
+}
+
+impl Drop for HostPermit {
+    fn drop(&mut self) {
+        let Some(ref host) = self.host else {
+            return;
+        };
+        let (active, released) = &*HOSTS;
+        let mut active = lock(active);
+        if let Some(count) = active.get_mut(host) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                active.remove(host);
+            }
+        }
+        released.notify_all();
+    }
+}
+
+/// Wait for a fetch slot on `url`'s host under `network.max_per_host` /
+/// `network.hosts`. URLs without a host, or hosts without a limit, never wait.
+pub fn host_permit(url: &str, config: &Config) -> HostPermit {
+    let Some((host, limit)) = host_of(url).and_then(|h| config.host_limit(&h).map(|l| (h, l)))
+    else {
+        return HostPermit { host: None };
+    };
+    let (active, released) = &*HOSTS;
+    let mut active = lock(active);
+    while active.get(&host).copied().unwrap_or(0) >= limit {
+        debug!(
+            "host_permit: waiting for a slot on {} (limit {})",
+            host, limit
+        );
+        active = released
+            .wait(active)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+    *active.entry(host.clone()).or_insert(0) += 1;
+    HostPermit { host: Some(host) }
+}
+
+/// Host of a repo's default URL and its fetch limit, for grouping bulk fetches
+/// with `util::run_parallel_keyed` (`None` when the host isn't limited)
+pub fn repo_host_limit(repo_name: &str, config: &Config) -> Option<(String, usize)> {
+    let metadata = Metadata::load(repo_name, config).ok()?;
+    let host = metadata.default_url().and_then(host_of)?;
+    let limit = config.host_limit(&host)?;
+    Some((host, limit))
+}
+
+/// Apply `network.max_download_rate` to the downloads that follow
+pub fn configure(config: &Config) -> Result<()> {
+    let rate = config.download_rate_limit()?;
+    let mut bucket = lock(&BUCKET);
+    match rate {
+        Some(rate) if bucket.as_ref().is_none_or(|b| b.rate != rate) => {
+            *bucket = Some(TokenBucket::new(rate));
+        }
+        Some(_) => {}
+        None => *bucket = None,
+    }
+    Ok(())
+}
+
+/// Warn (once per repo and process) that `network.max_download_rate` doesn't
+/// hold `repo_name`'s fetch: the git CLI downloads without going through the bucket
+pub fn warn_unmetered(repo_name: &str, config: &Config) {
+    if !matches!(config.download_rate_limit(), Ok(Some(_))) {
+        return;
+    }
+    if lock(&UNMETERED_WARNED).insert(repo_name.to_string()) {
+        warn!(
+            "'{}' is fetched with the git CLI, which network.max_download_rate doesn't limit",
+            repo_name
+        );
+    }
+}
+
+/// Tracks one fetch's cumulative byte count and charges what's new to the
+/// shared bucket, sleeping while the ceiling is exceeded
+#[derive(Debug, Default)]
+pub struct DownloadMeter {
+    seen: usize,
+}
+
+impl DownloadMeter {
+    /// Feed libgit2's `received_bytes` from a transfer progress callback
+    pub fn record(&mut self, received_bytes: usize) {
+        let delta = received_bytes.saturating_sub(self.seen) as u64;
+        self.seen = self.seen.max(received_bytes);
+        if delta == 0 {
+            return;
+        }
+        let wait = match lock(&BUCKET).as_mut() {
+            Some(bucket) => bucket.take(delta, Instant::now()),
+            None => return,
+        };
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// Bytes-per-second budget with one second of burst
+#[derive(Debug)]
+struct TokenBucket {
+    rate: u64,
+    /// Negative while fetches owe time to the bucket
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            tokens: rate as f64,
+            refilled: Instant::now(),
+        }
+    }
+
+    /// Spend `bytes` and return how long the caller must wait to stay under the rate
+    fn take(&mut self, bytes: u64, now: Instant) -> Duration {
+        let rate = self.rate.max(1) as f64;
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(rate);
+        self.refilled = now;
+        self.tokens -= bytes as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NetworkConfig;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(
+            host_of("git@GitLab.example.com:team/app.git").as_deref(),
+            Some("gitlab.example.com")
+        );
+        assert_eq!(
+            host_of("https://github.com/user/repo").as_deref(),
+            Some("github.com")
+        );
+        assert_eq!(host_of("/srv/git/repo.git"), None);
+        assert_eq!(host_of("file:///srv/git/repo.git"), None);
+    }
+
+    #[test]
+    fn test_token_bucket_waits_for_debt() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(1000);
+        bucket.refilled = start;
+        // The first second's worth is free, then it waits for the overdraft
+        assert_eq!(bucket.take(1000, start), Duration::ZERO);
+        assert_eq!(bucket.take(500, start), Duration::from_millis(500));
+        // Refilled after the wait, capped at one second of burst
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.take(1000, later), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_host_permit_caps_concurrency() {
+        let config = Config {
+            network: Some(NetworkConfig {
+                max_per_host: Some(4),
+                hosts: Some(HashMap::from([("throttle-test.example".to_string(), 2)])),
+                ..NetworkConfig::default()
+            }),
+            ..Config::default()
+        };
+        assert_eq!(config.host_limit("throttle-test.example"), Some(2));
+        assert_eq!(config.host_limit("other.example"), Some(4));
+
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..6 {
+                let (running, peak, config) = (&running, &peak, &config);
+                s.spawn(move || {
+                    let _permit = host_permit("https://throttle-test.example/a/b.git", config);
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_capped_host_leaves_parallel_slots_free() {
+        let config = Config {
+            network: Some(NetworkConfig {
+                hosts: Some(HashMap::from([("capped-test.example".to_string(), 1)])),
+                ..NetworkConfig::default()
+            }),
+            ..Config::default()
+        };
+        let url_of = |name: &str| {
+            let host = name.split('-').next().unwrap_or_default();
+            format!("https://{host}-test.example/{name}.git")
+        };
+
+        // Capped repos are queued first so they would take every slot
+        let names: Vec<String> = (0..4)
+            .map(|i| format!("capped-{i}"))
+            .chain((0..2).map(|i| format!("free-{i}")))
+            .collect();
+        let host_key = |name: &str| {
+            let host = host_of(&url_of(name))?;
+            let limit = config.host_limit(&host)?;
+            Some((host, limit))
+        };
+
+        let running = std::sync::Arc::new(AtomicUsize::new(0));
+        let peak = std::sync::Arc::new(AtomicUsize::new(0));
+        let finished = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let (op_config, op_running, op_peak, op_finished) = (
+            config.clone(),
+            running.clone(),
+            peak.clone(),
+            finished.clone(),
+        );
+        crate::util::run_parallel_keyed(names, 2, host_key, move |name| {
+            let _permit = host_permit(&url_of(name), &op_config);
+            let capped = name.starts_with("capped");
+            if capped {
+                let now = op_running.fetch_add(1, Ordering::SeqCst) + 1;
+                op_peak.fetch_max(now, Ordering::SeqCst);
+            }
+            std::thread::sleep(Duration::from_millis(40));
+            if capped {
+                op_running.fetch_sub(1, Ordering::SeqCst);
+            }
+            op_finished.lock().unwrap().push(name.to_string());
+        })
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+        let finished = finished.lock().unwrap();
+        let last_free = finished.iter().rposition(|n| n.starts_with("free"));
+        let last_capped = finished.iter().rposition(|n| n.starts_with("capped"));
+        assert!(last_free < last_capped, "finish order: {finished:?}");
+    }
+}
@@ -1,9 +1,15 @@
 use chrono::{DateTime, Utc};
+use log::debug;
+use std::collections::HashMap;
 use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
 use std::sync::Arc;
 use strsim::jaro_winkler;
 use tokio::sync::Semaphore;
 
+use crate::error::RepomanError;
+
 /// Convert a datetime to a human-readable relative string like "2h ago", "3 days ago", "just now".
 pub fn relative_time(dt: &DateTime<Utc>) -> String {
     let now = Utc::now();
@@ -51,6 +57,72 @@ pub fn relative_time(dt: &DateTime<Utc>) -> String {
     format!("{} years ago", years)
 }
 
+/// Format a byte count as "512 B", "3.4 KiB", "1.2 MiB", "2.0 GiB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Parse a point in time given as an age (`30m`, `12h`, `7d`, `2w`), a date
+/// (`2025-06-01`, midnight UTC) or an RFC 3339 timestamp
+pub fn parse_since(spec: &str) -> crate::error::Result<DateTime<Utc>> {
+    let spec = spec.trim();
+    let invalid = || RepomanError::InvalidTimeSpec(spec.to_string());
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(spec) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(spec, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?;
+        return Ok(midnight.and_utc());
+    }
+
+    let split = spec
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = spec.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let age = match unit {
+        "m" => chrono::Duration::try_minutes(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "d" => chrono::Duration::try_days(amount),
+        "w" => chrono::Duration::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    Ok(Utc::now() - age)
+}
+
+/// Parse a byte count with an optional binary suffix (`512`, `500k`, `5M`, `1g`)
+pub fn parse_byte_size(spec: &str) -> crate::error::Result<u64> {
+    let spec = spec.trim();
+    let invalid = || RepomanError::InvalidByteSize(spec.to_string());
+    let (digits, multiplier) = match spec.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&spec[..i], 1 << 10),
+        Some((i, 'm' | 'M')) => (&spec[..i], 1 << 20),
+        Some((i, 'g' | 'G')) => (&spec[..i], 1 << 30),
+        _ => (spec, 1),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(invalid)
+}
+
 /// Print a prompt to stderr and read y/n from stdin. Returns true for y/Y, false otherwise.
 /// If stdin is not a tty, returns true (non-interactive mode).
 pub fn confirm(prompt: &str) -> bool {
@@ -92,16 +164,46 @@ pub async fn run_parallel<R, F>(
 where
     R: Send + 'static,
     F: Fn(&str) -> R + Send + Sync + 'static,
+{
+    run_parallel_keyed(names, max_concurrent, |_| None, op).await
+}
+
+/// `run_parallel` where items sharing a key (e.g. a host) also share that
+/// key's own limit. An item waits for its key's slot before taking one of the
+/// `max_concurrent` global slots, so items held back by their key don't keep
+/// other items from running.
+pub async fn run_parallel_keyed<R, K, F>(
+    names: Vec<String>,
+    max_concurrent: usize,
+    key: K,
+    op: F,
+) -> Vec<(String, Result<R, String>)>
+where
+    R: Send + 'static,
+    K: Fn(&str) -> Option<(String, usize)>,
+    F: Fn(&str) -> R + Send + Sync + 'static,
 {
     let semaphore = Arc::new(Semaphore::new(max_concurrent));
+    let mut key_semaphores: HashMap<String, Arc<Semaphore>> = HashMap::new();
     let op = Arc::new(op);
 
     let mut handles = Vec::with_capacity(names.len());
 
     for name in names {
+        let key_sem = key(&name).map(|(k, limit)| {
+            Arc::clone(
+                key_semaphores
+                    .entry(k)
+                    .or_insert_with(|| Arc::new(Semaphore::new(limit.max(1)))),
+            )
+        });
         let sem = Arc::clone(&semaphore);
         let op = Arc::clone(&op);
         let handle = tokio::spawn(async move {
+            let _key_permit = match key_sem {
+                Some(ref s) => Some(s.acquire().await.expect("semaphore closed")),
+                None => None,
+            };
             let _permit = sem.acquire().await.expect("semaphore closed");
             let n = name.clone();
             let result = tokio::task::spawn_blocking(move || {
@@ -127,6 +229,28 @@ where
     results
 }
 
+/// `fs::remove_dir_all` that spares the entries of `dir` named in `keep`: the
+/// directories of namespaced repos (`alice/utils`) nested in `alice`'s. `dir`
+/// itself goes only when nothing was kept.
+pub fn remove_dir_keeping(dir: &Path, keep: &[&str]) -> io::Result<()> {
+    if keep.is_empty() {
+        return std::fs::remove_dir_all(dir);
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if keep.iter().any(|k| entry.file_name() == **k) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
 /// Truncate a string with "..." if longer than max characters.
 pub fn truncate(s: &str, max: usize) -> String {
     if s.len() <= max {
@@ -137,3 +261,24 @@ pub fn truncate(s: &str, max: usize) -> String {
         format!("{}...", &s[..max - 3])
     }
 }
+
+/// Run a git CLI command and return its stdout; a non-zero exit becomes
+/// `GitCommandFailed`. Used where libgit2 falls short (partial clones, sparse checkout).
+pub fn run_git(args: &[&str]) -> crate::error::Result<String> {
+    debug!("run_git: git {}", args.join(" "));
+    let output = Command::new("git").args(args).output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Name the subcommand, not the `-C <path>` prefix
+        let subcommand = match args {
+            ["-C", _, sub, ..] | [sub, ..] => *sub,
+            [] => "",
+        };
+        Err(RepomanError::GitCommandFailed(
+            subcommand.to_string(),
+            stderr.trim().to_string(),
+        ))
+    }
+}
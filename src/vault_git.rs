@@ -0,0 +1,420 @@
+//! Git-backed vault directory.
+//!
+//! When `vault_dir` is a git repository (see `repoman vault init`), every
+//! journaled change commits `vault.json` so the vault can be pushed to and
+//! pulled from a shared remote. Only `vault.json` is tracked: metadata, the
+//! journal and lock files describe this machine's pristines and clones.
+//!
+//! Pulling merges `vault.json` semantically rather than textually. Entries are
+//! matched by URL and `added_date`, which survive renames, and each field is
+//! merged three-way against the common ancestor.
+
+use git2::{Commit, IndexAddOption, Oid, Repository, Signature};
+use log::{debug, info};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::schema::{self, StateFile};
+use crate::vault::{Vault, VaultEntry};
+
+/// Tracked file names, relative to `vault_dir`.
+const VAULT_FILE: &str = "vault.json";
+const GITIGNORE: &str = ".gitignore";
+const GITIGNORE_CONTENTS: &str = "*\n!vault.json\n!.gitignore\n";
+
+/// Whether `vault_dir` is a git repository that vault changes are committed to.
+pub fn is_enabled(config: &Config) -> bool {
+    config.vault_dir.join(".git").exists()
+}
+
+/// Turn `vault_dir` into a git repository tracking only `vault.json`.
+/// Safe to run on an existing repository.
+pub fn init_repo(config: &Config) -> Result<Repository> {
+    std::fs::create_dir_all(&config.vault_dir)?;
+    let repo = if is_enabled(config) {
+        Repository::open(&config.vault_dir)?
+    } else {
+        info!("vault_git: initializing {}", config.vault_dir.display());
+        Repository::init(&config.vault_dir)?
+    };
+
+    let gitignore = config.vault_dir.join(GITIGNORE);
+    if !gitignore.exists() {
+        std::fs::write(&gitignore, GITIGNORE_CONTENTS)?;
+    }
+    Ok(repo)
+}
+
+/// Commit `vault.json` if it differs from HEAD. Returns the new commit, if any.
+/// Does nothing when the vault directory isn't git-backed.
+pub fn commit_if_changed(config: &Config, message: &str) -> Result<Option<Oid>> {
+    if !is_enabled(config) {
+        return Ok(None);
+    }
+    let repo = Repository::open(&config.vault_dir)?;
+    let tree_id = stage_vault(&repo)?;
+
+    let head = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    if head.as_ref().is_some_and(|c| c.tree_id() == tree_id) {
+        return Ok(None);
+    }
+
+    let parents: Vec<&Commit> = head.iter().collect();
+    let oid = commit_tree(&repo, tree_id, message, &parents)?;
+    debug!("vault_git: committed {} ({})", oid, message);
+    Ok(Some(oid))
+}
+
+/// Stage the tracked files and return the resulting tree.
+pub(crate) fn stage_vault(repo: &Repository) -> Result<Oid> {
+    let mut index = repo.index()?;
+    index.add_all([VAULT_FILE, GITIGNORE], IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    Ok(index.write_tree()?)
+}
+
+/// Create a commit of `tree_id` on HEAD with the given parents.
+pub(crate) fn commit_tree(
+    repo: &Repository,
+    tree_id: Oid,
+    message: &str,
+    parents: &[&Commit],
+) -> Result<Oid> {
+    let tree = repo.find_tree(tree_id)?;
+    let sig = repo
+        .signature()
+        .or_else(|_| Signature::now("repoman", "repoman@localhost"))?;
+    Ok(repo.commit(Some("HEAD"), &sig, &sig, message, &tree, parents)?)
+}
+
+/// Read the vault as it was in `commit` (empty if the commit has no `vault.json`).
+pub(crate) fn vault_at(repo: &Repository, commit: &Commit) -> Result<Vault> {
+    let Ok(entry) = commit.tree()?.get_path(Path::new(VAULT_FILE)) else {
+        return Ok(Vault::default());
+    };
+    let blob = repo.find_blob(entry.id())?;
+    let mut value: Value = serde_json::from_slice(blob.content())?;
+    if schema::is_outdated(StateFile::Vault, &value) {
+        schema::migrate(StateFile::Vault, &mut value);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Result of a three-way vault merge
+#[derive(Debug, Default)]
+pub struct VaultMerge {
+    pub vault: Vault,
+    /// Human-readable notes about changes that could not be merged cleanly.
+    /// The local side wins in every case listed here.
+    pub conflicts: Vec<String>,
+    /// Local entries whose name changed in the merge, as `(old, new)`.
+    pub renamed: Vec<(String, String)>,
+    /// Local entries that were removed in the merge.
+    pub removed: Vec<String>,
+}
+
+/// Identity of an entry across machines: stable under renames and edits.
+fn entry_key(entry: &VaultEntry) -> String {
+    format!("{} @ {}", entry.url, entry.added_date.to_rfc3339())
+}
+
+fn index_entries(vault: &Vault) -> HashMap<String, &VaultEntry> {
+    vault.entries.iter().map(|e| (entry_key(e), e)).collect()
+}
+
+fn same_entry(a: &VaultEntry, b: &VaultEntry) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Merge one entry field by field against its ancestor.
+fn merge_entry(
+    base: &VaultEntry,
+    ours: &VaultEntry,
+    theirs: &VaultEntry,
+    conflicts: &mut Vec<String>,
+) -> VaultEntry {
+    let (Ok(Value::Object(base)), Ok(Value::Object(mut merged)), Ok(Value::Object(theirs))) = (
+        serde_json::to_value(base),
+        serde_json::to_value(ours),
+        serde_json::to_value(theirs),
+    ) else {
+        return ours.clone();
+    };
+
+    for (field, their_value) in theirs {
+        let base_value = base.get(&field).unwrap_or(&Value::Null);
+        let our_value = merged.get(&field).unwrap_or(&Value::Null);
+        if *our_value == their_value || their_value == *base_value {
+            continue;
+        }
+        if our_value == base_value {
+            merged.insert(field, their_value);
+        } else {
+            conflicts.push(format!(
+                "'{}': '{}' changed on both sides, kept local value",
+                ours.name, field
+            ));
+        }
+    }
+    serde_json::from_value(Value::Object(merged)).unwrap_or_else(|_| ours.clone())
+}
+
+/// Three-way merge of vault contents. Additions from both sides are kept,
+/// renames and field edits are applied per field, and removals win only over
+/// unchanged entries. Aliases merge by alias name and follow renamed targets.
+pub fn merge_vaults(base: &Vault, ours: &Vault, theirs: &Vault) -> VaultMerge {
+    let base_idx = index_entries(base);
+    let our_idx = index_entries(ours);
+    let their_idx = index_entries(theirs);
+    let mut conflicts = Vec::new();
+
+    // Local order first, then entries that only exist remotely
+    let mut keys: Vec<String> = ours.entries.iter().map(entry_key).collect();
+    for entry in &theirs.entries {
+        let key = entry_key(entry);
+        if !our_idx.contains_key(&key) {
+            keys.push(key);
+        }
+    }
+
+    let mut merged: Vec<(String, VaultEntry)> = Vec::new();
+    for key in keys {
+        let entry = match (base_idx.get(&key), our_idx.get(&key), their_idx.get(&key)) {
+            (Some(b), Some(o), Some(t)) => merge_entry(b, o, t, &mut conflicts),
+            (None, Some(o), _) | (None, None, Some(o)) => (*o).clone(),
+            (Some(b), Some(o), None) => {
+                if same_entry(b, o) {
+                    continue;
+                }
+                conflicts.push(format!(
+                    "'{}': removed remotely but changed locally, kept it",
+                    o.name
+                ));
+                (*o).clone()
+            }
+            (Some(b), None, Some(t)) => {
+                if same_entry(b, t) {
+                    continue;
+                }
+                conflicts.push(format!(
+                    "'{}': removed locally but changed remotely, kept it",
+                    t.name
+                ));
+                (*t).clone()
+            }
+            (_, None, None) => continue,
+        };
+        merged.push((key, entry));
+    }
+
+    // Two different entries may now share a name (e.g. added on both machines)
+    let mut seen: HashMap<String, String> = HashMap::new();
+    let mut entries = Vec::new();
+    for (key, mut entry) in merged {
+        if let Some(existing_url) = seen.get(&entry.name) {
+            if *existing_url == entry.url {
+                conflicts.push(format!(
+                    "'{}': added on both sides, kept local entry",
+                    entry.name
+                ));
+                continue;
+            }
+            let mut candidate = format!("{}-remote", entry.name);
+            let mut n = 2;
+            while seen.contains_key(&candidate) {
+                candidate = format!("{}-remote{}", entry.name, n);
+                n += 1;
+            }
+            conflicts.push(format!(
+                "'{}': name taken by a different URL, incoming entry renamed to '{}'",
+                entry.name, candidate
+            ));
+            entry.name = candidate;
+        }
+        seen.insert(entry.name.clone(), entry.url.clone());
+        entries.push((key, entry));
+    }
+
+    // Map every name an entry has had on any side to its merged name
+    let final_names: HashMap<&str, &str> = entries
+        .iter()
+        .map(|(key, e)| (key.as_str(), e.name.as_str()))
+        .collect();
+    let mut name_map: HashMap<String, String> = HashMap::new();
+    for side in [theirs, base, ours] {
+        for entry in &side.entries {
+            if let Some(name) = final_names.get(entry_key(entry).as_str()) {
+                name_map.insert(entry.name.clone(), (*name).to_string());
+            }
+        }
+    }
+
+    let aliases = merge_aliases(base, ours, theirs, &name_map, &seen, &mut conflicts);
+
+    let renamed = ours
+        .entries
+        .iter()
+        .filter_map(|e| {
+            let new = final_names.get(entry_key(e).as_str())?;
+            (*new != e.name).then(|| (e.name.clone(), (*new).to_string()))
+        })
+        .collect();
+    let removed = ours
+        .entries
+        .iter()
+        .filter(|e| !final_names.contains_key(entry_key(e).as_str()))
+        .map(|e| e.name.clone())
+        .collect();
+
+    VaultMerge {
+        vault: Vault {
+            schema_version: ours.schema_version,
+            entries: entries.into_iter().map(|(_, e)| e).collect(),
+            aliases,
+        },
+        conflicts,
+        renamed,
+        removed,
+    }
+}
+
+fn merge_aliases(
+    base: &Vault,
+    ours: &Vault,
+    theirs: &Vault,
+    renames: &HashMap<String, String>,
+    names: &HashMap<String, String>,
+    conflicts: &mut Vec<String>,
+) -> HashMap<String, String> {
+    // Compare targets by their merged name so a rename alone isn't an alias edit
+    let normalize = |aliases: &HashMap<String, String>| -> BTreeMap<String, String> {
+        aliases
+            .iter()
+            .map(|(a, t)| (a.clone(), renames.get(t).unwrap_or(t).clone()))
+            .collect()
+    };
+    let (base, ours, theirs) = (
+        normalize(&base.aliases),
+        normalize(&ours.aliases),
+        normalize(&theirs.aliases),
+    );
+
+    let mut merged = HashMap::new();
+    for alias in ours.keys().chain(theirs.keys()) {
+        if merged.contains_key(alias) {
+            continue;
+        }
+        let target = match (base.get(alias), ours.get(alias), theirs.get(alias)) {
+            (_, Some(o), Some(t)) if o == t => o,
+            (Some(b), Some(o), Some(t)) if o == b => t,
+            (Some(b), Some(o), Some(t)) if t == b => o,
+            (_, Some(o), Some(_)) => {
+                conflicts.push(format!(
+                    "alias '{}': retargeted on both sides, kept local target",
+                    alias
+                ));
+                o
+            }
+            (Some(b), Some(o), None) | (Some(b), None, Some(o)) => {
+                if o == b {
+                    continue;
+                }
+                o
+            }
+            (None, Some(o), None) | (None, None, Some(o)) => o,
+            (_, None, None) => continue,
+        };
+        if names.contains_key(target) && !names.contains_key(alias) {
+            merged.insert(alias.clone(), target.clone());
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(entries: &[(&str, &str)], aliases: &[(&str, &str)]) -> Vault {
+        let mut vault = Vault::default();
+        for (name, url) in entries {
+            vault
+                .add_entry((*name).to_string(), (*url).to_string())
+                .unwrap();
+        }
+        for (alias, target) in aliases {
+            vault
+                .add_alias((*alias).to_string(), (*target).to_string())
+                .unwrap();
+        }
+        vault
+    }
+
+    #[test]
+    fn test_merge_keeps_additions_from_both_sides() {
+        let base = vault(&[("a", "url-a")], &[]);
+        let mut ours = base.clone();
+        ours.add_entry("b".to_string(), "url-b".to_string())
+            .unwrap();
+        ours.add_alias("bb".to_string(), "b".to_string()).unwrap();
+        let mut theirs = base.clone();
+        theirs
+            .add_entry("c".to_string(), "url-c".to_string())
+            .unwrap();
+
+        let merge = merge_vaults(&base, &ours, &theirs);
+        let names: Vec<&str> = merge
+            .vault
+            .entries
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        assert_eq!(merge.vault.aliases.get("bb"), Some(&"b".to_string()));
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_remote_rename_with_local_edit() {
+        let base = vault(&[("old", "url")], &[("o", "old")]);
+        let mut ours = base.clone();
+        ours.entries[0].tags = vec!["work".to_string()];
+        let mut theirs = base.clone();
+        theirs.entries[0].name = "new".to_string();
+        theirs.aliases.insert("o".to_string(), "new".to_string());
+
+        let merge = merge_vaults(&base, &ours, &theirs);
+        let entry = &merge.vault.entries[0];
+        assert_eq!(entry.name, "new");
+        assert_eq!(entry.tags, vec!["work"]);
+        assert_eq!(merge.vault.aliases.get("o"), Some(&"new".to_string()));
+        assert_eq!(merge.renamed, vec![("old".to_string(), "new".to_string())]);
+        assert!(merge.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_removal_and_name_clash() {
+        let base = vault(&[("gone", "url-gone")], &[]);
+        let mut ours = base.clone();
+        ours.add_entry("x".to_string(), "url-1".to_string())
+            .unwrap();
+        let mut theirs = vault(&[], &[]);
+        theirs
+            .add_entry("x".to_string(), "url-2".to_string())
+            .unwrap();
+
+        let merge = merge_vaults(&base, &ours, &theirs);
+        let names: Vec<&str> = merge
+            .vault
+            .entries
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["x", "x-remote"]);
+        assert_eq!(merge.removed, vec!["gone"]);
+        assert_eq!(merge.conflicts.len(), 1);
+    }
+}
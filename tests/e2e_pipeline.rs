@@ -17,10 +17,7 @@ fn test_full_pipeline_local_repo() {
         clones_dir: base.join("clones"),
         plugins_dir: base.join("plugins"),
         logs_dir: base.join("logs"),
-        agent_heartbeat_interval: None,
-        json_output: None,
-        max_parallel: None,
-        repos: None,
+        ..Config::default()
     };
 
     // Create the directories that Config points to.
@@ -123,7 +120,7 @@ fn test_full_pipeline_local_repo() {
 
     // 4. init_pristine → verify pristine dir exists and is bare.
     let pristine_path =
-        operations::init_pristine(&name, None, &config).expect("init_pristine failed");
+        operations::init_pristine(&name, None, None, &config).expect("init_pristine failed");
 
     assert!(pristine_path.exists(), "pristine dir should exist");
     // A bare repo has a HEAD file directly in its root.
@@ -139,7 +136,7 @@ fn test_full_pipeline_local_repo() {
 
     // 5. clone_from_pristine → verify clone dir exists and has a working tree.
     let clone_path =
-        operations::clone_from_pristine(&name, Some("test".to_string()), None, &config)
+        operations::clone_from_pristine(&name, Some("test".to_string()), None, &[], false, &config)
             .expect("clone_from_pristine failed");
 
     assert!(clone_path.exists(), "clone dir should exist");
@@ -221,7 +218,7 @@ fn test_full_pipeline_local_repo() {
     );
 
     // 8. destroy_clone → verify clone removed.
-    operations::destroy_clone("test", &config).expect("destroy_clone failed");
+    operations::destroy_clone("test", false, &config).expect("destroy_clone failed");
     assert!(
         !clone_path.exists(),
         "clone dir should be removed after destroy_clone"